@@ -0,0 +1,7 @@
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_posts_public_id;
+DROP INDEX IF EXISTS idx_users_public_id;
+
+-- Drop public id columns
+ALTER TABLE posts DROP COLUMN IF EXISTS public_id;
+ALTER TABLE users DROP COLUMN IF EXISTS public_id;
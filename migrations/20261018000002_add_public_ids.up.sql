@@ -0,0 +1,7 @@
+-- Add public UUID identifiers, backfilling existing rows
+ALTER TABLE users ADD COLUMN IF NOT EXISTS public_id UUID NOT NULL DEFAULT gen_random_uuid();
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS public_id UUID NOT NULL DEFAULT gen_random_uuid();
+
+-- Create unique indexes for lookups by public id
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_public_id ON users(public_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_posts_public_id ON posts(public_id);
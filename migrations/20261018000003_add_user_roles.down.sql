@@ -0,0 +1,3 @@
+-- Drop role constraint and column
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE users DROP COLUMN IF EXISTS role;
@@ -0,0 +1,5 @@
+-- Drop index first
+DROP INDEX IF EXISTS idx_posts_search_vector;
+
+-- Drop full-text search column
+ALTER TABLE posts DROP COLUMN IF EXISTS search_vector;
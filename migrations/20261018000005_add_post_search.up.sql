@@ -0,0 +1,10 @@
+-- Add full-text search column, title words rank above content words.
+-- The 'simple' configuration does no stemming so Vietnamese text is indexed as written.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(content, '')), 'B')
+    ) STORED;
+
+-- Create index for full-text search
+CREATE INDEX IF NOT EXISTS idx_posts_search_vector ON posts USING GIN (search_vector);
@@ -0,0 +1,5 @@
+-- Drop index first
+DROP INDEX IF EXISTS idx_posts_user_id_created_at;
+
+-- Drop publication timestamp
+ALTER TABLE posts DROP COLUMN IF EXISTS published_at;
@@ -0,0 +1,8 @@
+-- Add publication timestamp, set while a post is published
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS published_at TIMESTAMPTZ;
+
+-- Backfill posts published before the column existed
+UPDATE posts SET published_at = created_at WHERE is_published AND published_at IS NULL;
+
+-- Create index for author listings
+CREATE INDEX IF NOT EXISTS idx_posts_user_id_created_at ON posts(user_id, created_at DESC);
@@ -0,0 +1,14 @@
+-- Drop post status history table
+DROP TABLE IF EXISTS post_status_history;
+
+-- Turn the published flag back into a plain column
+DROP INDEX IF EXISTS idx_posts_status;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS is_published_old BOOLEAN NOT NULL DEFAULT false;
+UPDATE posts SET is_published_old = (status = 'published');
+ALTER TABLE posts DROP COLUMN IF EXISTS is_published;
+ALTER TABLE posts RENAME COLUMN is_published_old TO is_published;
+CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(is_published) WHERE is_published = true;
+
+-- Drop status column
+ALTER TABLE posts DROP CONSTRAINT IF EXISTS posts_status_check;
+ALTER TABLE posts DROP COLUMN IF EXISTS status;
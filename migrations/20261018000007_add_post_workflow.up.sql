@@ -0,0 +1,36 @@
+-- Add editorial status to posts
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'draft';
+ALTER TABLE posts DROP CONSTRAINT IF EXISTS posts_status_check;
+ALTER TABLE posts ADD CONSTRAINT posts_status_check
+    CHECK (status IN ('draft', 'in_review', 'approved', 'published', 'archived'));
+
+-- Replace the published flag with one derived from the status
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'posts' AND column_name = 'is_published' AND is_generated = 'NEVER'
+    ) THEN
+        UPDATE posts SET status = 'published' WHERE is_published;
+        ALTER TABLE posts DROP COLUMN is_published;
+        ALTER TABLE posts ADD COLUMN is_published BOOLEAN NOT NULL
+            GENERATED ALWAYS AS (status = 'published') STORED;
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(is_published) WHERE is_published = true;
+CREATE INDEX IF NOT EXISTS idx_posts_status ON posts(status);
+
+-- Create post status history table
+CREATE TABLE IF NOT EXISTS post_status_history (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    from_status VARCHAR(20) NOT NULL,
+    to_status VARCHAR(20) NOT NULL,
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for history lookups
+CREATE INDEX IF NOT EXISTS idx_post_status_history_post_id ON post_status_history(post_id, created_at);
@@ -0,0 +1,5 @@
+-- Drop index first
+DROP INDEX IF EXISTS idx_posts_publish_at;
+
+-- Drop scheduled publication time
+ALTER TABLE posts DROP COLUMN IF EXISTS publish_at;
@@ -0,0 +1,5 @@
+-- Add scheduled publication time to posts
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS publish_at TIMESTAMPTZ;
+
+-- Create index for the publishing scheduler
+CREATE INDEX IF NOT EXISTS idx_posts_publish_at ON posts(publish_at) WHERE publish_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Drop post revisions table
+DROP TABLE IF EXISTS post_revisions;
+
+-- Drop revision number
+ALTER TABLE posts DROP COLUMN IF EXISTS revision;
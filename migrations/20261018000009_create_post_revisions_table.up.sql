@@ -0,0 +1,19 @@
+-- Add current revision number to posts
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS revision INTEGER NOT NULL DEFAULT 1;
+
+-- Create post revisions table
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    author_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (post_id, revision)
+);
+
+-- Record the current content of existing posts as their first revision
+INSERT INTO post_revisions (post_id, revision, title, content, author_id, created_at)
+SELECT id, revision, title, content, user_id, updated_at FROM posts
+ON CONFLICT (post_id, revision) DO NOTHING;
@@ -0,0 +1,4 @@
+-- Drop content format and cached HTML rendering from posts
+ALTER TABLE posts DROP CONSTRAINT IF EXISTS posts_content_format_check;
+ALTER TABLE posts DROP COLUMN IF EXISTS content_html;
+ALTER TABLE posts DROP COLUMN IF EXISTS content_format;
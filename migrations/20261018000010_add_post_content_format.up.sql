@@ -0,0 +1,8 @@
+-- Add content format to posts
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_format VARCHAR(20) NOT NULL DEFAULT 'plain';
+ALTER TABLE posts DROP CONSTRAINT IF EXISTS posts_content_format_check;
+ALTER TABLE posts ADD CONSTRAINT posts_content_format_check
+    CHECK (content_format IN ('plain', 'markdown'));
+
+-- Cached HTML rendering of the content, NULL until rendered
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_html TEXT;
@@ -0,0 +1,5 @@
+-- Drop post tags join table first
+DROP TABLE IF EXISTS post_tags;
+
+-- Drop tags table
+DROP TABLE IF EXISTS tags;
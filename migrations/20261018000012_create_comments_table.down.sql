@@ -0,0 +1,5 @@
+-- Drop comment count first
+ALTER TABLE posts DROP COLUMN IF EXISTS comment_count;
+
+-- Drop comments table
+DROP TABLE IF EXISTS comments;
@@ -0,0 +1,7 @@
+-- Drop counters first
+ALTER TABLE posts DROP COLUMN IF EXISTS bookmark_count;
+ALTER TABLE posts DROP COLUMN IF EXISTS reaction_counts;
+
+-- Drop bookmarks and reactions tables
+DROP TABLE IF EXISTS post_bookmarks;
+DROP TABLE IF EXISTS post_reactions;
@@ -0,0 +1,26 @@
+-- Create post reactions table, one reaction per user and post
+CREATE TABLE IF NOT EXISTS post_reactions (
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post_id, user_id)
+);
+
+ALTER TABLE post_reactions DROP CONSTRAINT IF EXISTS post_reactions_kind_check;
+ALTER TABLE post_reactions ADD CONSTRAINT post_reactions_kind_check
+    CHECK (kind IN ('like', 'love', 'insightful', 'funny', 'celebrate'));
+
+-- Create post bookmarks table
+CREATE TABLE IF NOT EXISTS post_bookmarks (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, post_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_bookmarks_user_id_created_at ON post_bookmarks(user_id, created_at);
+
+-- Counters kept up to date by the reaction and bookmark endpoints
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS reaction_counts JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS bookmark_count INTEGER NOT NULL DEFAULT 0;
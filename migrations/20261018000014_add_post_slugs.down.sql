@@ -0,0 +1,4 @@
+-- Drop slug history first
+DROP TABLE IF EXISTS post_slugs;
+
+ALTER TABLE posts DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,37 @@
+-- Permalink of each post; new posts get a placeholder until their slug is assigned
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS slug VARCHAR(100) DEFAULT gen_random_uuid()::text;
+
+-- Every slug a post has had, the current one included. Slugs are never
+-- reused by another post, so old links keep redirecting to the right post
+CREATE TABLE IF NOT EXISTS post_slugs (
+    slug VARCHAR(100) PRIMARY KEY,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_slugs_post_id ON post_slugs(post_id);
+
+-- Slugs of existing posts, from their titles with Vietnamese diacritics removed
+WITH base AS (
+    SELECT id,
+           COALESCE(NULLIF(trim(BOTH '-' FROM left(regexp_replace(lower(translate(title,
+               'àáảãạăằắẳẵặâầấẩẫậÀÁẢÃẠĂẰẮẲẴẶÂẦẤẨẪẬèéẻẽẹêềếểễệÈÉẺẼẸÊỀẾỂỄỆìíỉĩịÌÍỈĨỊòóỏõọôồốổỗộơờớởỡợÒÓỎÕỌÔỒỐỔỖỘƠỜỚỞỠỢùúủũụưừứửữựÙÚỦŨỤƯỪỨỬỮỰỳýỷỹỵỲÝỶỸỴđĐ',
+               'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaeeeeeeeeeeeeeeeeeeeeeeiiiiiiiiiioooooooooooooooooooooooooooooooooouuuuuuuuuuuuuuuuuuuuuuyyyyyyyyyydd')),
+               '[^a-z0-9]+', '-', 'g'), 90)), ''), 'post') AS slug
+    FROM posts
+    WHERE id NOT IN (SELECT post_id FROM post_slugs)
+), numbered AS (
+    SELECT id, slug, ROW_NUMBER() OVER (PARTITION BY slug ORDER BY id) AS n FROM base
+)
+UPDATE posts
+SET slug = CASE WHEN numbered.n = 1 THEN numbered.slug ELSE numbered.slug || '-' || posts.id END
+FROM numbered
+WHERE posts.id = numbered.id;
+
+INSERT INTO post_slugs (slug, post_id)
+SELECT slug, id FROM posts
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE posts ALTER COLUMN slug SET NOT NULL;
+ALTER TABLE posts DROP CONSTRAINT IF EXISTS posts_slug_key;
+ALTER TABLE posts ADD CONSTRAINT posts_slug_key UNIQUE (slug);
@@ -0,0 +1,7 @@
+-- Drop trigger and its function first
+DROP TRIGGER IF EXISTS attachments_queue_deleted_files ON attachments;
+DROP FUNCTION IF EXISTS queue_deleted_attachment_files();
+
+-- Drop attachment tables
+DROP TABLE IF EXISTS deleted_files;
+DROP TABLE IF EXISTS attachments;
@@ -0,0 +1,41 @@
+-- Create attachments table, the files themselves live in the storage backend
+CREATE TABLE IF NOT EXISTS attachments (
+    id SERIAL PRIMARY KEY,
+    public_id UUID NOT NULL UNIQUE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_key VARCHAR(255) NOT NULL,
+    -- Images only
+    width INTEGER,
+    height INTEGER,
+    thumbnail_key VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_post_id ON attachments(post_id);
+
+-- Stored files of deleted attachments, removed from storage in the background
+CREATE TABLE IF NOT EXISTS deleted_files (
+    storage_key VARCHAR(255) PRIMARY KEY,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Attachments also go away when their post or its author is deleted, so the
+-- files are queued by a trigger rather than by each code path
+CREATE OR REPLACE FUNCTION queue_deleted_attachment_files() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deleted_files (storage_key)
+    SELECT key FROM unnest(ARRAY[OLD.storage_key, OLD.thumbnail_key]) AS key
+    WHERE key IS NOT NULL
+    ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS attachments_queue_deleted_files ON attachments;
+CREATE TRIGGER attachments_queue_deleted_files
+    AFTER DELETE ON attachments
+    FOR EACH ROW EXECUTE FUNCTION queue_deleted_attachment_files();
@@ -0,0 +1,11 @@
+-- Drop moderation audit trail and reports tables
+DROP TABLE IF EXISTS moderation_actions;
+DROP TABLE IF EXISTS reports;
+
+-- Derive the published flag from the status alone again
+ALTER TABLE posts DROP COLUMN IF EXISTS is_published;
+ALTER TABLE posts ADD COLUMN is_published BOOLEAN NOT NULL
+    GENERATED ALWAYS AS (status = 'published') STORED;
+CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(is_published) WHERE is_published = true;
+
+ALTER TABLE posts DROP COLUMN IF EXISTS hidden_at;
@@ -0,0 +1,74 @@
+-- Posts hidden by moderation stay visible to their author and admins only
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS hidden_at TIMESTAMPTZ;
+
+-- A hidden post no longer counts as published
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'posts' AND column_name = 'is_published'
+          AND generation_expression LIKE '%hidden_at%'
+    ) THEN
+        ALTER TABLE posts DROP COLUMN is_published;
+        ALTER TABLE posts ADD COLUMN is_published BOOLEAN NOT NULL
+            GENERATED ALWAYS AS (status = 'published' AND hidden_at IS NULL) STORED;
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(is_published) WHERE is_published = true;
+
+-- Create reports table, a report is about a post or about one of its comments
+CREATE TABLE IF NOT EXISTS reports (
+    id SERIAL PRIMARY KEY,
+    public_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+    reporter_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL,
+    details TEXT,
+    -- NULL while the report waits in the moderation queue
+    resolution VARCHAR(20),
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE reports DROP CONSTRAINT IF EXISTS reports_reason_check;
+ALTER TABLE reports ADD CONSTRAINT reports_reason_check
+    CHECK (reason IN ('spam', 'inappropriate'));
+ALTER TABLE reports DROP CONSTRAINT IF EXISTS reports_resolution_check;
+ALTER TABLE reports ADD CONSTRAINT reports_resolution_check
+    CHECK (resolution IN ('dismissed', 'actioned'));
+
+-- One open report per user and item
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_post ON reports(post_id, reporter_id)
+    WHERE comment_id IS NULL AND resolved_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_comment ON reports(comment_id, reporter_id)
+    WHERE comment_id IS NOT NULL AND resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_reports_open ON reports(created_at) WHERE resolved_at IS NULL;
+
+-- Create moderation audit trail. Targets are kept by public id so entries
+-- outlive the posts and comments they are about.
+CREATE TABLE IF NOT EXISTS moderation_actions (
+    id SERIAL PRIMARY KEY,
+    target_type VARCHAR(20) NOT NULL,
+    target_id UUID NOT NULL,
+    author_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    -- NULL for actions taken automatically
+    moderator_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(20) NOT NULL,
+    note TEXT,
+    reports_resolved INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE moderation_actions DROP CONSTRAINT IF EXISTS moderation_actions_target_type_check;
+ALTER TABLE moderation_actions ADD CONSTRAINT moderation_actions_target_type_check
+    CHECK (target_type IN ('post', 'comment'));
+ALTER TABLE moderation_actions DROP CONSTRAINT IF EXISTS moderation_actions_action_check;
+ALTER TABLE moderation_actions ADD CONSTRAINT moderation_actions_action_check
+    CHECK (action IN ('dismiss', 'hide', 'delete', 'warn', 'auto_hide'));
+
+CREATE INDEX IF NOT EXISTS idx_moderation_actions_created_at ON moderation_actions(created_at);
+CREATE INDEX IF NOT EXISTS idx_moderation_actions_target_id ON moderation_actions(target_id);
+CREATE INDEX IF NOT EXISTS idx_moderation_actions_warnings ON moderation_actions(author_id, created_at)
+    WHERE action = 'warn';
@@ -0,0 +1,3 @@
+-- Drop feed index and follows table
+DROP INDEX IF EXISTS idx_posts_author_published;
+DROP TABLE IF EXISTS follows;
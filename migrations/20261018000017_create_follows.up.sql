@@ -0,0 +1,18 @@
+-- Create follows table, one row per user following an author
+CREATE TABLE IF NOT EXISTS follows (
+    follower_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    followee_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (follower_id, followee_id)
+);
+
+ALTER TABLE follows DROP CONSTRAINT IF EXISTS follows_not_self_check;
+ALTER TABLE follows ADD CONSTRAINT follows_not_self_check CHECK (follower_id <> followee_id);
+
+CREATE INDEX IF NOT EXISTS idx_follows_followee_id_created_at ON follows(followee_id, created_at);
+
+-- Latest published posts of an author, read once per followed author by the
+-- personal feed so it does not slow down as posts grows
+CREATE INDEX IF NOT EXISTS idx_posts_author_published
+    ON posts(user_id, (COALESCE(published_at, created_at)) DESC, public_id DESC)
+    WHERE is_published AND deleted_at IS NULL;
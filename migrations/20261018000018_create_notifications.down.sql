@@ -0,0 +1,3 @@
+-- Drop notification preferences and notifications tables
+DROP TABLE IF EXISTS notification_preferences;
+DROP TABLE IF EXISTS notifications;
@@ -0,0 +1,47 @@
+-- Create notifications table, one row per recipient of an event
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    public_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- User whose action triggered the notification
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    kind VARCHAR(20) NOT NULL,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id INTEGER REFERENCES comments(id) ON DELETE CASCADE,
+    -- Outcome of a review: approved or rejected
+    detail VARCHAR(20),
+    -- Reviewer's comment
+    note TEXT,
+    -- Delivery chosen by the recipient's preferences when it was created
+    in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    email_digest BOOLEAN NOT NULL DEFAULT FALSE,
+    read_at TIMESTAMPTZ,
+    emailed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE notifications DROP CONSTRAINT IF EXISTS notifications_kind_check;
+ALTER TABLE notifications ADD CONSTRAINT notifications_kind_check
+    CHECK (kind IN ('comment', 'mention', 'review'));
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_id_created_at ON notifications(user_id, created_at DESC)
+    WHERE in_app;
+CREATE INDEX IF NOT EXISTS idx_notifications_unread ON notifications(user_id)
+    WHERE in_app AND read_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_notifications_digest ON notifications(created_at)
+    WHERE email_digest AND emailed_at IS NULL;
+
+-- Create notification preferences table, users without a row for a kind
+-- get it in the app and not by email
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    email_digest BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, kind)
+);
+
+ALTER TABLE notification_preferences DROP CONSTRAINT IF EXISTS notification_preferences_kind_check;
+ALTER TABLE notification_preferences ADD CONSTRAINT notification_preferences_kind_check
+    CHECK (kind IN ('comment', 'mention', 'review'));
@@ -0,0 +1,9 @@
+-- Drop event triggers and event log
+DROP TRIGGER IF EXISTS comments_record_event ON comments;
+DROP TRIGGER IF EXISTS posts_record_delete_event ON posts;
+DROP TRIGGER IF EXISTS posts_record_event ON posts;
+DROP FUNCTION IF EXISTS record_comment_event();
+DROP FUNCTION IF EXISTS record_post_event();
+DROP FUNCTION IF EXISTS post_event_payload(posts);
+DROP FUNCTION IF EXISTS record_event(VARCHAR, UUID, INTEGER, BOOLEAN, JSONB);
+DROP TABLE IF EXISTS events;
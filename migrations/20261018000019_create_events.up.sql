@@ -0,0 +1,146 @@
+-- Create event log of changes to posts and comments, streamed to clients
+-- and kept for a while so they can resume after a disconnect
+CREATE TABLE IF NOT EXISTS events (
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(30) NOT NULL,
+    -- Public id of the post the event is about
+    post_id UUID NOT NULL,
+    -- Author of the post, who sees its events even while it is unpublished
+    owner_id INTEGER,
+    -- Whether everyone may see the event
+    is_public BOOLEAN NOT NULL,
+    payload JSONB NOT NULL,
+    transaction_id BIGINT NOT NULL DEFAULT txid_current(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE events DROP CONSTRAINT IF EXISTS events_kind_check;
+ALTER TABLE events ADD CONSTRAINT events_kind_check
+    CHECK (kind IN ('post.created', 'post.updated', 'post.published', 'post.deleted', 'comment.created'));
+
+CREATE INDEX IF NOT EXISTS idx_events_post_id ON events(post_id);
+
+-- Events are recorded by triggers so that every code path, including the
+-- scheduler and moderation, produces them. Listeners are told the id of
+-- each new event once its transaction commits.
+CREATE OR REPLACE FUNCTION record_event(
+    event_kind VARCHAR, event_post_id UUID, event_owner_id INTEGER, event_is_public BOOLEAN, event_payload JSONB
+) RETURNS VOID AS $$
+DECLARE
+    event_id BIGINT;
+BEGIN
+    INSERT INTO events (kind, post_id, owner_id, is_public, payload)
+    VALUES (event_kind, event_post_id, event_owner_id, event_is_public, event_payload)
+    RETURNING id INTO event_id;
+
+    PERFORM pg_notify('events', event_id::text);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION post_event_payload(post posts) RETURNS JSONB AS $$
+    SELECT jsonb_build_object(
+        'id', post.public_id,
+        'slug', post.slug,
+        'title', post.title,
+        'status', post.status,
+        'is_published', post.is_published AND post.deleted_at IS NULL,
+        'author_id', (SELECT u.public_id FROM users u WHERE u.id = post.user_id),
+        'published_at', post.published_at,
+        'updated_at', post.updated_at
+    );
+$$ LANGUAGE sql STABLE;
+
+-- Post events are recorded when the transaction commits, from the post as
+-- committed. A transaction that changes a post in several statements, such
+-- as creating it and then giving it a slug, produces a single event.
+CREATE OR REPLACE FUNCTION record_post_event() RETURNS TRIGGER AS $$
+DECLARE
+    post posts;
+    event_kind VARCHAR(30);
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        -- Posts purged from the trash were announced when they were deleted
+        IF OLD.deleted_at IS NULL THEN
+            PERFORM record_event('post.deleted', OLD.public_id, OLD.user_id,
+                                 OLD.is_published, post_event_payload(OLD));
+        END IF;
+        RETURN NULL;
+    END IF;
+
+    SELECT * INTO post FROM posts WHERE id = NEW.id;
+    IF NOT FOUND THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        event_kind := 'post.created';
+    ELSIF OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN
+        event_kind := 'post.deleted';
+    ELSIF NEW.status = 'published' AND OLD.status <> 'published' THEN
+        event_kind := 'post.published';
+    -- Counters of comments, reactions and bookmarks do not make a new version.
+    -- The slug is assigned right after a post is created.
+    ELSIF NEW.updated_at IS DISTINCT FROM OLD.updated_at
+          OR NEW.deleted_at IS DISTINCT FROM OLD.deleted_at
+          OR NEW.slug IS DISTINCT FROM OLD.slug THEN
+        event_kind := 'post.updated';
+    ELSE
+        RETURN NULL;
+    END IF;
+
+    IF EXISTS (
+        SELECT 1 FROM events e
+        WHERE e.post_id = post.public_id AND e.transaction_id = txid_current()
+          AND (e.kind = event_kind OR (event_kind = 'post.updated' AND e.kind LIKE 'post.%'))
+    ) THEN
+        RETURN NULL;
+    END IF;
+
+    PERFORM record_event(
+        event_kind, post.public_id, post.user_id,
+        CASE WHEN TG_OP = 'INSERT' THEN FALSE ELSE OLD.is_published AND OLD.deleted_at IS NULL END
+            OR (post.is_published AND post.deleted_at IS NULL),
+        post_event_payload(post));
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS posts_record_event ON posts;
+CREATE CONSTRAINT TRIGGER posts_record_event
+    AFTER INSERT OR UPDATE ON posts
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION record_post_event();
+
+DROP TRIGGER IF EXISTS posts_record_delete_event ON posts;
+CREATE TRIGGER posts_record_delete_event
+    AFTER DELETE ON posts
+    FOR EACH ROW EXECUTE FUNCTION record_post_event();
+
+CREATE OR REPLACE FUNCTION record_comment_event() RETURNS TRIGGER AS $$
+DECLARE
+    post posts;
+BEGIN
+    SELECT * INTO post FROM posts WHERE id = NEW.post_id;
+
+    PERFORM record_event(
+        'comment.created', post.public_id, post.user_id,
+        post.is_published AND post.deleted_at IS NULL,
+        jsonb_build_object(
+            'id', NEW.public_id,
+            'post_id', post.public_id,
+            'parent_id', (SELECT c.public_id FROM comments c WHERE c.id = NEW.parent_id),
+            'author_id', (SELECT u.public_id FROM users u WHERE u.id = NEW.user_id),
+            'content', NEW.content,
+            'created_at', NEW.created_at
+        )
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS comments_record_event ON comments;
+CREATE TRIGGER comments_record_event
+    AFTER INSERT ON comments
+    FOR EACH ROW EXECUTE FUNCTION record_comment_event();
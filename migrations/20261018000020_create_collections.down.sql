@@ -0,0 +1,4 @@
+-- Drop post reads and collections tables
+DROP TABLE IF EXISTS post_reads;
+DROP TABLE IF EXISTS collection_posts;
+DROP TABLE IF EXISTS collections;
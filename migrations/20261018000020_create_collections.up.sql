@@ -0,0 +1,36 @@
+-- Create collections table, an ordered series of posts such as a multi-part guide
+CREATE TABLE IF NOT EXISTS collections (
+    id SERIAL PRIMARY KEY,
+    public_id UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_collections_user_id ON collections(user_id);
+
+-- Create collection posts table, the place of each post in a collection
+CREATE TABLE IF NOT EXISTS collection_posts (
+    collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (collection_id, post_id)
+);
+
+-- Deferrable so a reorder can swap positions within a single statement
+ALTER TABLE collection_posts DROP CONSTRAINT IF EXISTS collection_posts_position_key;
+ALTER TABLE collection_posts ADD CONSTRAINT collection_posts_position_key
+    UNIQUE (collection_id, position) DEFERRABLE;
+
+CREATE INDEX IF NOT EXISTS idx_collection_posts_post_id ON collection_posts(post_id);
+
+-- Create post reads table, the posts each user finished reading
+CREATE TABLE IF NOT EXISTS post_reads (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id INTEGER NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    read_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, post_id)
+);
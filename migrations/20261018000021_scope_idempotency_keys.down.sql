@@ -0,0 +1,7 @@
+-- Keys used by several callers cannot share the narrower primary key
+DELETE FROM idempotency_keys WHERE principal <> '';
+
+ALTER TABLE idempotency_keys DROP CONSTRAINT IF EXISTS idempotency_keys_pkey;
+ALTER TABLE idempotency_keys ADD CONSTRAINT idempotency_keys_pkey PRIMARY KEY (key, request_path);
+
+ALTER TABLE idempotency_keys DROP COLUMN IF EXISTS principal;
@@ -0,0 +1,7 @@
+-- Scope idempotency keys to the caller so that two clients picking the same
+-- key never see each other's responses. The principal is a hash of the
+-- Authorization header; keys stored before this are left to expire.
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS principal TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE idempotency_keys DROP CONSTRAINT IF EXISTS idempotency_keys_pkey;
+ALTER TABLE idempotency_keys ADD CONSTRAINT idempotency_keys_pkey PRIMARY KEY (key, principal, request_path);
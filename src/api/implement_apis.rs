@@ -2,17 +2,36 @@ use axum::Router;
 
 use crate::config::AppState;
 use crate::routes::{
-    health,
+    attachments,
+    auth,
+    batch,
+    collections,
+    events,
+    follows,
+    moderation,
+    notifications,
     posts,
     productions,
+    tags,
+    trash,
     users,
 };
 
 pub fn api_router() -> Router<AppState> {
     Router::new()
+        .nest("/auth", auth::auth_router())
         .nest("/users", users::user_router())
         .nest("/posts", posts::post_router())
         .nest("/productions", productions::production_router())
+        .nest("/batch", batch::batch_router())
+        .nest("/trash", trash::trash_router())
+        .nest("/tags", tags::tag_router())
+        .nest("/attachments", attachments::attachment_router())
+        .nest("/moderation", moderation::moderation_router())
+        .nest("/feed", follows::follow_feed_router())
+        .nest("/notifications", notifications::notification_router())
+        .nest("/events", events::event_router())
+        .nest("/collections", collections::collection_router())
         // Thêm các routes khác ở đây
         // .nest("/orders", orders::order_router())
         // .nest("/commands", commands::command_router())
@@ -0,0 +1,100 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::{AppConfig, AppState},
+    models::responses::ApiResponse,
+};
+
+pub const ROLE_ADMIN: &str = "admin";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    /// Public id of the user
+    pub sub: Uuid,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+pub fn issue_token(config: &AppConfig, user_id: Uuid) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id,
+        iat: now,
+        exp: now + config.jwt_ttl_seconds as i64,
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+}
+
+/// Authenticated caller, resolved from an `Authorization: Bearer` token.
+/// Role and active state are read from the database on every request so
+/// deactivating a user takes effect immediately.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: i32,
+    pub role: String,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == ROLE_ADMIN
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("Missing bearer token"))?;
+
+        let claims = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(state.config.jwt_secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map_err(|_| unauthorized("Invalid or expired token"))?
+        .claims;
+
+        sqlx::query_as!(
+            AuthUser,
+            "SELECT id, role FROM users
+             WHERE public_id = $1 AND is_active AND deleted_at IS NULL",
+            claims.sub
+        )
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| unauthorized("User is no longer active"))
+    }
+}
+
+pub fn unauthorized(message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiResponse::<()>::error(message)),
+    )
+        .into_response()
+}
+
+pub fn forbidden(message: &str) -> Response {
+    (StatusCode::FORBIDDEN, Json(ApiResponse::<()>::error(message))).into_response()
+}
@@ -1,5 +1,10 @@
+use std::sync::Arc;
+
 use serde::Deserialize;
 use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::{database::models::PostEvent, storage::Storage};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
@@ -7,23 +12,120 @@ pub struct AppConfig {
     pub server_host: String,
     pub server_port: u16,
     pub jwt_secret: String,
+    pub jwt_ttl_seconds: u64,
+    pub require_if_match: bool,
+    pub idempotency_ttl_seconds: u64,
+    pub trash_retention_days: u32,
+    pub publish_scheduler_interval_seconds: u64,
+    /// Absolute URL the API is reached at, used for links in feeds
+    pub public_base_url: String,
+    /// `local` or `s3`
+    pub storage_backend: String,
+    pub storage_local_dir: String,
+    pub s3_bucket: Option<String>,
+    /// Endpoint of an S3-compatible service, AWS when unset
+    pub s3_endpoint: Option<String>,
+    pub s3_region: String,
+    pub attachment_max_bytes: usize,
+    /// Open reports from different users that hide a post or comment until
+    /// a moderator looks at it, 0 to never hide automatically
+    pub report_auto_hide_threshold: u32,
+    /// How often notifications users want by email are sent as a digest
+    pub notification_digest_interval_seconds: u64,
+    /// `none` or `smtp`
+    pub mail_backend: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Upgrade the SMTP connection with STARTTLS
+    pub smtp_starttls: bool,
+    /// Sender of outgoing mail, e.g. `Blog <noreply@example.com>`
+    pub mail_from: Option<String>,
+    /// Latest events kept for clients resuming a stream
+    pub event_log_size: u32,
+    /// Apply pending migrations at startup
+    pub run_migrations: bool,
+    /// Account made an admin at startup, created with the password when
+    /// it does not exist yet
+    pub admin_email: Option<String>,
+    pub admin_password: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub config: AppConfig,
+    pub storage: Arc<dyn Storage>,
+    /// Events of all instances, as they are committed
+    pub events: broadcast::Sender<Arc<PostEvent>>,
 }
 
 impl AppConfig {
     pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let server_port: u16 = std::env::var("SERVER_PORT")
+            .unwrap_or_else(|_| "3000".to_string())
+            .parse()?;
+
         Ok(Self {
             database_url: std::env::var("DATABASE_URL")?,
             server_host: std::env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
-            server_port: std::env::var("SERVER_PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()?,
+            server_port,
             jwt_secret: std::env::var("JWT_SECRET")?,
+            jwt_ttl_seconds: std::env::var("JWT_TTL_SECONDS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()?,
+            require_if_match: std::env::var("REQUIRE_IF_MATCH")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            idempotency_ttl_seconds: std::env::var("IDEMPOTENCY_TTL_SECONDS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()?,
+            trash_retention_days: std::env::var("TRASH_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            publish_scheduler_interval_seconds: std::env::var("PUBLISH_SCHEDULER_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            public_base_url: std::env::var("PUBLIC_BASE_URL")
+                .unwrap_or_else(|_| format!("http://localhost:{}", server_port))
+                .trim_end_matches('/')
+                .to_string(),
+            storage_backend: std::env::var("STORAGE_BACKEND")
+                .unwrap_or_else(|_| "local".to_string()),
+            storage_local_dir: std::env::var("STORAGE_LOCAL_DIR")
+                .unwrap_or_else(|_| "uploads".to_string()),
+            s3_bucket: std::env::var("S3_BUCKET").ok().filter(|bucket| !bucket.is_empty()),
+            s3_endpoint: std::env::var("S3_ENDPOINT").ok().filter(|endpoint| !endpoint.is_empty()),
+            s3_region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            attachment_max_bytes: std::env::var("ATTACHMENT_MAX_BYTES")
+                .unwrap_or_else(|_| "10485760".to_string())
+                .parse()?,
+            report_auto_hide_threshold: std::env::var("REPORT_AUTO_HIDE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            notification_digest_interval_seconds: std::env::var("NOTIFICATION_DIGEST_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()?,
+            mail_backend: std::env::var("MAIL_BACKEND").unwrap_or_else(|_| "none".to_string()),
+            smtp_host: std::env::var("SMTP_HOST").ok().filter(|host| !host.is_empty()),
+            smtp_port: std::env::var("SMTP_PORT")
+                .unwrap_or_else(|_| "587".to_string())
+                .parse()?,
+            smtp_username: std::env::var("SMTP_USERNAME").ok().filter(|username| !username.is_empty()),
+            smtp_password: std::env::var("SMTP_PASSWORD").ok().filter(|password| !password.is_empty()),
+            smtp_starttls: std::env::var("SMTP_STARTTLS")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()?,
+            mail_from: std::env::var("MAIL_FROM").ok().filter(|from| !from.is_empty()),
+            event_log_size: std::env::var("EVENT_LOG_SIZE")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()?,
+            run_migrations: std::env::var("RUN_MIGRATIONS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            admin_email: std::env::var("ADMIN_EMAIL").ok().filter(|email| !email.is_empty()),
+            admin_password: std::env::var("ADMIN_PASSWORD").ok().filter(|password| !password.is_empty()),
         })
     }
     
@@ -1,5 +1,13 @@
-use sqlx::{PgPool, postgres::PgPoolOptions};
-use std::time::Duration;
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, http::StatusCode};
+use sqlx::{pool::PoolConnection, postgres::PgPoolOptions, PgConnection, PgPool, Postgres, Transaction};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::config::AppState;
 
 pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
     PgPoolOptions::new()
@@ -9,6 +17,7 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
         .await
 }
 
+#[allow(dead_code)]
 pub async fn establish_connection() -> Result<PgPool, sqlx::Error> {
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
@@ -16,6 +25,7 @@ pub async fn establish_connection() -> Result<PgPool, sqlx::Error> {
     create_pool(&database_url).await
 }
 
+#[allow(dead_code)]
 pub async fn setup_database() -> Result<PgPool, sqlx::Error> {
     let pool = establish_connection().await?;
     
@@ -27,4 +37,52 @@ pub async fn setup_database() -> Result<PgPool, sqlx::Error> {
     tracing::info!("Database connection established and migrations completed");
     
     Ok(pool)
-}
\ No newline at end of file
+}
+
+/// Transaction shared by the operations of an atomic batch request. The batch
+/// handler puts it in the request extensions so `DbConn` picks it up.
+#[derive(Clone)]
+pub struct SharedTransaction(pub Arc<Mutex<Transaction<'static, Postgres>>>);
+
+/// Connection a handler runs its queries on: a pooled connection, or the
+/// surrounding batch transaction when there is one
+pub enum DbConn {
+    Pooled(Box<PoolConnection<Postgres>>),
+    Transaction(OwnedMutexGuard<Transaction<'static, Postgres>>),
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for DbConn {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(SharedTransaction(tx)) = parts.extensions.get::<SharedTransaction>().cloned() {
+            return Ok(Self::Transaction(tx.lock_owned().await));
+        }
+
+        state.db.acquire().await.map(|conn| Self::Pooled(Box::new(conn))).map_err(|e| {
+            tracing::error!("Failed to acquire database connection: {}", e);
+            StatusCode::SERVICE_UNAVAILABLE
+        })
+    }
+}
+
+impl Deref for DbConn {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pooled(conn) => conn,
+            Self::Transaction(tx) => tx,
+        }
+    }
+}
+
+impl DerefMut for DbConn {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pooled(conn) => conn,
+            Self::Transaction(tx) => tx,
+        }
+    }
+}
@@ -1,5 +1,2 @@
 pub mod connection;
-pub mod models;
-
-pub use connection::*;
-pub use models::*;
\ No newline at end of file
+pub mod models;
\ No newline at end of file
@@ -5,11 +5,13 @@ use utoipa::ToSchema;
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub id: i32,
+    pub public_id: uuid::Uuid,
     pub email: String,
     pub username: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub full_name: Option<String>,
+    pub role: String,
     pub is_active: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
@@ -18,25 +20,168 @@ pub struct User {
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Post {
     pub id: i32,
+    pub public_id: uuid::Uuid,
+    pub slug: String,
     pub title: String,
     pub content: String,
+    pub content_format: String,
+    pub content_html: Option<String>,
+    pub tags: Vec<String>,
+    pub comment_count: i32,
+    pub reaction_counts: serde_json::Value,
+    pub bookmark_count: i32,
+    /// Reaction of the user the post was loaded for
+    pub my_reaction: Option<String>,
+    pub is_bookmarked: bool,
+    /// `attachments` rows of the post as a JSON array
+    pub attachments: serde_json::Value,
     pub user_id: i32,
+    pub user_public_id: uuid::Uuid,
+    pub status: String,
     pub is_published: bool,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub publish_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
-pub struct PostWithUser {
+#[derive(Debug, Clone, FromRow)]
+pub struct TrashedItem {
+    pub kind: String,
+    pub public_id: uuid::Uuid,
+    pub label: String,
+    pub deleted_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct QueuedReport {
+    pub target_type: String,
+    pub target_id: uuid::Uuid,
+    pub post_id: uuid::Uuid,
+    pub post_title: String,
+    /// Content of the reported comment or post, cut short
+    pub excerpt: Option<String>,
+    pub author_id: uuid::Uuid,
+    pub is_hidden: bool,
+    pub reasons: Vec<String>,
+    pub details: Vec<String>,
+    pub first_reported_at: chrono::DateTime<chrono::Utc>,
+    pub last_reported_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ModerationLogEntry {
+    pub target_type: String,
+    pub target_id: uuid::Uuid,
+    pub action: String,
+    pub author_public_id: Option<uuid::Uuid>,
+    pub moderator_public_id: Option<uuid::Uuid>,
+    pub note: Option<String>,
+    pub reports_resolved: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Notification {
+    pub public_id: uuid::Uuid,
+    pub kind: String,
+    pub actor_public_id: Option<uuid::Uuid>,
+    pub actor_username: Option<String>,
+    pub post_public_id: uuid::Uuid,
+    pub post_title: String,
+    pub comment_public_id: Option<uuid::Uuid>,
+    pub detail: Option<String>,
+    pub note: Option<String>,
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Change to a post or comment, from the event log
+#[derive(Debug, Clone, FromRow)]
+pub struct PostEvent {
+    pub id: i64,
+    pub kind: String,
+    pub post_id: uuid::Uuid,
+    pub owner_id: Option<i32>,
+    pub is_public: bool,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Comment {
     pub id: i32,
+    pub public_id: uuid::Uuid,
+    pub parent_id: Option<i32>,
+    pub parent_public_id: Option<uuid::Uuid>,
+    /// `None` once deleted
+    pub user_public_id: Option<uuid::Uuid>,
+    /// `None` when deleted or hidden from the viewer
+    pub content: Option<String>,
+    pub is_hidden: bool,
+    pub is_deleted: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, FromRow)]
+pub struct Attachment {
+    pub id: i32,
+    pub public_id: uuid::Uuid,
+    pub post_id: i32,
+    pub user_id: Option<i32>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub thumbnail_key: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Collection with its progress for the user it was loaded for. Counts
+/// only cover the posts that user can read.
+#[derive(Debug, Clone, FromRow)]
+pub struct CollectionSummary {
+    pub id: i32,
+    pub public_id: uuid::Uuid,
+    pub user_public_id: uuid::Uuid,
     pub title: String,
-    pub content: String,
-    pub user_id: i32,
-    pub is_published: bool,
+    pub description: Option<String>,
+    pub post_count: i64,
+    pub read_count: i64,
+    /// First post not read yet, in collection order
+    pub next_post_public_id: Option<uuid::Uuid>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
-    // User fields
-    pub user_email: String,
-    pub user_username: String,
-    pub user_full_name: Option<String>,
-}
\ No newline at end of file
+}
+
+/// Post of a collection, numbered among the posts the viewer can read
+#[derive(Debug, Clone, FromRow)]
+pub struct CollectionPost {
+    pub position: i64,
+    pub public_id: uuid::Uuid,
+    pub slug: String,
+    pub title: String,
+    pub user_public_id: uuid::Uuid,
+    pub status: String,
+    pub is_published: bool,
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub added_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Place of a post in one of its collections, with the neighbouring posts
+#[derive(Debug, Clone, FromRow)]
+pub struct PostSeries {
+    pub collection_public_id: uuid::Uuid,
+    pub title: String,
+    pub position: i64,
+    pub total: i64,
+    pub previous_public_id: Option<uuid::Uuid>,
+    pub previous_slug: Option<String>,
+    pub previous_title: Option<String>,
+    pub next_public_id: Option<uuid::Uuid>,
+    pub next_slug: Option<String>,
+    pub next_title: Option<String>,
+}
@@ -0,0 +1,488 @@
+use axum::{
+    extract::{multipart::Field, Multipart, Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use bytes::{Bytes, BytesMut};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    database::{connection::DbConn, models::Attachment},
+    models::responses::{ApiResponse, AttachmentResponse},
+    services::attachment_service::{
+        self, clean_file_name, detect_content_type, is_image, MAX_FILES_PER_UPLOAD,
+        THUMBNAIL_CONTENT_TYPE,
+    },
+    storage::{Storage, StorageError},
+    AppState,
+};
+
+/// Declared content type of clients that do not know better
+const UNSPECIFIED_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// A validated file of an upload request
+struct Upload {
+    file_name: String,
+    content_type: &'static str,
+    data: Bytes,
+}
+
+/// Upload attachments to a post
+///
+/// Send the files as `multipart/form-data` fields named `file`. PNG, JPEG,
+/// GIF, WebP and PDF files are accepted; the type is detected from the
+/// content. Images get a thumbnail.
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/attachments",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body(content = String, content_type = "multipart/form-data", description = "One or more `file` fields"),
+    responses(
+        (status = 201, description = "Attachments uploaded", body = AttachmentsApiResponse),
+        (status = 400, description = "Malformed multipart body, no file or too many files"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can attach files"),
+        (status = 404, description = "Post not found"),
+        (status = 413, description = "A file is larger than the allowed size"),
+        (status = 415, description = "File type not accepted")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Attachments"
+)]
+pub async fn upload_attachments(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Response, StatusCode> {
+    let post = sqlx::query!(
+        "SELECT id, user_id FROM posts WHERE public_id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(post) = post else {
+        return Ok(error(StatusCode::NOT_FOUND, "Post not found"));
+    };
+
+    if post.user_id != auth.id && !auth.is_admin() {
+        return Ok(forbidden("Only the author or an admin can attach files to this post"));
+    }
+
+    // Every file is checked before anything is stored
+    let mut uploads = Vec::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(_) => return Ok(error(StatusCode::BAD_REQUEST, "Malformed multipart body")),
+        };
+
+        if field.name() != Some("file") {
+            return Ok(error(StatusCode::BAD_REQUEST, "Files must be sent in fields named 'file'"));
+        }
+        if uploads.len() == MAX_FILES_PER_UPLOAD {
+            return Ok(error(
+                StatusCode::BAD_REQUEST,
+                &format!("At most {} files can be uploaded at once", MAX_FILES_PER_UPLOAD),
+            ));
+        }
+
+        match read_upload(field, state.config.attachment_max_bytes).await {
+            Ok(upload) => uploads.push(upload),
+            Err(response) => return Ok(response),
+        }
+    }
+
+    if uploads.is_empty() {
+        return Ok(error(StatusCode::BAD_REQUEST, "No file uploaded"));
+    }
+
+    let mut stored_keys = Vec::new();
+    let result = store_uploads(&state, &mut conn, post.id, auth.id, uploads, &mut stored_keys).await;
+
+    match result {
+        Ok(attachments) => Ok((
+            StatusCode::CREATED,
+            Json(ApiResponse::success(attachments, "Attachments uploaded successfully")),
+        )
+            .into_response()),
+        Err(status) => {
+            // Nothing was recorded, so nothing refers to the stored files
+            for key in stored_keys {
+                if let Err(e) = state.storage.delete(&key).await {
+                    tracing::warn!("Failed to delete stored file {}: {}", key, e);
+                }
+            }
+            Err(status)
+        }
+    }
+}
+
+/// Reads a file field up to `max_bytes` and checks its type
+async fn read_upload(mut field: Field<'_>, max_bytes: usize) -> Result<Upload, Response> {
+    let file_name = clean_file_name(field.file_name().unwrap_or_default());
+    let declared_type = field.content_type().map(str::to_string);
+
+    let mut data = BytesMut::new();
+    loop {
+        match field.chunk().await {
+            Ok(Some(chunk)) if data.len() + chunk.len() > max_bytes => {
+                return Err(error(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    &format!("{} is larger than {} bytes", file_name, max_bytes),
+                ));
+            }
+            Ok(Some(chunk)) => data.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(_) => return Err(error(StatusCode::BAD_REQUEST, "Malformed multipart body")),
+        }
+    }
+
+    let Some(content_type) = detect_content_type(&data) else {
+        return Err(error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            &format!("{} is not a PNG, JPEG, GIF, WebP or PDF file", file_name),
+        ));
+    };
+
+    // A declared type is optional, but it has to agree with the content
+    if let Some(declared) = declared_type.as_deref() {
+        if declared != UNSPECIFIED_CONTENT_TYPE && declared != content_type {
+            return Err(error(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                &format!("{} is declared as {} but contains {}", file_name, declared, content_type),
+            ));
+        }
+    }
+
+    Ok(Upload {
+        file_name,
+        content_type,
+        data: data.freeze(),
+    })
+}
+
+/// Stores the files with their thumbnails and records them. Keys of the
+/// stored files are pushed to `stored_keys` as they are written.
+async fn store_uploads(
+    state: &AppState,
+    conn: &mut PgConnection,
+    post_id: i32,
+    user_id: i32,
+    uploads: Vec<Upload>,
+    stored_keys: &mut Vec<String>,
+) -> Result<Vec<AttachmentResponse>, StatusCode> {
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut attachments = Vec::with_capacity(uploads.len());
+
+    for upload in uploads {
+        let public_id = Uuid::new_v4();
+        let storage_key = format!("attachments/{}/{}", post_id, public_id);
+
+        let thumbnail = if is_image(upload.content_type) {
+            let data = upload.data.clone();
+            tokio::task::spawn_blocking(move || attachment_service::make_thumbnail(&data))
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .map_err(|e| tracing::warn!("No thumbnail for {}: {}", upload.file_name, e))
+                .ok()
+        } else {
+            None
+        };
+
+        put(state, &storage_key, upload.data.clone(), upload.content_type).await?;
+        stored_keys.push(storage_key.clone());
+
+        let thumbnail_key = match &thumbnail {
+            Some(thumbnail) => {
+                let key = format!("{}-thumbnail", storage_key);
+                put(state, &key, Bytes::from(thumbnail.data.clone()), THUMBNAIL_CONTENT_TYPE).await?;
+                stored_keys.push(key.clone());
+                Some(key)
+            }
+            None => None,
+        };
+
+        let attachment = sqlx::query_as!(
+            Attachment,
+            "INSERT INTO attachments (public_id, post_id, user_id, file_name, content_type, size_bytes,
+                                      storage_key, width, height, thumbnail_key)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             RETURNING *",
+            public_id,
+            post_id,
+            user_id,
+            upload.file_name,
+            upload.content_type,
+            upload.data.len() as i64,
+            storage_key,
+            thumbnail.as_ref().map(|thumbnail| thumbnail.width as i32),
+            thumbnail.as_ref().map(|thumbnail| thumbnail.height as i32),
+            thumbnail_key
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to record attachment: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        attachments.push(AttachmentResponse::from(attachment));
+    }
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(attachments)
+}
+
+async fn put(state: &AppState, key: &str, data: Bytes, content_type: &str) -> Result<(), StatusCode> {
+    state.storage.put(key, data, content_type).await.map_err(|e| {
+        tracing::error!("Failed to store {}: {}", key, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Download an attachment
+///
+/// Attachments of unpublished posts are only available to their author and
+/// admins.
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "File content"),
+        (status = 404, description = "Attachment not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Attachments"
+)]
+pub async fn get_attachment(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    match visible_attachment(&mut conn, id, auth.as_ref()).await? {
+        Some(attachment) => {
+            file_response(
+                state.storage.as_ref(),
+                &attachment.storage_key,
+                &attachment.content_type,
+                &attachment.file_name,
+            )
+            .await
+        }
+        None => Ok(error(StatusCode::NOT_FOUND, "Attachment not found")),
+    }
+}
+
+/// Download the thumbnail of an image attachment
+#[utoipa::path(
+    get,
+    path = "/api/attachments/{id}/thumbnail",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "JPEG thumbnail"),
+        (status = 404, description = "Attachment not found or without thumbnail")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Attachments"
+)]
+pub async fn get_attachment_thumbnail(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let attachment = visible_attachment(&mut conn, id, auth.as_ref()).await?;
+
+    match attachment.and_then(|attachment| Some((attachment.thumbnail_key?, attachment.file_name))) {
+        Some((key, file_name)) => {
+            file_response(state.storage.as_ref(), &key, THUMBNAIL_CONTENT_TYPE, &file_name).await
+        }
+        None => Ok(error(StatusCode::NOT_FOUND, "Thumbnail not found")),
+    }
+}
+
+/// Delete an attachment
+///
+/// Allowed for the author of the post, the uploader and admins. The stored
+/// files are removed in the background.
+#[utoipa::path(
+    delete,
+    path = "/api/attachments/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "Attachment deleted", body = StringApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Not allowed to delete the attachment"),
+        (status = 404, description = "Attachment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Attachments"
+)]
+pub async fn delete_attachment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(attachment) = visible_attachment(&mut conn, id, Some(&auth)).await? else {
+        return Ok(error(StatusCode::NOT_FOUND, "Attachment not found"));
+    };
+
+    let post_author = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", attachment.post_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if post_author != auth.id && attachment.user_id != Some(auth.id) && !auth.is_admin() {
+        return Ok(forbidden("Only the author, the uploader or an admin can delete this attachment"));
+    }
+
+    sqlx::query!("DELETE FROM attachments WHERE id = $1", attachment.id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete attachment: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(ApiResponse::success(
+        "Attachment deleted".to_string(),
+        "Attachment deleted successfully",
+    ))
+    .into_response())
+}
+
+/// The attachment if the caller may read its post
+async fn visible_attachment(
+    conn: &mut PgConnection,
+    id: Uuid,
+    auth: Option<&AuthUser>,
+) -> Result<Option<Attachment>, StatusCode> {
+    sqlx::query_as!(
+        Attachment,
+        "SELECT a.* FROM attachments a
+         JOIN posts p ON p.id = a.post_id
+         WHERE a.public_id = $1 AND p.deleted_at IS NULL
+           AND (p.is_published OR p.user_id = $2 OR $3)",
+        id,
+        auth.map(|auth| auth.id),
+        auth.is_some_and(AuthUser::is_admin)
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn file_response(
+    storage: &dyn Storage,
+    key: &str,
+    content_type: &str,
+    file_name: &str,
+) -> Result<Response, StatusCode> {
+    let data = match storage.get(key).await {
+        Ok(data) => data,
+        Err(StorageError::NotFound) => {
+            tracing::error!("Stored file {} is missing", key);
+            return Ok(error(StatusCode::NOT_FOUND, "Attachment not found"));
+        }
+        Err(e) => {
+            tracing::error!("Failed to read stored file {}: {}", key, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Non-ASCII names are replaced rather than encoded, the name is a hint
+    let ascii_name: String = file_name
+        .chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", ascii_name)),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    const BOUNDARY: &str = "upload-boundary";
+
+    /// Reads the single file of a multipart body holding `data`
+    async fn upload(data: &[u8], declared_type: &str, max_bytes: usize) -> Result<Upload, Response> {
+        let mut body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"files\"; filename=\"../a.pdf\"\r\n\
+             Content-Type: {declared_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+        let request = Request::post("/")
+            .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={BOUNDARY}"))
+            .body(Body::from(body))
+            .unwrap();
+        let mut multipart = Multipart::from_request(request, &()).await.unwrap();
+        let field = multipart.next_field().await.unwrap().unwrap();
+        read_upload(field, max_bytes).await
+    }
+
+    fn pdf(len: usize) -> Vec<u8> {
+        let mut data = b"%PDF-1.7\n".to_vec();
+        data.resize(len, b' ');
+        data
+    }
+
+    #[tokio::test]
+    async fn files_up_to_the_limit_are_read() {
+        let upload = upload(&pdf(1024), "application/pdf", 1024).await.ok().unwrap();
+        assert_eq!(upload.file_name, "a.pdf");
+        assert_eq!(upload.content_type, "application/pdf");
+        assert_eq!(upload.data.len(), 1024);
+    }
+
+    #[tokio::test]
+    async fn files_over_the_limit_are_rejected() {
+        let response = upload(&pdf(1025), "application/pdf", 1024).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn content_has_to_match_the_declared_type() {
+        let response = upload(&pdf(64), "image/png", 1024).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = upload(b"plain text", UNSPECIFIED_CONTENT_TYPE, 1024).await.err().unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        assert!(upload(&pdf(64), UNSPECIFIED_CONTENT_TYPE, 1024).await.is_ok());
+    }
+}
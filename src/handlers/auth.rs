@@ -0,0 +1,73 @@
+use axum::{extract::State, http::StatusCode, Json};
+use validator::Validate;
+
+use crate::{
+    auth::issue_token,
+    config::AppState,
+    database::{connection::DbConn, models::User},
+    models::{
+        requests::LoginRequest,
+        responses::{ApiResponse, LoginResponse, UserResponse},
+    },
+};
+
+/// Log in with email and password
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in successfully", body = LoginApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Invalid email or password")
+    ),
+    tag = "Auth"
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Json(payload): Json<LoginRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<LoginResponse>>), StatusCode> {
+    if payload.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Invalid input data")),
+        ));
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        "SELECT id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at
+         FROM users WHERE email = $1 AND is_active AND deleted_at IS NULL",
+        payload.email
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let user = match user {
+        Some(user) if bcrypt::verify(&payload.password, &user.password_hash).unwrap_or(false) => user,
+        _ => {
+            return Ok((
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::error("Invalid email or password")),
+            ));
+        }
+    };
+
+    let access_token = issue_token(&state.config, user.public_id).map_err(|e| {
+        tracing::error!("Failed to issue token: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = LoginResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        user: UserResponse::from(user),
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(ApiResponse::success(response, "Logged in successfully")),
+    ))
+}
@@ -0,0 +1,253 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    Json, Router,
+};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+
+use crate::{
+    api::implement_apis::api_router,
+    config::AppState,
+    database::connection::SharedTransaction,
+    models::{
+        requests::{BatchOperation, BatchRequest},
+        responses::{ApiResponse, BatchOperationResult, BatchResponse},
+    },
+};
+
+const MAX_OPERATIONS: usize = 500;
+/// Largest response body of an operation the batch buffers
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+/// Time an operation may take before it is abandoned
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
+/// Routes whose responses never end, which a batch cannot buffer
+const STREAMING_PATHS: &[&str] = &["/events"];
+
+/// Execute several API operations in one request
+#[utoipa::path(
+    post,
+    path = "/api/batch",
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "Per-operation results; `committed` is false when an atomic batch was rolled back. An operation that runs longer than 10 seconds fails with 504, one whose response is larger than 1 MiB with 502.", body = BatchApiResponse),
+        (status = 400, description = "Invalid operation list, or an operation on a streaming route such as `/api/events`")
+    ),
+    tag = "Batch"
+)]
+pub async fn execute_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<BatchRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<BatchResponse>>), StatusCode> {
+    if payload.operations.is_empty() || payload.operations.len() > MAX_OPERATIONS {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!(
+                "A batch must contain between 1 and {} operations",
+                MAX_OPERATIONS
+            ))),
+        ));
+    }
+
+    let mut requests = Vec::with_capacity(payload.operations.len());
+    for (index, operation) in payload.operations.into_iter().enumerate() {
+        match build_request(operation, &headers) {
+            Ok(request) => requests.push(request),
+            Err(message) => {
+                return Ok((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::error(&format!("Operation {}: {}", index, message))),
+                ));
+            }
+        }
+    }
+
+    let transaction = if payload.atomic {
+        let tx = state.db.begin().await.map_err(|e| {
+            tracing::error!("Failed to start batch transaction: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        Some(SharedTransaction(Arc::new(Mutex::new(tx))))
+    } else {
+        None
+    };
+
+    let router = api_router().with_state(state.clone());
+    let mut results = Vec::with_capacity(requests.len());
+    let mut failed_at = None;
+
+    for (index, mut request) in requests.into_iter().enumerate() {
+        if let Some(transaction) = &transaction {
+            request.extensions_mut().insert(transaction.clone());
+        }
+
+        let (status, body) =
+            match tokio::time::timeout(OPERATION_TIMEOUT, run_operation(&router, request)).await {
+                Ok(Ok(outcome)) => outcome,
+                Ok(Err(message)) => (StatusCode::BAD_GATEWAY, error_body(&message)),
+                Err(_) => (
+                    StatusCode::GATEWAY_TIMEOUT,
+                    error_body(&format!(
+                        "Operation did not finish within {} seconds",
+                        OPERATION_TIMEOUT.as_secs()
+                    )),
+                ),
+            };
+
+        results.push(BatchOperationResult {
+            status: status.as_u16(),
+            body,
+        });
+
+        if transaction.is_some() && !status.is_success() {
+            failed_at = Some(index);
+            break;
+        }
+    }
+
+    let committed = match transaction {
+        Some(SharedTransaction(tx)) => {
+            let tx = Arc::try_unwrap(tx)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .into_inner();
+            let outcome = if failed_at.is_some() {
+                tx.rollback().await
+            } else {
+                tx.commit().await
+            };
+            outcome.map_err(|e| {
+                tracing::error!("Failed to finish batch transaction: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            failed_at.is_none()
+        }
+        None => true,
+    };
+
+    let response = BatchResponse {
+        atomic: payload.atomic,
+        committed,
+        results,
+    };
+
+    let message = match failed_at {
+        Some(index) => format!("Operation {} failed, batch rolled back", index),
+        None => "Batch executed".to_string(),
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(ApiResponse {
+            success: committed,
+            message,
+            data: Some(response),
+        }),
+    ))
+}
+
+/// Runs one operation and buffers its response, up to `MAX_RESPONSE_BYTES`
+async fn run_operation(router: &Router, request: Request) -> Result<(StatusCode, Value), String> {
+    let response = router.clone().oneshot(request).await.unwrap_or_else(|e| match e {});
+    let status = response.status();
+    let body = to_bytes(response.into_body(), MAX_RESPONSE_BYTES)
+        .await
+        .map_err(|e| format!("Response could not be buffered: {}", e))?;
+    let body = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+
+    Ok((status, body))
+}
+
+fn error_body(message: &str) -> Value {
+    serde_json::to_value(ApiResponse::<()>::error(message)).unwrap_or(Value::Null)
+}
+
+fn build_request(operation: BatchOperation, headers: &HeaderMap) -> Result<Request, String> {
+    let method = Method::from_bytes(operation.method.to_uppercase().as_bytes())
+        .map_err(|_| format!("invalid method '{}'", operation.method))?;
+
+    let path = operation
+        .path
+        .strip_prefix("/api")
+        .filter(|path| path.starts_with('/'))
+        .ok_or_else(|| format!("path '{}' must start with /api/", operation.path))?;
+
+    if is_route(path, "/batch") {
+        return Err("batches cannot be nested".to_string());
+    }
+
+    if STREAMING_PATHS.iter().any(|route| is_route(path, route)) {
+        return Err(format!("'{}' streams its response and cannot be batched", operation.path));
+    }
+
+    // Operations run as the caller of the batch
+    let mut builder = Request::builder().method(method).uri(path);
+    if let Some(authorization) = headers.get(header::AUTHORIZATION) {
+        builder = builder.header(header::AUTHORIZATION, authorization);
+    }
+
+    let request = match operation.body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    };
+
+    request.map_err(|e| e.to_string())
+}
+
+/// Whether `path` addresses `route` or something below it
+fn is_route(path: &str, route: &str) -> bool {
+    path.strip_prefix(route)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(method: &str, path: &str) -> BatchOperation {
+        BatchOperation {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: None,
+        }
+    }
+
+    fn rejection(method: &str, path: &str) -> Option<String> {
+        build_request(operation(method, path), &HeaderMap::new()).err()
+    }
+
+    #[test]
+    fn streaming_routes_are_rejected() {
+        for path in ["/api/events", "/api/events?since=1", "/api/events/"] {
+            let message = rejection("GET", path).unwrap_or_default();
+            assert!(message.contains("streams its response"), "{path}: {message}");
+        }
+    }
+
+    #[test]
+    fn nested_batches_are_rejected() {
+        for path in ["/api/batch", "/api/batch?x=1", "/api/batch/"] {
+            assert_eq!(rejection("POST", path).as_deref(), Some("batches cannot be nested"));
+        }
+    }
+
+    #[test]
+    fn routes_sharing_a_prefix_are_allowed() {
+        assert_eq!(rejection("GET", "/api/eventsfoo"), None);
+        assert_eq!(rejection("GET", "/api/batches"), None);
+        assert_eq!(rejection("GET", "/api/posts"), None);
+    }
+
+    #[test]
+    fn paths_outside_the_api_are_rejected() {
+        assert!(rejection("GET", "/health").is_some());
+        assert!(rejection("GET", "/apiposts").is_some());
+    }
+}
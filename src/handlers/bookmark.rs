@@ -0,0 +1,196 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    database::connection::DbConn,
+    handlers::post::{load_posts, visible_post_id},
+    models::{
+        requests::PaginationParams,
+        responses::{ApiResponse, BookmarkResponse, PaginatedResponse, PostResponse},
+    },
+};
+
+/// Bookmark a post
+///
+/// Bookmarking a post twice keeps a single bookmark.
+#[utoipa::path(
+    put,
+    path = "/api/posts/{id}/bookmark",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post bookmarked", body = BookmarkApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Bookmarks"
+)]
+pub async fn add_bookmark(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(post_not_found());
+    };
+
+    // The counter only moves when a bookmark was actually added
+    let bookmark_count = sqlx::query_scalar!(
+        "WITH added AS (
+            INSERT INTO post_bookmarks (user_id, post_id)
+            VALUES ($2, $1)
+            ON CONFLICT DO NOTHING
+            RETURNING post_id
+        )
+        UPDATE posts SET bookmark_count = bookmark_count + (SELECT COUNT(*) FROM added)::int
+        WHERE id = $1
+        RETURNING bookmark_count",
+        post_id,
+        auth.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to bookmark post: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = BookmarkResponse {
+        is_bookmarked: true,
+        bookmark_count,
+    };
+    Ok(Json(ApiResponse::success(response, "Post bookmarked")).into_response())
+}
+
+/// Remove a bookmark
+///
+/// Succeeds whether or not the post was bookmarked.
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/bookmark",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Bookmark removed", body = BookmarkApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Bookmarks"
+)]
+pub async fn remove_bookmark(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(post_not_found());
+    };
+
+    let bookmark_count = sqlx::query_scalar!(
+        "WITH removed AS (
+            DELETE FROM post_bookmarks
+            WHERE user_id = $2 AND post_id = $1
+            RETURNING post_id
+        )
+        UPDATE posts SET bookmark_count = bookmark_count - (SELECT COUNT(*) FROM removed)::int
+        WHERE id = $1
+        RETURNING bookmark_count",
+        post_id,
+        auth.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to remove bookmark: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = BookmarkResponse {
+        is_bookmarked: false,
+        bookmark_count,
+    };
+    Ok(Json(ApiResponse::success(response, "Bookmark removed")).into_response())
+}
+
+/// Get the caller's bookmarked posts, most recently bookmarked first
+///
+/// Bookmarked posts that were unpublished or moved to the trash are left
+/// out until they are visible again.
+#[utoipa::path(
+    get,
+    path = "/api/users/me/bookmarks",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Bookmarked posts", body = PostsApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Bookmarks"
+)]
+pub async fn get_my_bookmarks(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<ApiResponse<PaginatedResponse<PostResponse>>>, StatusCode> {
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM post_bookmarks bm
+        JOIN posts p ON p.id = bm.post_id
+        WHERE bm.user_id = $1 AND p.deleted_at IS NULL
+          AND (p.is_published OR p.user_id = $1 OR $2)
+        "#,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let ids = sqlx::query_scalar!(
+        "SELECT p.id
+         FROM post_bookmarks bm
+         JOIN posts p ON p.id = bm.post_id
+         WHERE bm.user_id = $1 AND p.deleted_at IS NULL
+           AND (p.is_published OR p.user_id = $1 OR $2)
+         ORDER BY bm.created_at DESC, p.id DESC
+         LIMIT $3 OFFSET $4",
+        auth.id,
+        auth.is_admin(),
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let posts = load_posts(&mut conn, &ids, Some(auth.id)).await?;
+
+    let response = PaginatedResponse {
+        data: posts.into_iter().map(PostResponse::from).collect(),
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Bookmarks retrieved successfully")))
+}
+
+fn post_not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Post not found"))).into_response()
+}
@@ -0,0 +1,445 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    database::connection::DbConn,
+    models::{
+        requests::BulkPostRequest,
+        responses::{ApiResponse, BulkPostFailure, BulkPostResponse},
+    },
+    services::{
+        post_service::{
+            BulkPostAction, PostAction, PostService, PostStatus, TransitionError, MAX_BULK_POSTS,
+        },
+        tag_service::{self, MAX_TAGS_PER_POST},
+    },
+};
+
+/// Post selected by a bulk operation, locked until the operation ends
+struct BulkTarget {
+    id: i32,
+    public_id: Uuid,
+    user_id: i32,
+    status: String,
+    is_published: bool,
+    tags: Vec<String>,
+}
+
+/// Why a single post was left unchanged
+type ItemError = (StatusCode, String);
+
+/// Publish, unpublish, retag or delete many posts at once
+///
+/// Posts are given by `ids` or selected by `filter`; a filter only matches
+/// posts the caller may change (all posts for admins). Each post is checked
+/// like the single-post endpoint would check it: `publish` applies to
+/// approved posts, `unpublish` takes published ones back to approved,
+/// `retag` adds `add_tags` and removes `remove_tags`, `delete` moves posts
+/// to the trash.
+///
+/// Everything runs in one transaction. Posts that cannot be changed are
+/// reported in `failed` and do not stop the others.
+#[utoipa::path(
+    post,
+    path = "/api/posts/bulk",
+    request_body = BulkPostRequest,
+    responses(
+        (status = 200, description = "Per-post outcome", body = BulkPostApiResponse),
+        (status = 400, description = "Invalid input, or the filter matches too many posts"),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn bulk_posts(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Json(payload): Json<BulkPostRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let Some(action) = BulkPostAction::parse(&payload.action) else {
+        return Ok(bad_request("Invalid input data"));
+    };
+
+    let add_tags = tag_service::normalize_tags(payload.add_tags.as_deref().unwrap_or_default());
+    let remove_tags = tag_service::normalize_tags(payload.remove_tags.as_deref().unwrap_or_default());
+    if action == BulkPostAction::Retag && add_tags.is_empty() && remove_tags.is_empty() {
+        return Ok(bad_request("retag needs add_tags or remove_tags"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Requested posts in order, `None` for those the caller cannot see
+    let targets: Vec<(Uuid, Option<BulkTarget>)> = match (payload.ids, payload.filter) {
+        (Some(ids), None) => {
+            let mut ids = ids;
+            let mut seen = std::collections::HashSet::new();
+            ids.retain(|id| seen.insert(*id));
+
+            let mut found = sqlx::query_as!(
+                BulkTarget,
+                r#"
+                SELECT p.id, p.public_id, p.user_id, p.status, p.is_published,
+                       ARRAY(SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                             WHERE pt.post_id = p.id ORDER BY t.name) AS "tags!"
+                FROM posts p
+                WHERE p.public_id = ANY($1) AND p.deleted_at IS NULL
+                ORDER BY p.id
+                FOR UPDATE
+                "#,
+                &ids
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            ids.into_iter()
+                .map(|id| {
+                    let target = found
+                        .iter()
+                        .position(|post| post.public_id == id)
+                        .map(|index| found.swap_remove(index))
+                        .filter(|post| is_visible(post, &auth));
+                    (id, target)
+                })
+                .collect()
+        }
+        (None, Some(filter)) => {
+            let status = match filter.status.as_deref() {
+                None => None,
+                Some(value) => match PostStatus::parse(value) {
+                    Some(status) => Some(status.as_str()),
+                    None => return Ok(bad_request(&format!("Unknown status: {}", value))),
+                },
+            };
+            let tag = filter.tag.as_deref().map(tag_service::normalize_tag);
+
+            let found = sqlx::query_as!(
+                BulkTarget,
+                r#"
+                SELECT p.id, p.public_id, p.user_id, p.status, p.is_published,
+                       ARRAY(SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                             WHERE pt.post_id = p.id ORDER BY t.name) AS "tags!"
+                FROM posts p
+                WHERE p.deleted_at IS NULL
+                  AND (p.user_id = $1 OR $2)
+                  AND ($3::text IS NULL OR p.status = $3)
+                  AND ($4::text IS NULL OR EXISTS (
+                      SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                      WHERE pt.post_id = p.id AND t.name = $4))
+                  AND ($5::uuid IS NULL OR p.user_id = (SELECT id FROM users WHERE public_id = $5))
+                ORDER BY p.id
+                LIMIT $6
+                FOR UPDATE
+                "#,
+                auth.id,
+                auth.is_admin(),
+                status,
+                tag,
+                filter.author_id,
+                MAX_BULK_POSTS as i64 + 1
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            if found.len() > MAX_BULK_POSTS {
+                return Ok(bad_request(&format!(
+                    "The filter matches more than {} posts, narrow it down",
+                    MAX_BULK_POSTS
+                )));
+            }
+
+            found.into_iter().map(|post| (post.public_id, Some(post))).collect()
+        }
+        _ => return Ok(bad_request("Give either ids or filter")),
+    };
+
+    let mut outcomes = Vec::with_capacity(targets.len());
+    for (id, target) in targets {
+        let result = match target {
+            Some(post) => apply(&mut tx, &auth, action, &post, &add_tags, &remove_tags).await?,
+            None => Err((StatusCode::NOT_FOUND, "Post not found".to_string())),
+        };
+        outcomes.push((id, result));
+    }
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let (response, message) = report(action, outcomes);
+    Ok(Json(ApiResponse::success(response, &message)).into_response())
+}
+
+/// Unpublished posts of others are reported as not found, like the
+/// single-post endpoints do
+fn is_visible(post: &BulkTarget, auth: &AuthUser) -> bool {
+    post.is_published || post.user_id == auth.id || auth.is_admin()
+}
+
+/// Splits the outcome of each post into succeeded and failed, keeping the
+/// order of the request
+fn report(
+    action: BulkPostAction,
+    outcomes: Vec<(Uuid, Result<(), ItemError>)>,
+) -> (BulkPostResponse, String) {
+    let mut response = BulkPostResponse {
+        action: action.to_string(),
+        succeeded: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for (id, result) in outcomes {
+        match result {
+            Ok(()) => response.succeeded.push(id),
+            Err((status, error)) => response.failed.push(BulkPostFailure {
+                id,
+                status: status.as_u16(),
+                error,
+            }),
+        }
+    }
+
+    let message = format!(
+        "{} of {} posts updated",
+        response.succeeded.len(),
+        response.succeeded.len() + response.failed.len()
+    );
+    (response, message)
+}
+
+/// Checks that the caller may apply the action to a post in `from`.
+/// Returns the status a workflow action leads to, `None` for actions that
+/// leave the status alone.
+fn check(
+    action: BulkPostAction,
+    from: PostStatus,
+    is_author: bool,
+    is_admin: bool,
+) -> Result<Option<PostStatus>, ItemError> {
+    let step = match action {
+        BulkPostAction::Publish => PostAction::Publish,
+        BulkPostAction::Unpublish => PostAction::Unpublish,
+        BulkPostAction::Retag | BulkPostAction::Delete if is_author || is_admin => return Ok(None),
+        BulkPostAction::Retag | BulkPostAction::Delete => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("You are not allowed to {} this post", action),
+            ));
+        }
+    };
+
+    match PostService::transition(step, from, is_author, is_admin) {
+        Ok(to) => Ok(Some(to)),
+        Err(TransitionError::Forbidden) => Err((
+            StatusCode::FORBIDDEN,
+            format!("You are not allowed to {} this post", action),
+        )),
+        Err(TransitionError::InvalidTransition { from, .. }) => Err((
+            StatusCode::CONFLICT,
+            format!("Cannot {} a post that is {}", action, from),
+        )),
+    }
+}
+
+/// Tags of a post after a retag, sorted
+fn retagged(tags: &[String], add_tags: &[String], remove_tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .filter(|tag| !remove_tags.contains(tag))
+        .cloned()
+        .collect();
+    for tag in add_tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags.sort();
+    tags
+}
+
+/// Applies the action to one post. Database errors abort the whole
+/// operation; the inner result tells why this post was skipped.
+async fn apply(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    action: BulkPostAction,
+    post: &BulkTarget,
+    add_tags: &[String],
+    remove_tags: &[String],
+) -> Result<Result<(), ItemError>, StatusCode> {
+    let from = PostStatus::parse(&post.status).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let to = match check(action, from, post.user_id == auth.id, auth.is_admin()) {
+        Ok(to) => to,
+        Err(error) => return Ok(Err(error)),
+    };
+
+    if let Some(to) = to {
+        PostService::apply_transition(conn, post.id, from, to, auth.id, None)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to {} post: {}", action, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        return Ok(Ok(()));
+    }
+
+    match action {
+        BulkPostAction::Retag => {
+            let tags = retagged(&post.tags, add_tags, remove_tags);
+            if tags == post.tags {
+                return Ok(Ok(()));
+            }
+            if tags.len() > MAX_TAGS_PER_POST {
+                return Ok(Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("A post can have at most {} tags", MAX_TAGS_PER_POST),
+                )));
+            }
+
+            tag_service::set_post_tags(conn, post.id, &tags)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to tag post: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            sqlx::query!("UPDATE posts SET updated_at = NOW() WHERE id = $1", post.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        }
+        BulkPostAction::Delete => {
+            sqlx::query!("UPDATE posts SET deleted_at = NOW() WHERE id = $1", post.id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to delete post: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+        }
+        BulkPostAction::Publish | BulkPostAction::Unpublish => {}
+    }
+
+    Ok(Ok(()))
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::ROLE_ADMIN;
+
+    fn user(id: i32, role: &str) -> AuthUser {
+        AuthUser {
+            id,
+            role: role.to_string(),
+        }
+    }
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn workflow_actions_follow_the_single_post_rules() {
+        assert_eq!(
+            check(BulkPostAction::Publish, PostStatus::Approved, true, false),
+            Ok(Some(PostStatus::Published))
+        );
+        assert_eq!(
+            check(BulkPostAction::Unpublish, PostStatus::Published, false, true),
+            Ok(Some(PostStatus::Approved))
+        );
+
+        let (status, error) =
+            check(BulkPostAction::Unpublish, PostStatus::Draft, true, false).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error, "Cannot unpublish a post that is draft");
+
+        let (status, _) =
+            check(BulkPostAction::Publish, PostStatus::InReview, true, true).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn other_users_posts_are_forbidden() {
+        for action in BulkPostAction::ALL {
+            let from = match action {
+                BulkPostAction::Publish => PostStatus::Approved,
+                _ => PostStatus::Published,
+            };
+            let (status, error) = check(action, from, false, false).unwrap_err();
+            assert_eq!(status, StatusCode::FORBIDDEN, "{action}");
+            assert_eq!(error, format!("You are not allowed to {} this post", action));
+
+            assert!(check(action, from, true, false).is_ok(), "{action}");
+            assert!(check(action, from, false, true).is_ok(), "{action}");
+        }
+    }
+
+    #[test]
+    fn unpublished_posts_of_others_are_hidden() {
+        let mut post = BulkTarget {
+            id: 1,
+            public_id: Uuid::nil(),
+            user_id: 2,
+            status: "draft".to_string(),
+            is_published: false,
+            tags: Vec::new(),
+        };
+        let (reader, admin) = (user(1, "user"), user(3, ROLE_ADMIN));
+
+        assert!(!is_visible(&post, &reader));
+        assert!(is_visible(&post, &admin));
+        post.user_id = reader.id;
+        assert!(is_visible(&post, &reader));
+        post.user_id = 2;
+        post.is_published = true;
+        assert!(is_visible(&post, &reader));
+    }
+
+    #[test]
+    fn retagging_adds_and_removes() {
+        let current = tags(&["rust", "web"]);
+        assert_eq!(
+            retagged(&current, &tags(&["axum", "rust"]), &tags(&["web"])),
+            tags(&["axum", "rust"])
+        );
+        assert_eq!(retagged(&current, &[], &tags(&["missing"])), current);
+    }
+
+    #[test]
+    fn report_keeps_the_request_order() {
+        let ids: Vec<Uuid> = (1..=4).map(Uuid::from_u128).collect();
+        let outcomes = vec![
+            (ids[0], Ok(())),
+            (ids[1], Err((StatusCode::NOT_FOUND, "Post not found".to_string()))),
+            (ids[2], Ok(())),
+            (ids[3], Err((StatusCode::FORBIDDEN, "nope".to_string()))),
+        ];
+
+        let (response, message) = report(BulkPostAction::Delete, outcomes);
+        assert_eq!(response.action, "delete");
+        assert_eq!(response.succeeded, vec![ids[0], ids[2]]);
+        let failed: Vec<(Uuid, u16, &str)> = response
+            .failed
+            .iter()
+            .map(|failure| (failure.id, failure.status, failure.error.as_str()))
+            .collect();
+        assert_eq!(failed, vec![(ids[1], 404, "Post not found"), (ids[3], 403, "nope")]);
+        assert_eq!(message, "2 of 4 posts updated");
+    }
+}
@@ -0,0 +1,774 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    database::{
+        connection::DbConn,
+        models::{CollectionPost, CollectionSummary},
+    },
+    handlers::post::visible_post_id,
+    models::{
+        requests::{
+            AddCollectionPostRequest, CreateCollectionRequest, PaginationParams, PatchCollectionRequest,
+            ReorderCollectionRequest,
+        },
+        responses::{
+            ApiResponse, CollectionDetailResponse, CollectionPostResponse, CollectionResponse,
+            PaginatedResponse, PostReadResponse,
+        },
+    },
+    services::collection_service::{self, MAX_COLLECTION_POSTS},
+};
+
+/// Collection looked up for a write
+struct CollectionTarget {
+    id: i32,
+    user_id: i32,
+}
+
+/// Create a collection
+///
+/// `post_ids` fills the collection in the given order. Only posts written
+/// by the caller can be added, except by admins.
+#[utoipa::path(
+    post,
+    path = "/api/collections",
+    request_body = CreateCollectionRequest,
+    responses(
+        (status = 201, description = "Collection created", body = CollectionApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "A post was written by someone else"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn create_collection(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Json(payload): Json<CreateCollectionRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let collection = sqlx::query!(
+        "INSERT INTO collections (user_id, title, description) VALUES ($1, $2, $3) RETURNING id, public_id",
+        auth.id,
+        payload.title,
+        payload.description
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to create collection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for id in payload.post_ids.unwrap_or_default() {
+        let post_id = match addable_post(&mut tx, &auth, id).await? {
+            Ok(post_id) => post_id,
+            Err(response) => return Ok(response),
+        };
+
+        collection_service::add_post(&mut tx, collection.id, post_id, None)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to add post to collection: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let collection = fetch_collection(&mut conn, collection.public_id, Some(&auth))
+        .await?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(collection, "Collection created successfully")),
+    )
+        .into_response())
+}
+
+/// List collections with the caller's progress through each
+///
+/// Collections without any post the caller can read are left out, unless
+/// the caller owns them or is an admin. Most recently changed first.
+#[utoipa::path(
+    get,
+    path = "/api/collections",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Collections", body = CollectionsApiResponse)
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn get_collections(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, StatusCode> {
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+    let viewer = auth.as_ref().map(|auth| auth.id);
+    let is_admin = auth.as_ref().is_some_and(AuthUser::is_admin);
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM collections c
+        WHERE c.user_id = $1 OR $2 OR EXISTS (
+            SELECT 1 FROM collection_posts cp
+            JOIN posts p ON p.id = cp.post_id
+            WHERE cp.collection_id = c.id AND p.deleted_at IS NULL
+              AND (p.is_published OR p.user_id = $1 OR $2)
+        )
+        "#,
+        viewer,
+        is_admin
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let collections = sqlx::query_as!(
+        CollectionSummary,
+        r#"
+        SELECT c.id, c.public_id, u.public_id AS user_public_id, c.title, c.description,
+               COUNT(p.id) AS "post_count!",
+               COUNT(r.post_id) AS "read_count!",
+               (ARRAY_AGG(p.public_id ORDER BY cp.position)
+                    FILTER (WHERE p.id IS NOT NULL AND r.post_id IS NULL))[1] AS "next_post_public_id?",
+               c.created_at, c.updated_at
+        FROM collections c
+        JOIN users u ON u.id = c.user_id
+        LEFT JOIN collection_posts cp ON cp.collection_id = c.id
+        LEFT JOIN posts p ON p.id = cp.post_id AND p.deleted_at IS NULL
+                         AND (p.is_published OR p.user_id = $1 OR $2)
+        LEFT JOIN post_reads r ON r.post_id = p.id AND r.user_id = $1
+        GROUP BY c.id, u.public_id
+        HAVING c.user_id = $1 OR $2 OR COUNT(p.id) > 0
+        ORDER BY c.updated_at DESC, c.id DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        viewer,
+        is_admin,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to list collections: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = PaginatedResponse {
+        data: collections.into_iter().map(CollectionResponse::from).collect::<Vec<_>>(),
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Collections retrieved successfully")).into_response())
+}
+
+/// Get a collection with its posts in order
+///
+/// Posts the caller cannot read are left out and the others numbered
+/// without them. Each post says whether the caller read it.
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    ),
+    responses(
+        (status = 200, description = "Collection found", body = CollectionApiResponse),
+        (status = 404, description = "Collection not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn get_collection(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    match fetch_collection(&mut conn, id, auth.as_ref()).await? {
+        Some(collection) => Ok(Json(ApiResponse::success(collection, "Collection found successfully")).into_response()),
+        None => Ok(not_found("Collection not found")),
+    }
+}
+
+/// Update the title or description of a collection (owner or admin)
+#[utoipa::path(
+    patch,
+    path = "/api/collections/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    ),
+    request_body = PatchCollectionRequest,
+    responses(
+        (status = 200, description = "Collection updated", body = CollectionApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the owner or an admin can change a collection"),
+        (status = 404, description = "Collection not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn patch_collection(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PatchCollectionRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() || matches!(payload.title, Some(None)) {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let collection = match editable_collection(&mut tx, &auth, id).await? {
+        Ok(collection) => collection,
+        Err(response) => return Ok(response),
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE collections
+        SET title = COALESCE($2, title),
+            description = CASE WHEN $3 THEN $4 ELSE description END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        collection.id,
+        payload.title.flatten(),
+        payload.description.is_some(),
+        payload.description.flatten()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update collection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&mut conn, id, &auth, "Collection updated successfully").await
+}
+
+/// Delete a collection (owner or admin)
+///
+/// The posts of the collection are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    ),
+    responses(
+        (status = 200, description = "Collection deleted", body = StringApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the owner or an admin can change a collection"),
+        (status = 404, description = "Collection not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn delete_collection(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let collection = match editable_collection(&mut tx, &auth, id).await? {
+        Ok(collection) => collection,
+        Err(response) => return Ok(response),
+    };
+
+    sqlx::query!("DELETE FROM collections WHERE id = $1", collection.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete collection: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(
+        "Collection deleted".to_string(),
+        "Collection deleted successfully",
+    ))
+    .into_response())
+}
+
+/// Add a post to a collection (owner or admin)
+///
+/// The post goes to `position`, moving the posts from there on one place
+/// down, or to the end. Only posts written by the caller can be added,
+/// except by admins.
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/posts",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    ),
+    request_body = AddCollectionPostRequest,
+    responses(
+        (status = 200, description = "Post added", body = CollectionApiResponse),
+        (status = 400, description = "Invalid input or the collection is full"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Not allowed to change the collection or add the post"),
+        (status = 404, description = "Collection or post not found"),
+        (status = 409, description = "The post is already in the collection")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn add_collection_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddCollectionPostRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let collection = match editable_collection(&mut tx, &auth, id).await? {
+        Ok(collection) => collection,
+        Err(response) => return Ok(response),
+    };
+    let post_id = match addable_post(&mut tx, &auth, payload.post_id).await? {
+        Ok(post_id) => post_id,
+        Err(response) => return Ok(response),
+    };
+
+    let current = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!", COALESCE(BOOL_OR(post_id = $2), FALSE) AS "contains!"
+        FROM collection_posts WHERE collection_id = $1
+        "#,
+        collection.id,
+        post_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if current.contains {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("The post is already in the collection")),
+        )
+            .into_response());
+    }
+    if current.count as usize >= MAX_COLLECTION_POSTS {
+        return Ok(bad_request(&format!(
+            "A collection holds at most {} posts",
+            MAX_COLLECTION_POSTS
+        )));
+    }
+
+    collection_service::add_post(&mut tx, collection.id, post_id, payload.position)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to add post to collection: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&mut conn, id, &auth, "Post added to the collection").await
+}
+
+/// Remove a post from a collection (owner or admin)
+///
+/// The posts after it move up one place. The post itself is kept.
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}/posts/{post_id}",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID"),
+        ("post_id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post removed", body = CollectionApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the owner or an admin can change a collection"),
+        (status = 404, description = "Collection not found or the post is not in it")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn remove_collection_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path((id, post_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, StatusCode> {
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let collection = match editable_collection(&mut tx, &auth, id).await? {
+        Ok(collection) => collection,
+        Err(response) => return Ok(response),
+    };
+
+    let post_id = sqlx::query_scalar!("SELECT id FROM posts WHERE public_id = $1", post_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let removed = match post_id {
+        Some(post_id) => collection_service::remove_post(&mut tx, collection.id, post_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to remove post from collection: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+        None => false,
+    };
+    if !removed {
+        return Ok(not_found("The post is not in the collection"));
+    }
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&mut conn, id, &auth, "Post removed from the collection").await
+}
+
+/// Reorder the posts of a collection (owner or admin)
+///
+/// `post_ids` must list every post of the collection exactly once, in the
+/// new order. Posts in the trash keep their place after the others.
+#[utoipa::path(
+    put,
+    path = "/api/collections/{id}/order",
+    params(
+        ("id" = Uuid, Path, description = "Collection ID")
+    ),
+    request_body = ReorderCollectionRequest,
+    responses(
+        (status = 200, description = "Posts reordered", body = CollectionApiResponse),
+        (status = 400, description = "The posts listed are not the posts of the collection"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the owner or an admin can change a collection"),
+        (status = 404, description = "Collection not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn reorder_collection(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReorderCollectionRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let collection = match editable_collection(&mut tx, &auth, id).await? {
+        Ok(collection) => collection,
+        Err(response) => return Ok(response),
+    };
+
+    let posts: HashMap<Uuid, i32> = sqlx::query!(
+        "SELECT p.id, p.public_id FROM collection_posts cp
+         JOIN posts p ON p.id = cp.post_id
+         WHERE cp.collection_id = $1 AND p.deleted_at IS NULL",
+        collection.id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .into_iter()
+    .map(|post| (post.public_id, post.id))
+    .collect();
+
+    // Ids are distinct, so listing as many known posts lists them all
+    let order: Vec<i32> = payload
+        .post_ids
+        .iter()
+        .filter_map(|id| posts.get(id).copied())
+        .collect();
+    if order.len() != payload.post_ids.len() || order.len() != posts.len() {
+        return Ok(bad_request("post_ids must list every post of the collection exactly once"));
+    }
+
+    collection_service::set_order(&mut tx, collection.id, &order)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reorder collection: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    collection_service::touch(&mut tx, collection.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    collection_response(&mut conn, id, &auth, "Collection reordered").await
+}
+
+/// Mark a post as read
+///
+/// Counts towards the caller's progress through the collections the post
+/// is part of. Marking a post twice keeps the first time it was read.
+#[utoipa::path(
+    put,
+    path = "/api/posts/{id}/read",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post marked as read", body = PostReadApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn mark_post_read(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let read_at = sqlx::query_scalar!(
+        "INSERT INTO post_reads (user_id, post_id) VALUES ($1, $2)
+         ON CONFLICT (user_id, post_id) DO UPDATE SET read_at = post_reads.read_at
+         RETURNING read_at",
+        auth.id,
+        post_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to mark post as read: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = PostReadResponse {
+        is_read: true,
+        read_at: Some(read_at),
+    };
+    Ok(Json(ApiResponse::success(response, "Post marked as read")).into_response())
+}
+
+/// Mark a post as not read
+///
+/// Succeeds whether or not the post was read.
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/read",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post marked as not read", body = PostReadApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Collections"
+)]
+pub async fn mark_post_unread(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    sqlx::query!(
+        "DELETE FROM post_reads WHERE user_id = $1 AND post_id = $2",
+        auth.id,
+        post_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to mark post as not read: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = PostReadResponse {
+        is_read: false,
+        read_at: None,
+    };
+    Ok(Json(ApiResponse::success(response, "Post marked as not read")).into_response())
+}
+
+/// Collection as seen by the caller, `None` when it is not listed for them
+async fn fetch_collection(
+    conn: &mut PgConnection,
+    id: Uuid,
+    auth: Option<&AuthUser>,
+) -> Result<Option<CollectionDetailResponse>, StatusCode> {
+    let viewer = auth.map(|auth| auth.id);
+    let is_admin = auth.is_some_and(AuthUser::is_admin);
+
+    let collection = sqlx::query_as!(
+        CollectionSummary,
+        r#"
+        SELECT c.id, c.public_id, u.public_id AS user_public_id, c.title, c.description,
+               COUNT(p.id) AS "post_count!",
+               COUNT(r.post_id) AS "read_count!",
+               (ARRAY_AGG(p.public_id ORDER BY cp.position)
+                    FILTER (WHERE p.id IS NOT NULL AND r.post_id IS NULL))[1] AS "next_post_public_id?",
+               c.created_at, c.updated_at
+        FROM collections c
+        JOIN users u ON u.id = c.user_id
+        LEFT JOIN collection_posts cp ON cp.collection_id = c.id
+        LEFT JOIN posts p ON p.id = cp.post_id AND p.deleted_at IS NULL
+                         AND (p.is_published OR p.user_id = $2 OR $3)
+        LEFT JOIN post_reads r ON r.post_id = p.id AND r.user_id = $2
+        WHERE c.public_id = $1
+        GROUP BY c.id, u.public_id
+        HAVING c.user_id = $2 OR $3 OR COUNT(p.id) > 0
+        "#,
+        id,
+        viewer,
+        is_admin
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load collection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let Some(collection) = collection else {
+        return Ok(None);
+    };
+
+    let posts = sqlx::query_as!(
+        CollectionPost,
+        r#"
+        SELECT ROW_NUMBER() OVER (ORDER BY cp.position) AS "position!",
+               p.public_id, p.slug, p.title, u.public_id AS user_public_id,
+               p.status, p.is_published, p.published_at, r.read_at AS "read_at?", cp.added_at
+        FROM collection_posts cp
+        JOIN posts p ON p.id = cp.post_id
+        JOIN users u ON u.id = p.user_id
+        LEFT JOIN post_reads r ON r.post_id = p.id AND r.user_id = $2
+        WHERE cp.collection_id = $1 AND p.deleted_at IS NULL
+          AND (p.is_published OR p.user_id = $2 OR $3)
+        ORDER BY cp.position
+        "#,
+        collection.id,
+        viewer,
+        is_admin
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load collection posts: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Some(CollectionDetailResponse {
+        collection: CollectionResponse::from(collection),
+        posts: posts.into_iter().map(CollectionPostResponse::from).collect(),
+    }))
+}
+
+/// Response to a change of a collection, showing it as it is now
+async fn collection_response(
+    conn: &mut PgConnection,
+    id: Uuid,
+    auth: &AuthUser,
+    message: &str,
+) -> Result<Response, StatusCode> {
+    let collection = fetch_collection(conn, id, Some(auth))
+        .await?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(ApiResponse::success(collection, message)).into_response())
+}
+
+/// Locks a collection the caller may change, being its owner or an admin,
+/// or returns the response explaining why they may not
+async fn editable_collection(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+) -> Result<Result<CollectionTarget, Response>, StatusCode> {
+    let collection = sqlx::query_as!(
+        CollectionTarget,
+        "SELECT id, user_id FROM collections WHERE public_id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match collection {
+        None => Ok(Err(not_found("Collection not found"))),
+        Some(collection) if collection.user_id != auth.id && !auth.is_admin() => Ok(Err(forbidden(
+            "Only the owner or an admin can change a collection",
+        ))),
+        Some(collection) => Ok(Ok(collection)),
+    }
+}
+
+/// Post the caller may put in a collection: one they wrote, or any post
+/// for admins
+async fn addable_post(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+) -> Result<Result<i32, Response>, StatusCode> {
+    let post = sqlx::query!(
+        "SELECT id, user_id FROM posts
+         WHERE public_id = $1 AND deleted_at IS NULL
+           AND (is_published OR user_id = $2 OR $3)",
+        id,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match post {
+        None => Ok(Err(not_found("Post not found"))),
+        Some(post) if post.user_id != auth.id && !auth.is_admin() => Ok(Err(forbidden(
+            "Only the author or an admin can add a post to a collection",
+        ))),
+        Some(post) => Ok(Ok(post.id)),
+    }
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
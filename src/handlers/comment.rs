@@ -0,0 +1,486 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    database::{connection::DbConn, models::Comment},
+    handlers::post::visible_post_id,
+    models::{
+        requests::{CommentListParams, CreateCommentRequest, PaginationParams, UpdateCommentRequest},
+        responses::{ApiResponse, CommentResponse, PaginatedResponse},
+    },
+    services::comment_service::{self, build_tree, CommentListMode},
+};
+
+/// Comment looked up for a write
+struct CommentTarget {
+    id: i32,
+    user_id: i32,
+    is_deleted: bool,
+}
+
+/// List the comments of a post
+///
+/// Deleted comments that still have replies stay in the thread with their
+/// content removed. Comments of a post in the trash are unavailable until
+/// the post is restored.
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/comments",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        PaginationParams,
+        CommentListParams
+    ),
+    responses(
+        (status = 200, description = "Comments of the post", body = CommentsApiResponse),
+        (status = 400, description = "Unknown mode"),
+        (status = 404, description = "Post not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn get_post_comments(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+    Query(list): Query<CommentListParams>,
+) -> Result<Response, StatusCode> {
+    let mode = match list.mode.as_deref() {
+        None => CommentListMode::Tree,
+        Some(value) => match CommentListMode::parse(value) {
+            Some(mode) => mode,
+            None => return Ok(bad_request(&format!("Unknown mode: {}", value))),
+        },
+    };
+
+    let Some(post_id) = visible_post_id(&mut conn, id, auth.as_ref()).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let viewer = auth.as_ref().map(|auth| auth.id);
+    let is_admin = auth.as_ref().is_some_and(AuthUser::is_admin);
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let (total, data) = match mode {
+        CommentListMode::Flat => {
+            let total = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM comments WHERE post_id = $1"#,
+                post_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            let comments = sqlx::query_as!(
+                Comment,
+                r#"
+                SELECT c.id, c.public_id, c.parent_id, pc.public_id AS "parent_public_id?",
+                       CASE WHEN c.deleted_at IS NULL THEN u.public_id END AS user_public_id,
+                       CASE WHEN c.deleted_at IS NULL AND (NOT c.is_hidden OR c.user_id = $4 OR $5)
+                            THEN c.content END AS content,
+                       c.is_hidden, c.deleted_at IS NOT NULL AS "is_deleted!",
+                       c.created_at, c.updated_at
+                FROM comments c
+                JOIN users u ON u.id = c.user_id
+                LEFT JOIN comments pc ON pc.id = c.parent_id
+                WHERE c.post_id = $1
+                ORDER BY c.created_at, c.id
+                LIMIT $2 OFFSET $3
+                "#,
+                post_id,
+                limit as i64,
+                offset as i64,
+                viewer,
+                is_admin
+            )
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            (total, comments.into_iter().map(CommentResponse::from).collect())
+        }
+        CommentListMode::Tree => {
+            let total = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM comments WHERE post_id = $1 AND parent_id IS NULL"#,
+                post_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            // Page through top-level comments, then pull in their whole threads
+            let comments = sqlx::query_as!(
+                Comment,
+                r#"
+                WITH RECURSIVE roots AS (
+                    SELECT id FROM comments
+                    WHERE post_id = $1 AND parent_id IS NULL
+                    ORDER BY created_at, id
+                    LIMIT $2 OFFSET $3
+                ), thread AS (
+                    SELECT id FROM roots
+                    UNION ALL
+                    SELECT c.id FROM comments c JOIN thread ON c.parent_id = thread.id
+                )
+                SELECT c.id, c.public_id, c.parent_id, pc.public_id AS "parent_public_id?",
+                       CASE WHEN c.deleted_at IS NULL THEN u.public_id END AS user_public_id,
+                       CASE WHEN c.deleted_at IS NULL AND (NOT c.is_hidden OR c.user_id = $4 OR $5)
+                            THEN c.content END AS content,
+                       c.is_hidden, c.deleted_at IS NOT NULL AS "is_deleted!",
+                       c.created_at, c.updated_at
+                FROM thread
+                JOIN comments c ON c.id = thread.id
+                JOIN users u ON u.id = c.user_id
+                LEFT JOIN comments pc ON pc.id = c.parent_id
+                ORDER BY c.created_at, c.id
+                "#,
+                post_id,
+                limit as i64,
+                offset as i64,
+                viewer,
+                is_admin
+            )
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            (total, build_tree(comments))
+        }
+    };
+
+    let total = total as u64;
+    let response = PaginatedResponse {
+        data,
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Comments retrieved successfully")).into_response())
+}
+
+/// Comment on a post or reply to a comment
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/comments",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 201, description = "Comment created", body = CommentApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post or parent comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn create_comment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateCommentRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let parent_id = match payload.parent_id {
+        None => None,
+        Some(parent_id) => match find_comment(&mut conn, post_id, parent_id).await? {
+            Some(parent) if !parent.is_deleted => Some(parent.id),
+            _ => return Ok(not_found("Parent comment not found")),
+        },
+    };
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let comment_id = comment_service::create(&mut tx, post_id, parent_id, auth.id, &payload.content)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create comment: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let comment = fetch_comment(&mut conn, comment_id, &auth).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(comment, "Comment created successfully")),
+    )
+        .into_response())
+}
+
+/// Edit a comment (author only)
+#[utoipa::path(
+    patch,
+    path = "/api/posts/{id}/comments/{comment_id}",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("comment_id" = Uuid, Path, description = "Comment ID")
+    ),
+    request_body = UpdateCommentRequest,
+    responses(
+        (status = 200, description = "Comment updated", body = CommentApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author can edit a comment"),
+        (status = 404, description = "Post or comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn update_comment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateCommentRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let comment = match writable_comment(&mut conn, &auth, id, comment_id).await? {
+        Ok(comment) => comment,
+        Err(response) => return Ok(response),
+    };
+
+    if comment.user_id != auth.id {
+        return Ok(forbidden("Only the author can edit a comment"));
+    }
+
+    sqlx::query!(
+        "UPDATE comments SET content = $2, updated_at = NOW() WHERE id = $1",
+        comment.id,
+        payload.content
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update comment: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let comment = fetch_comment(&mut conn, comment.id, &auth).await?;
+    Ok(Json(ApiResponse::success(comment, "Comment updated successfully")).into_response())
+}
+
+/// Delete a comment (author or admin)
+///
+/// A comment with replies is kept as a placeholder so the thread stays
+/// intact; otherwise it is removed.
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/comments/{comment_id}",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("comment_id" = Uuid, Path, description = "Comment ID")
+    ),
+    responses(
+        (status = 200, description = "Comment deleted", body = StringApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can delete a comment"),
+        (status = 404, description = "Post or comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn delete_comment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, StatusCode> {
+    let comment = match writable_comment(&mut conn, &auth, id, comment_id).await? {
+        Ok(comment) => comment,
+        Err(response) => return Ok(response),
+    };
+
+    if comment.user_id != auth.id && !auth.is_admin() {
+        return Ok(forbidden("Only the author or an admin can delete a comment"));
+    }
+
+    let deleted = comment_service::delete(&mut conn, comment.id).await.map_err(|e| {
+        tracing::error!("Failed to delete comment: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !deleted {
+        return Ok(not_found("Comment not found"));
+    }
+
+    Ok(Json(ApiResponse::success(
+        "Comment deleted".to_string(),
+        "Comment deleted successfully",
+    ))
+    .into_response())
+}
+
+/// Hide a comment from other users (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/comments/{comment_id}/hide",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("comment_id" = Uuid, Path, description = "Comment ID")
+    ),
+    responses(
+        (status = 200, description = "Comment hidden", body = CommentApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate comments"),
+        (status = 404, description = "Post or comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn hide_comment(
+    conn: DbConn,
+    auth: AuthUser,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, StatusCode> {
+    moderate_comment(conn, auth, id, comment_id, true).await
+}
+
+/// Show a hidden comment again (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/comments/{comment_id}/unhide",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("comment_id" = Uuid, Path, description = "Comment ID")
+    ),
+    responses(
+        (status = 200, description = "Comment visible again", body = CommentApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate comments"),
+        (status = 404, description = "Post or comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Comments"
+)]
+pub async fn unhide_comment(
+    conn: DbConn,
+    auth: AuthUser,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, StatusCode> {
+    moderate_comment(conn, auth, id, comment_id, false).await
+}
+
+async fn moderate_comment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    id: Uuid,
+    comment_id: Uuid,
+    hide: bool,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can moderate comments"));
+    }
+
+    let comment = match writable_comment(&mut conn, &auth, id, comment_id).await? {
+        Ok(comment) => comment,
+        Err(response) => return Ok(response),
+    };
+
+    comment_service::set_hidden(&mut conn, comment.id, hide, Some(auth.id))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to moderate comment: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let comment = fetch_comment(&mut conn, comment.id, &auth).await?;
+    let message = if hide { "Comment hidden" } else { "Comment visible again" };
+    Ok(Json(ApiResponse::success(comment, message)).into_response())
+}
+
+/// Comment of a visible post that has not been deleted, or the response
+/// explaining why there is none
+async fn writable_comment(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+    comment_id: Uuid,
+) -> Result<Result<CommentTarget, Response>, StatusCode> {
+    let Some(post_id) = visible_post_id(conn, id, Some(auth)).await? else {
+        return Ok(Err(not_found("Post not found")));
+    };
+
+    match find_comment(conn, post_id, comment_id).await? {
+        Some(comment) if !comment.is_deleted => Ok(Ok(comment)),
+        _ => Ok(Err(not_found("Comment not found"))),
+    }
+}
+
+async fn find_comment(
+    conn: &mut PgConnection,
+    post_id: i32,
+    comment_id: Uuid,
+) -> Result<Option<CommentTarget>, StatusCode> {
+    sqlx::query_as!(
+        CommentTarget,
+        r#"
+        SELECT id, user_id, deleted_at IS NOT NULL AS "is_deleted!"
+        FROM comments
+        WHERE public_id = $1 AND post_id = $2
+        "#,
+        comment_id,
+        post_id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn fetch_comment(
+    conn: &mut PgConnection,
+    comment_id: i32,
+    auth: &AuthUser,
+) -> Result<CommentResponse, StatusCode> {
+    sqlx::query_as!(
+        Comment,
+        r#"
+        SELECT c.id, c.public_id, c.parent_id, pc.public_id AS "parent_public_id?",
+               CASE WHEN c.deleted_at IS NULL THEN u.public_id END AS user_public_id,
+               CASE WHEN c.deleted_at IS NULL AND (NOT c.is_hidden OR c.user_id = $2 OR $3)
+                    THEN c.content END AS content,
+               c.is_hidden, c.deleted_at IS NOT NULL AS "is_deleted!",
+               c.created_at, c.updated_at
+        FROM comments c
+        JOIN users u ON u.id = c.user_id
+        LEFT JOIN comments pc ON pc.id = c.parent_id
+        WHERE c.id = $1
+        "#,
+        comment_id,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_one(conn)
+    .await
+    .map(CommentResponse::from)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
@@ -0,0 +1,246 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use axum::{
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::models::responses::ApiResponse;
+
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Strong entity tag for a resource, derived from its `updated_at`
+pub fn entity_tag(updated_at: &DateTime<Utc>) -> String {
+    format!("\"{:x}\"", updated_at.timestamp_micros())
+}
+
+/// Strong entity tag for a representation that carries more than the
+/// resource itself, such as counters or the caller's own reaction. It
+/// starts with the version of the resource so it still works for `If-Match`.
+pub fn representation_tag(updated_at: &DateTime<Utc>, body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:x}-{:x}\"", updated_at.timestamp_micros(), hasher.finish())
+}
+
+/// `ETag` and `Last-Modified` headers for a resource
+pub fn validator_headers(updated_at: &DateTime<Utc>) -> [(HeaderName, String); 2] {
+    [
+        (header::ETAG, entity_tag(updated_at)),
+        (header::LAST_MODIFIED, updated_at.format(HTTP_DATE).to_string()),
+    ]
+}
+
+/// `ETag` and `Vary` headers for a representation that depends on the caller
+pub fn representation_headers(tag: &str) -> [(HeaderName, String); 2] {
+    [
+        (header::ETAG, tag.to_string()),
+        (header::VARY, header::AUTHORIZATION.to_string()),
+    ]
+}
+
+/// Evaluates `If-None-Match`, falling back to `If-Modified-Since`, for a GET
+pub fn is_not_modified(headers: &HeaderMap, updated_at: &DateTime<Utc>) -> bool {
+    if let Some(value) = header_str(headers, header::IF_NONE_MATCH) {
+        return none_match(value, &entity_tag(updated_at));
+    }
+
+    header_str(headers, header::IF_MODIFIED_SINCE)
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+        .map(|since| updated_at.timestamp() <= since.timestamp())
+        .unwrap_or(false)
+}
+
+/// Evaluates `If-None-Match` against a representation tag for a GET.
+/// `If-Modified-Since` is not used: the representation changes without
+/// the resource being modified.
+pub fn is_representation_unchanged(headers: &HeaderMap, tag: &str) -> bool {
+    header_str(headers, header::IF_NONE_MATCH).is_some_and(|value| none_match(value, tag))
+}
+
+fn none_match(value: &str, current: &str) -> bool {
+    value.trim() == "*"
+        || value
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == current)
+}
+
+/// Parsed `If-Match` header of a write request
+#[derive(Debug)]
+pub enum IfMatch {
+    Absent,
+    Any,
+    Versions(Vec<DateTime<Utc>>),
+}
+
+impl IfMatch {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let Some(value) = header_str(headers, header::IF_MATCH) else {
+            return Self::Absent;
+        };
+
+        if value.trim() == "*" {
+            return Self::Any;
+        }
+
+        // Weak or foreign tags never match under strong comparison. A
+        // representation tag names the version before its `-`.
+        let versions = value
+            .split(',')
+            .filter_map(|tag| tag.trim().strip_prefix('"')?.strip_suffix('"'))
+            .filter_map(|tag| tag.split('-').next())
+            .filter_map(|tag| i64::from_str_radix(tag, 16).ok())
+            .filter_map(|micros| Utc.timestamp_micros(micros).single())
+            .collect();
+
+        Self::Versions(versions)
+    }
+
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    /// `updated_at` values the write is allowed to replace, `None` for any
+    pub fn versions(&self) -> Option<&[DateTime<Utc>]> {
+        match self {
+            Self::Versions(versions) => Some(versions),
+            _ => None,
+        }
+    }
+}
+
+pub fn not_modified(updated_at: &DateTime<Utc>) -> Response {
+    (StatusCode::NOT_MODIFIED, validator_headers(updated_at)).into_response()
+}
+
+pub fn precondition_failed() -> Response {
+    (
+        StatusCode::PRECONDITION_FAILED,
+        Json(ApiResponse::<()>::error(
+            "Resource has been modified since it was fetched",
+        )),
+    )
+        .into_response()
+}
+
+pub fn precondition_required() -> Response {
+    (
+        StatusCode::PRECONDITION_REQUIRED,
+        Json(ApiResponse::<()>::error("If-Match header is required")),
+    )
+        .into_response()
+}
+
+fn header_str(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn at(micros: i64) -> DateTime<Utc> {
+        Utc.timestamp_micros(micros).unwrap()
+    }
+
+    fn if_match(value: &str) -> IfMatch {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_str(value).unwrap());
+        IfMatch::from_headers(&headers)
+    }
+
+    fn versions(value: &str) -> Vec<DateTime<Utc>> {
+        if_match(value).versions().expect("a list of versions").to_vec()
+    }
+
+    #[test]
+    fn if_match_is_absent_without_the_header() {
+        let parsed = IfMatch::from_headers(&HeaderMap::new());
+        assert!(parsed.is_absent());
+        assert!(parsed.versions().is_none());
+    }
+
+    #[test]
+    fn if_match_star_allows_any_version() {
+        for value in ["*", " * "] {
+            let parsed = if_match(value);
+            assert!(!parsed.is_absent());
+            assert!(parsed.versions().is_none(), "{value}");
+        }
+    }
+
+    #[test]
+    fn if_match_reads_entity_and_representation_tags() {
+        let (first, second) = (at(1_700_000_000_000_000), at(1_700_000_000_000_001));
+
+        assert_eq!(versions(&entity_tag(&first)), vec![first]);
+        assert_eq!(versions(&representation_tag(&second, b"{}")), vec![second]);
+
+        let list = format!("{}, {}", entity_tag(&first), representation_tag(&second, b"{}"));
+        assert_eq!(versions(&list), vec![first, second]);
+    }
+
+    #[test]
+    fn if_match_ignores_weak_and_malformed_tags() {
+        let version = at(1_700_000_000_000_000);
+        let weak = format!("W/{}", entity_tag(&version));
+
+        assert!(versions(&weak).is_empty());
+        assert_eq!(versions(&format!("{}, {}", weak, entity_tag(&version))), vec![version]);
+        for value in ["", "\"\"", "\"xyz\"", "6553f100", "\"-1\"", "\"6553f100", "garbage, \"\""] {
+            assert!(versions(value).is_empty(), "{value}");
+        }
+    }
+
+    #[test]
+    fn none_match_compares_weakly() {
+        let tag = entity_tag(&at(42));
+
+        assert!(none_match(&tag, &tag));
+        assert!(none_match(&format!("W/{}", tag), &tag));
+        assert!(none_match(&format!("\"other\", W/{} ,\"more\"", tag), &tag));
+        assert!(none_match("*", &tag));
+        assert!(none_match(" * ", &tag));
+    }
+
+    #[test]
+    fn none_match_rejects_other_and_malformed_tags() {
+        let tag = entity_tag(&at(42));
+
+        assert!(!none_match(&entity_tag(&at(43)), &tag));
+        assert!(!none_match("", &tag));
+        assert!(!none_match("2a", &tag));
+        assert!(!none_match("\"*\"", &tag));
+        assert!(!none_match("*, \"other\"", &tag));
+    }
+
+    #[test]
+    fn representation_tags_follow_version_and_body() {
+        let version = at(1_700_000_000_000_000);
+        let tag = representation_tag(&version, b"{\"likes\":1}");
+
+        assert_eq!(tag, representation_tag(&version, b"{\"likes\":1}"));
+        assert_ne!(tag, representation_tag(&version, b"{\"likes\":2}"));
+        assert_ne!(tag, representation_tag(&at(1_700_000_000_000_001), b"{\"likes\":1}"));
+        assert!(tag.starts_with(&format!("\"{:x}-", version.timestamp_micros())));
+    }
+
+    #[test]
+    fn representations_are_unchanged_only_for_a_matching_tag() {
+        let tag = representation_tag(&at(42), b"{}");
+        let mut headers = HeaderMap::new();
+        assert!(!is_representation_unchanged(&headers, &tag));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&tag).unwrap());
+        assert!(is_representation_unchanged(&headers, &tag));
+        assert!(!is_representation_unchanged(&headers, &representation_tag(&at(42), b"[]")));
+
+        // The resource tag alone does not match a representation
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&entity_tag(&at(42))).unwrap());
+        assert!(!is_representation_unchanged(&headers, &tag));
+    }
+}
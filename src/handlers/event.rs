@@ -0,0 +1,125 @@
+use std::{collections::HashSet, convert::Infallible};
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use tokio_stream::{wrappers::errors::BroadcastStreamRecvError, wrappers::BroadcastStream, StreamExt};
+
+use crate::{
+    auth::AuthUser,
+    config::AppState,
+    database::models::PostEvent,
+    models::{requests::EventStreamParams, responses::ApiResponse},
+    services::event_service::{self, EventTopic, Subscription, MAX_TOPICS},
+};
+
+/// Stream post and comment events (Server-Sent Events)
+///
+/// Sends `post.created`, `post.updated`, `post.published`, `post.deleted`
+/// and `comment.created` events whose data is the post or comment as JSON.
+/// Events of unpublished posts only reach their author and admins.
+///
+/// A client reconnecting with `Last-Event-ID` first gets the events it
+/// missed. When they are no longer in the event log, or the client falls
+/// too far behind, a `reset` event tells it to reload its data instead.
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    params(EventStreamParams),
+    responses(
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Unknown topic or invalid Last-Event-ID")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Events"
+)]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    headers: HeaderMap,
+    Query(params): Query<EventStreamParams>,
+) -> Result<Response, StatusCode> {
+    let mut topics = Vec::new();
+    for value in params.topics.as_deref().unwrap_or("posts,comments").split(',') {
+        match EventTopic::parse(value.trim()) {
+            Some(topic) if !topics.contains(&topic) => topics.push(topic),
+            Some(_) => {}
+            None => return Ok(bad_request(&format!("Unknown topic: {}", value.trim()))),
+        }
+    }
+    if topics.len() > MAX_TOPICS {
+        return Ok(bad_request(&format!("At most {} topics per stream", MAX_TOPICS)));
+    }
+
+    let last_event_id = match headers.get("last-event-id") {
+        Some(value) => match value.to_str().ok().and_then(|value| value.trim().parse::<i64>().ok()) {
+            Some(id) => Some(id),
+            None => return Ok(bad_request("Invalid Last-Event-ID")),
+        },
+        None => params.last_event_id,
+    };
+
+    let subscription = Subscription::new(topics, auth.as_ref());
+
+    // Subscribed before reading the log so no event falls in between
+    let live = BroadcastStream::new(state.events.subscribe());
+
+    let mut missed: Vec<Result<Event, Infallible>> = Vec::new();
+    let mut replayed = HashSet::new();
+    if let Some(last_event_id) = last_event_id {
+        let is_logged = event_service::is_logged(&state.db, last_event_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        if is_logged {
+            let events = event_service::events_after(&state.db, last_event_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to read the event log: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            for event in events {
+                replayed.insert(event.id);
+                if subscription.accepts(&event) {
+                    missed.push(Ok(sse_event(&event)));
+                }
+            }
+        } else {
+            missed.push(Ok(reset_event("Missed events are no longer available")));
+        }
+    }
+
+    let live = live.filter_map(move |message| match message {
+        Ok(event) if replayed.contains(&event.id) => None,
+        Ok(event) => subscription.accepts(&event).then(|| Ok(sse_event(&event))),
+        Err(BroadcastStreamRecvError::Lagged(_)) => Some(Ok(reset_event("Too many events were missed"))),
+    });
+
+    let stream = tokio_stream::iter(missed).chain(live);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+fn sse_event(event: &PostEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(&event.kind)
+        .data(event.payload.to_string())
+}
+
+/// Tells the client to reload instead of relying on the events it got
+fn reset_event(reason: &str) -> Event {
+    Event::default()
+        .event("reset")
+        .data(serde_json::json!({ "reason": reason }).to_string())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
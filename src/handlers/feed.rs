@@ -0,0 +1,260 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    database::connection::DbConn,
+    handlers::conditional,
+    models::responses::ApiResponse,
+    services::{
+        feed_service::{Feed, FeedEntry, FeedFormat, FEED_LENGTH},
+        render_service,
+        tag_service::normalize_tag,
+    },
+    AppState,
+};
+
+/// Posts a feed is made of
+enum FeedScope {
+    All,
+    Author(Uuid),
+    Tag(String),
+}
+
+/// Feed of the latest published posts
+///
+/// `posts.atom`, `posts.rss` or `posts.json` (JSON Feed 1.1).
+#[utoipa::path(
+    get,
+    path = "/feeds/{file}",
+    params(
+        ("file" = String, Path, description = "`posts.atom`, `posts.rss` or `posts.json`")
+    ),
+    responses(
+        (status = 200, description = "Feed document", content_type = "application/atom+xml"),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
+        (status = 404, description = "Unknown feed")
+    ),
+    tag = "Feeds"
+)]
+pub async fn get_posts_feed(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Path(file): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    feed_response(&mut conn, &state, FeedScope::All, &file, &headers).await
+}
+
+/// Feed of the latest published posts of a user
+#[utoipa::path(
+    get,
+    path = "/feeds/users/{id}/{file}",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        ("file" = String, Path, description = "`posts.atom`, `posts.rss` or `posts.json`")
+    ),
+    responses(
+        (status = 200, description = "Feed document", content_type = "application/atom+xml"),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
+        (status = 404, description = "Unknown feed or user")
+    ),
+    tag = "Feeds"
+)]
+pub async fn get_user_posts_feed(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Path((id, file)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    feed_response(&mut conn, &state, FeedScope::Author(id), &file, &headers).await
+}
+
+/// Feed of the latest published posts with a tag
+#[utoipa::path(
+    get,
+    path = "/feeds/tags/{name}/{file}",
+    params(
+        ("name" = String, Path, description = "Tag name"),
+        ("file" = String, Path, description = "`posts.atom`, `posts.rss` or `posts.json`")
+    ),
+    responses(
+        (status = 200, description = "Feed document", content_type = "application/atom+xml"),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
+        (status = 404, description = "Unknown feed or tag")
+    ),
+    tag = "Feeds"
+)]
+pub async fn get_tag_posts_feed(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    Path((name, file)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    feed_response(&mut conn, &state, FeedScope::Tag(normalize_tag(&name)), &file, &headers).await
+}
+
+async fn feed_response(
+    conn: &mut PgConnection,
+    state: &AppState,
+    scope: FeedScope,
+    file: &str,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let Some(format) = FeedFormat::from_file_name(file) else {
+        return Ok(not_found("Feed not found"));
+    };
+
+    let base_url = &state.config.public_base_url;
+    let (title, path) = match &scope {
+        FeedScope::All => ("Posts".to_string(), "/feeds".to_string()),
+        FeedScope::Author(id) => {
+            let username = sqlx::query_scalar!(
+                "SELECT username FROM users WHERE public_id = $1 AND deleted_at IS NULL",
+                id
+            )
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            let Some(username) = username else {
+                return Ok(not_found("User not found"));
+            };
+            (format!("Posts by {}", username), format!("/feeds/users/{}", id))
+        }
+        FeedScope::Tag(name) => {
+            let exists = sqlx::query_scalar!(
+                r#"SELECT EXISTS(SELECT 1 FROM tags WHERE name = $1) AS "exists!""#,
+                name
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            if !exists {
+                return Ok(not_found("Tag not found"));
+            }
+            (
+                format!("Posts tagged {}", name),
+                format!("/feeds/tags/{}", encode_path_segment(name)),
+            )
+        }
+    };
+
+    let (author, tag) = match scope {
+        FeedScope::All => (None, None),
+        FeedScope::Author(id) => (Some(id), None),
+        FeedScope::Tag(name) => (None, Some(name)),
+    };
+
+    // Posts that were unpublished or trashed still count, so that dropping
+    // out of the feed changes its validators as well
+    let last_modified = sqlx::query_scalar!(
+        r#"
+        SELECT MAX(GREATEST(p.updated_at, p.deleted_at))
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE ($1::uuid IS NULL OR u.public_id = $1)
+          AND ($2::varchar IS NULL OR EXISTS (
+                SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE pt.post_id = p.id AND t.name = $2))
+        "#,
+        author,
+        tag
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+
+    if conditional::is_not_modified(headers, &last_modified) {
+        return Ok(conditional::not_modified(&last_modified));
+    }
+
+    let posts = sqlx::query!(
+        r#"
+        SELECT p.public_id, p.slug, p.title, p.content, p.content_format, p.content_html,
+               ARRAY(SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                     WHERE pt.post_id = p.id ORDER BY t.name) AS "tags!",
+               u.username,
+               COALESCE(p.published_at, p.created_at) AS "published_at!",
+               p.updated_at
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.is_published AND p.deleted_at IS NULL
+          AND ($1::uuid IS NULL OR u.public_id = $1)
+          AND ($2::varchar IS NULL OR EXISTS (
+                SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE pt.post_id = p.id AND t.name = $2))
+        ORDER BY COALESCE(p.published_at, p.created_at) DESC, p.id DESC
+        LIMIT $3
+        "#,
+        author,
+        tag,
+        FEED_LENGTH
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let entries: Vec<FeedEntry> = posts
+        .into_iter()
+        .map(|post| {
+            let rendered = render_service::rendered(&post.content, &post.content_format, post.content_html);
+            FeedEntry {
+                id: post.public_id,
+                url: format!("{}/api/posts/by-slug/{}", base_url, post.slug),
+                title: post.title,
+                author: post.username,
+                tags: post.tags,
+                summary: rendered.excerpt,
+                content_html: rendered.html,
+                published: post.published_at,
+                updated: post.updated_at,
+            }
+        })
+        .collect();
+
+    let feed = Feed {
+        title,
+        feed_url: format!("{}{}/{}", base_url, path, file),
+        home_page_url: base_url.clone(),
+        updated: entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or(last_modified),
+        entries,
+    };
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, format.content_type())],
+        conditional::validator_headers(&last_modified),
+        feed.render(format),
+    )
+        .into_response())
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+/// Percent-encodes everything but unreserved characters (RFC 3986)
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
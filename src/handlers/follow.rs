@@ -0,0 +1,379 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    database::connection::DbConn,
+    handlers::post::load_posts,
+    models::{
+        requests::{PaginationParams, PostFeedParams},
+        responses::{
+            ApiResponse, FollowResponse, FollowedUserResponse, PaginatedResponse, PostFeedResponse,
+            PostResponse,
+        },
+    },
+    services::follow_service::{FeedCursor, DEFAULT_FEED_PAGE, MAX_FEED_PAGE},
+};
+
+/// Follow a user
+///
+/// Following a user twice keeps a single follow.
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}/follow",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User followed", body = FollowApiResponse),
+        (status = 400, description = "Users cannot follow themselves"),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Follows"
+)]
+pub async fn follow_user(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(user_id) = live_user_id(&mut conn, id).await? else {
+        return Ok(user_not_found());
+    };
+
+    if user_id == auth.id {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("You cannot follow yourself")),
+        )
+            .into_response());
+    }
+
+    sqlx::query!(
+        "INSERT INTO follows (follower_id, followee_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        auth.id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to follow user: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = FollowResponse {
+        is_following: true,
+        follower_count: follower_count(&mut conn, user_id).await?,
+    };
+    Ok(Json(ApiResponse::success(response, "User followed")).into_response())
+}
+
+/// Stop following a user
+///
+/// Succeeds whether or not the caller followed the user.
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}/follow",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User unfollowed", body = FollowApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "User not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Follows"
+)]
+pub async fn unfollow_user(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(user_id) = live_user_id(&mut conn, id).await? else {
+        return Ok(user_not_found());
+    };
+
+    sqlx::query!(
+        "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2",
+        auth.id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to unfollow user: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = FollowResponse {
+        is_following: false,
+        follower_count: follower_count(&mut conn, user_id).await?,
+    };
+    Ok(Json(ApiResponse::success(response, "User unfollowed")).into_response())
+}
+
+/// List the followers of a user, most recent first
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/followers",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        PaginationParams
+    ),
+    responses(
+        (status = 200, description = "Followers", body = FollowsApiResponse),
+        (status = 404, description = "User not found")
+    ),
+    tag = "Follows"
+)]
+pub async fn get_followers(
+    mut conn: DbConn,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, StatusCode> {
+    let Some(user_id) = live_user_id(&mut conn, id).await? else {
+        return Ok(user_not_found());
+    };
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM follows f
+        JOIN users u ON u.id = f.follower_id
+        WHERE f.followee_id = $1 AND u.deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let users = sqlx::query_as!(
+        FollowedUserResponse,
+        r#"
+        SELECT u.public_id AS id, u.username, u.full_name, f.created_at AS followed_at
+        FROM follows f
+        JOIN users u ON u.id = f.follower_id
+        WHERE f.followee_id = $1 AND u.deleted_at IS NULL
+        ORDER BY f.created_at DESC, u.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let response = PaginatedResponse {
+        data: users,
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Followers retrieved successfully")).into_response())
+}
+
+/// List the users a user follows, most recent first
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/following",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        PaginationParams
+    ),
+    responses(
+        (status = 200, description = "Followed users", body = FollowsApiResponse),
+        (status = 404, description = "User not found")
+    ),
+    tag = "Follows"
+)]
+pub async fn get_following(
+    mut conn: DbConn,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, StatusCode> {
+    let Some(user_id) = live_user_id(&mut conn, id).await? else {
+        return Ok(user_not_found());
+    };
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM follows f
+        JOIN users u ON u.id = f.followee_id
+        WHERE f.follower_id = $1 AND u.deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let users = sqlx::query_as!(
+        FollowedUserResponse,
+        r#"
+        SELECT u.public_id AS id, u.username, u.full_name, f.created_at AS followed_at
+        FROM follows f
+        JOIN users u ON u.id = f.followee_id
+        WHERE f.follower_id = $1 AND u.deleted_at IS NULL
+        ORDER BY f.created_at DESC, u.id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let response = PaginatedResponse {
+        data: users,
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Following retrieved successfully")).into_response())
+}
+
+/// Published posts of the authors the caller follows, newest first
+///
+/// Pages are linked by cursor: pass `next_cursor` of a page as `cursor` to
+/// get the next one. Posts published in the meantime do not shift pages.
+#[utoipa::path(
+    get,
+    path = "/api/feed",
+    params(PostFeedParams),
+    responses(
+        (status = 200, description = "A page of the feed", body = PostFeedApiResponse),
+        (status = 400, description = "Invalid cursor"),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Follows"
+)]
+pub async fn get_feed(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PostFeedParams>,
+) -> Result<Response, StatusCode> {
+    let limit = params.limit.unwrap_or(DEFAULT_FEED_PAGE).clamp(1, MAX_FEED_PAGE) as i64;
+
+    let cursor = match params.cursor.as_deref() {
+        None => None,
+        Some(value) => match FeedCursor::parse(value) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return Ok((StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error("Invalid cursor")))
+                    .into_response());
+            }
+        },
+    };
+
+    // Each followed author contributes at most one page of their newest
+    // posts, read from idx_posts_author_published, before the pages are
+    // merged. The cost depends on the number of followed authors, not on
+    // the size of posts.
+    let ids = sqlx::query_scalar!(
+        r#"
+        SELECT latest.id AS "id!"
+        FROM follows f
+        CROSS JOIN LATERAL (
+            SELECT lp.id, COALESCE(lp.published_at, lp.created_at) AS feed_at, lp.public_id
+            FROM posts lp
+            WHERE lp.user_id = f.followee_id AND lp.is_published AND lp.deleted_at IS NULL
+              AND ($2::timestamptz IS NULL
+                   OR (COALESCE(lp.published_at, lp.created_at), lp.public_id) < ($2, $3))
+            ORDER BY COALESCE(lp.published_at, lp.created_at) DESC, lp.public_id DESC
+            LIMIT $4
+        ) latest
+        WHERE f.follower_id = $1
+        ORDER BY latest.feed_at DESC, latest.public_id DESC
+        LIMIT $4
+        "#,
+        auth.id,
+        cursor.map(|cursor| cursor.published_at),
+        cursor.map(|cursor| cursor.post_id),
+        limit + 1
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load feed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut posts = load_posts(&mut conn, &ids, Some(auth.id)).await?;
+
+    // The extra post only tells whether there is a next page
+    let next_cursor = if posts.len() as i64 > limit {
+        posts.truncate(limit as usize);
+        posts.last().map(|post| {
+            FeedCursor {
+                published_at: post.published_at.unwrap_or(post.created_at),
+                post_id: post.public_id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    let response = PostFeedResponse {
+        data: posts.into_iter().map(PostResponse::from).collect(),
+        next_cursor,
+    };
+
+    Ok(Json(ApiResponse::success(response, "Feed retrieved successfully")).into_response())
+}
+
+async fn live_user_id(conn: &mut PgConnection, id: Uuid) -> Result<Option<i32>, StatusCode> {
+    sqlx::query_scalar!(
+        "SELECT id FROM users WHERE public_id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn follower_count(conn: &mut PgConnection, user_id: i32) -> Result<i64, StatusCode> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM follows f
+        JOIN users u ON u.id = f.follower_id
+        WHERE f.followee_id = $1 AND u.deleted_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn user_not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("User not found"))).into_response()
+}
@@ -1,7 +1,21 @@
+pub mod attachment;
+pub mod auth;
+pub mod batch;
+pub mod bookmark;
+pub mod bulk;
+pub mod collection;
+pub mod comment;
+pub mod conditional;
+pub mod event;
+pub mod feed;
+pub mod follow;
 pub mod health;
+pub mod moderation;
+pub mod notification;
 pub mod post;
+pub mod reaction;
+pub mod revision;
+pub mod tag;
+pub mod trash;
 pub mod user;
-
-pub use health::*;
-pub use post::*;
-pub use user::*;
\ No newline at end of file
+pub mod workflow;
\ No newline at end of file
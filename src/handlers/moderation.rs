@@ -0,0 +1,581 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    config::AppState,
+    database::{
+        connection::DbConn,
+        models::{ModerationLogEntry, QueuedReport},
+    },
+    handlers::post::visible_post_id,
+    models::{
+        requests::{ModerationActionRequest, ModerationLogParams, PaginationParams, ReportRequest},
+        responses::{
+            ApiResponse, ModerationActionResponse, ModerationQueueItemResponse, PaginatedResponse,
+            ReportResponse,
+        },
+    },
+    services::moderation_service::{self, ModerationAction, ReportReason, ReportTarget},
+};
+
+/// Length of the content excerpt shown in the moderation queue
+const EXCERPT_LENGTH: i32 = 200;
+
+/// Report a post as spam or inappropriate
+///
+/// A user has one open report per post. Once enough different users
+/// reported it, the post is hidden until a moderator reviews it.
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/report",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body = ReportRequest,
+    responses(
+        (status = 201, description = "Report received", body = ReportApiResponse),
+        (status = 400, description = "Invalid input or own post"),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "The caller already reported the post")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn report_post(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReportRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let author_id = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", post_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if author_id == auth.id {
+        return Ok(bad_request("You cannot report your own post"));
+    }
+
+    let target = ReportTarget::Post { id: post_id, public_id: id };
+    file_report(&mut conn, &state, &auth, target, payload).await
+}
+
+/// Report a comment as spam or inappropriate
+///
+/// A user has one open report per comment. Once enough different users
+/// reported it, the comment is hidden until a moderator reviews it.
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/comments/{comment_id}/report",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("comment_id" = Uuid, Path, description = "Comment ID")
+    ),
+    request_body = ReportRequest,
+    responses(
+        (status = 201, description = "Report received", body = ReportApiResponse),
+        (status = 400, description = "Invalid input or own comment"),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post or comment not found"),
+        (status = 409, description = "The caller already reported the comment")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn report_comment(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ReportRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let comment = sqlx::query!(
+        "SELECT id, user_id FROM comments
+         WHERE public_id = $1 AND post_id = $2 AND deleted_at IS NULL",
+        comment_id,
+        post_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(comment) = comment else {
+        return Ok(not_found("Comment not found"));
+    };
+
+    if comment.user_id == auth.id {
+        return Ok(bad_request("You cannot report your own comment"));
+    }
+
+    let target = ReportTarget::Comment {
+        id: comment.id,
+        post_id,
+        public_id: comment_id,
+    };
+    file_report(&mut conn, &state, &auth, target, payload).await
+}
+
+async fn file_report(
+    conn: &mut PgConnection,
+    state: &AppState,
+    auth: &AuthUser,
+    target: ReportTarget,
+    payload: ReportRequest,
+) -> Result<Response, StatusCode> {
+    let Some(reason) = ReportReason::parse(&payload.reason) else {
+        return Ok(bad_request("Invalid input data"));
+    };
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let report = moderation_service::file_report(
+        &mut tx,
+        target,
+        auth.id,
+        reason,
+        payload.details.as_deref(),
+        state.config.report_auto_hide_threshold,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to file report: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(report) = report else {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(&format!(
+                "You have already reported this {}",
+                target.kind()
+            ))),
+        )
+            .into_response());
+    };
+
+    let response = ReportResponse {
+        id: report.public_id,
+        target_type: target.kind().to_string(),
+        target_id: target.public_id(),
+        reason: reason.to_string(),
+        details: payload.details,
+        created_at: report.created_at,
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::success(response, "Report received")),
+    )
+        .into_response())
+}
+
+/// List posts and comments with open reports (admin only)
+///
+/// Items reported by the most users come first.
+#[utoipa::path(
+    get,
+    path = "/api/moderation/queue",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Reported items", body = ModerationQueueApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate content")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn get_moderation_queue(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can moderate content"));
+    }
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    // Reports on trashed posts and deleted comments wait until those come back
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM (
+            SELECT 1
+            FROM reports r
+            JOIN posts p ON p.id = r.post_id
+            LEFT JOIN comments c ON c.id = r.comment_id
+            WHERE r.resolved_at IS NULL AND p.deleted_at IS NULL AND c.deleted_at IS NULL
+            GROUP BY r.post_id, r.comment_id
+        ) items
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let items = sqlx::query_as!(
+        QueuedReport,
+        r#"
+        SELECT CASE WHEN c.id IS NULL THEN 'post' ELSE 'comment' END AS "target_type!",
+               COALESCE(c.public_id, p.public_id) AS "target_id!",
+               p.public_id AS post_id,
+               p.title AS post_title,
+               LEFT(COALESCE(c.content, p.content), $3) AS excerpt,
+               COALESCE(cu.public_id, pu.public_id) AS "author_id!",
+               CASE WHEN c.id IS NULL THEN p.hidden_at IS NOT NULL ELSE c.is_hidden END AS "is_hidden!",
+               ARRAY_AGG(r.reason ORDER BY r.created_at) AS "reasons!",
+               ARRAY_REMOVE(ARRAY_AGG(r.details ORDER BY r.created_at), NULL) AS "details!",
+               MIN(r.created_at) AS "first_reported_at!",
+               MAX(r.created_at) AS "last_reported_at!"
+        FROM reports r
+        JOIN posts p ON p.id = r.post_id
+        JOIN users pu ON pu.id = p.user_id
+        LEFT JOIN comments c ON c.id = r.comment_id
+        LEFT JOIN users cu ON cu.id = c.user_id
+        WHERE r.resolved_at IS NULL AND p.deleted_at IS NULL AND c.deleted_at IS NULL
+        GROUP BY p.id, pu.id, c.id, cu.id
+        ORDER BY COUNT(*) DESC, MAX(r.created_at) DESC, p.id, c.id
+        LIMIT $1 OFFSET $2
+        "#,
+        limit as i64,
+        offset as i64,
+        EXCERPT_LENGTH
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to load moderation queue: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = PaginatedResponse {
+        data: items
+            .into_iter()
+            .map(ModerationQueueItemResponse::from)
+            .collect::<Vec<_>>(),
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    };
+
+    Ok(Json(ApiResponse::success(response, "Moderation queue retrieved successfully")).into_response())
+}
+
+/// Act on a reported post (admin only)
+///
+/// `dismiss` closes the open reports as unfounded and shows the post again
+/// if it was hidden. `hide` hides it from everyone but its author and
+/// admins, `delete` moves it to the trash and `warn` records a warning for
+/// the author, which requires a `note`. Every action closes the post's open
+/// reports and is kept in the audit trail.
+#[utoipa::path(
+    post,
+    path = "/api/moderation/posts/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body = ModerationActionRequest,
+    responses(
+        (status = 200, description = "Action taken", body = ModerationActionApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate content"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn moderate_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ModerationActionRequest>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can moderate content"));
+    }
+
+    let action = match checked_action(&payload) {
+        Ok(action) => action,
+        Err(message) => return Ok(bad_request(message)),
+    };
+
+    let post = sqlx::query!(
+        "SELECT id, user_id FROM posts WHERE public_id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(post) = post else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let target = ReportTarget::Post { id: post.id, public_id: id };
+    take_action(&mut conn, &auth, target, post.user_id, action, payload.note.as_deref()).await
+}
+
+/// Act on a reported comment (admin only)
+///
+/// Same actions as for posts; `delete` removes the comment like its author
+/// deleting it would.
+#[utoipa::path(
+    post,
+    path = "/api/moderation/comments/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Comment ID")
+    ),
+    request_body = ModerationActionRequest,
+    responses(
+        (status = 200, description = "Action taken", body = ModerationActionApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate content"),
+        (status = 404, description = "Comment not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn moderate_comment(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ModerationActionRequest>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can moderate content"));
+    }
+
+    let action = match checked_action(&payload) {
+        Ok(action) => action,
+        Err(message) => return Ok(bad_request(message)),
+    };
+
+    let comment = sqlx::query!(
+        "SELECT c.id, c.post_id, c.user_id
+         FROM comments c
+         JOIN posts p ON p.id = c.post_id
+         WHERE c.public_id = $1 AND c.deleted_at IS NULL AND p.deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(comment) = comment else {
+        return Ok(not_found("Comment not found"));
+    };
+
+    let target = ReportTarget::Comment {
+        id: comment.id,
+        post_id: comment.post_id,
+        public_id: id,
+    };
+    take_action(&mut conn, &auth, target, comment.user_id, action, payload.note.as_deref()).await
+}
+
+/// Action of a valid request, or why the request is rejected
+fn checked_action(payload: &ModerationActionRequest) -> Result<ModerationAction, &'static str> {
+    if payload.validate().is_err() {
+        return Err("Invalid input data");
+    }
+
+    match ModerationAction::parse(&payload.action) {
+        Some(ModerationAction::Warn) if payload.note.is_none() => {
+            Err("A note for the author is required to warn")
+        }
+        Some(action) => Ok(action),
+        None => Err("Invalid input data"),
+    }
+}
+
+async fn take_action(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    target: ReportTarget,
+    author_id: i32,
+    action: ModerationAction,
+    note: Option<&str>,
+) -> Result<Response, StatusCode> {
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let entry_id = moderation_service::take_action(&mut tx, target, Some(author_id), auth.id, action, note)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to moderate {}: {}", target.kind(), e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let entry = sqlx::query_as!(
+        ModerationLogEntry,
+        r#"
+        SELECT m.target_type, m.target_id, m.action,
+               au.public_id AS "author_public_id?", mu.public_id AS "moderator_public_id?",
+               m.note, m.reports_resolved, m.created_at
+        FROM moderation_actions m
+        LEFT JOIN users au ON au.id = m.author_id
+        LEFT JOIN users mu ON mu.id = m.moderator_id
+        WHERE m.id = $1
+        "#,
+        entry_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(
+        ModerationActionResponse::from(entry),
+        &format!("Moderation action '{}' taken", action),
+    ))
+    .into_response())
+}
+
+/// List the moderation audit trail (admin only)
+///
+/// Most recent first, including posts hidden automatically.
+#[utoipa::path(
+    get,
+    path = "/api/moderation/actions",
+    params(PaginationParams, ModerationLogParams),
+    responses(
+        (status = 200, description = "Moderation actions", body = ModerationLogApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can moderate content")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn get_moderation_log(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<ModerationLogParams>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can moderate content"));
+    }
+
+    let response = log_entries(&mut conn, &params, filter.target_id, None).await?;
+    Ok(Json(ApiResponse::success(response, "Moderation actions retrieved successfully")).into_response())
+}
+
+/// List the warnings the caller received from moderators
+#[utoipa::path(
+    get,
+    path = "/api/users/me/warnings",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Warnings, most recent first", body = ModerationLogApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Moderation"
+)]
+pub async fn get_my_warnings(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<Response, StatusCode> {
+    let mut response = log_entries(&mut conn, &params, None, Some(auth.id)).await?;
+
+    // Authors are not told which moderator warned them
+    for entry in &mut response.data {
+        entry.moderator_id = None;
+    }
+
+    Ok(Json(ApiResponse::success(response, "Warnings retrieved successfully")).into_response())
+}
+
+/// A page of the audit trail, optionally about one item or limited to the
+/// warnings of one author
+async fn log_entries(
+    conn: &mut PgConnection,
+    params: &PaginationParams,
+    target_id: Option<Uuid>,
+    warnings_of: Option<i32>,
+) -> Result<PaginatedResponse<ModerationActionResponse>, StatusCode> {
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM moderation_actions
+        WHERE ($1::uuid IS NULL OR target_id = $1)
+          AND ($2::int4 IS NULL OR (author_id = $2 AND action = 'warn'))
+        "#,
+        target_id,
+        warnings_of
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let entries = sqlx::query_as!(
+        ModerationLogEntry,
+        r#"
+        SELECT m.target_type, m.target_id, m.action,
+               au.public_id AS "author_public_id?", mu.public_id AS "moderator_public_id?",
+               m.note, m.reports_resolved, m.created_at
+        FROM moderation_actions m
+        LEFT JOIN users au ON au.id = m.author_id
+        LEFT JOIN users mu ON mu.id = m.moderator_id
+        WHERE ($1::uuid IS NULL OR m.target_id = $1)
+          AND ($2::int4 IS NULL OR (m.author_id = $2 AND m.action = 'warn'))
+        ORDER BY m.created_at DESC, m.id DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        target_id,
+        warnings_of,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(PaginatedResponse {
+        data: entries.into_iter().map(ModerationActionResponse::from).collect(),
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+    })
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
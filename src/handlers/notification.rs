@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    database::{connection::DbConn, models::Notification},
+    models::{
+        requests::{NotificationListParams, PaginationParams, UpdateNotificationPreferencesRequest},
+        responses::{
+            ApiResponse, NotificationListResponse, NotificationPreferenceResponse,
+            NotificationReadResponse, NotificationResponse,
+        },
+    },
+    services::notification_service::NotificationKind,
+};
+
+/// List the caller's notifications, newest first
+///
+/// Notifications about posts in the trash are left out. The unread counts
+/// cover all notifications of the caller, whatever the filters.
+#[utoipa::path(
+    get,
+    path = "/api/notifications",
+    params(PaginationParams, NotificationListParams),
+    responses(
+        (status = 200, description = "Notifications and unread counts", body = NotificationsApiResponse),
+        (status = 400, description = "Unknown kind"),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn get_notifications(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<NotificationListParams>,
+) -> Result<Response, StatusCode> {
+    let kind = match filter.kind.as_deref() {
+        None => None,
+        Some(value) => match NotificationKind::parse(value) {
+            Some(kind) => Some(kind.as_str()),
+            None => return Ok(bad_request(&format!("Unknown kind: {}", value))),
+        },
+    };
+    let unread_only = filter.unread_only.unwrap_or(false);
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM notifications n
+        JOIN posts p ON p.id = n.post_id
+        WHERE n.user_id = $1 AND n.in_app AND p.deleted_at IS NULL
+          AND (NOT $2 OR n.read_at IS NULL)
+          AND ($3::text IS NULL OR n.kind = $3)
+        "#,
+        auth.id,
+        unread_only,
+        kind
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let notifications = sqlx::query_as!(
+        Notification,
+        r#"
+        SELECT n.public_id, n.kind,
+               a.public_id AS "actor_public_id?", a.username AS "actor_username?",
+               p.public_id AS post_public_id, p.title AS post_title,
+               c.public_id AS "comment_public_id?",
+               n.detail, n.note, n.read_at, n.created_at
+        FROM notifications n
+        JOIN posts p ON p.id = n.post_id
+        LEFT JOIN users a ON a.id = n.actor_id
+        LEFT JOIN comments c ON c.id = n.comment_id
+        WHERE n.user_id = $1 AND n.in_app AND p.deleted_at IS NULL
+          AND (NOT $2 OR n.read_at IS NULL)
+          AND ($3::text IS NULL OR n.kind = $3)
+        ORDER BY n.created_at DESC, n.id DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        auth.id,
+        unread_only,
+        kind,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to list notifications: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let unread_counts = unread_counts(&mut conn, auth.id).await?;
+
+    let response = NotificationListResponse {
+        data: notifications.into_iter().map(NotificationResponse::from).collect(),
+        page,
+        limit,
+        total,
+        total_pages: params.total_pages(total),
+        unread_count: unread_counts.values().sum(),
+        unread_counts,
+    };
+
+    Ok(Json(ApiResponse::success(response, "Notifications retrieved successfully")).into_response())
+}
+
+/// Mark one of the caller's notifications as read
+#[utoipa::path(
+    post,
+    path = "/api/notifications/{id}/read",
+    params(
+        ("id" = Uuid, Path, description = "Notification ID")
+    ),
+    responses(
+        (status = 200, description = "Notification marked as read", body = NotificationReadApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Notification not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn mark_notification_read(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let notification = sqlx::query!(
+        r#"SELECT id, read_at IS NULL AS "is_unread!" FROM notifications
+           WHERE public_id = $1 AND user_id = $2 AND in_app"#,
+        id,
+        auth.id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(notification) = notification else {
+        return Ok(not_found("Notification not found"));
+    };
+
+    let marked = if notification.is_unread {
+        sqlx::query!(
+            "UPDATE notifications SET read_at = NOW() WHERE id = $1 AND read_at IS NULL",
+            notification.id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .rows_affected()
+    } else {
+        0
+    };
+
+    let response = NotificationReadResponse {
+        marked,
+        unread_count: unread_counts(&mut conn, auth.id).await?.values().sum(),
+    };
+    Ok(Json(ApiResponse::success(response, "Notification marked as read")).into_response())
+}
+
+/// Mark all of the caller's notifications as read
+#[utoipa::path(
+    post,
+    path = "/api/notifications/read",
+    responses(
+        (status = 200, description = "Notifications marked as read", body = NotificationReadApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn mark_all_notifications_read(mut conn: DbConn, auth: AuthUser) -> Result<Response, StatusCode> {
+    let marked = sqlx::query!(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND in_app AND read_at IS NULL",
+        auth.id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to mark notifications as read: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    let response = NotificationReadResponse {
+        marked,
+        unread_count: 0,
+    };
+    Ok(Json(ApiResponse::success(response, "Notifications marked as read")).into_response())
+}
+
+/// Get how the caller receives each kind of notification
+#[utoipa::path(
+    get,
+    path = "/api/notifications/preferences",
+    responses(
+        (status = 200, description = "Preferences of every kind", body = NotificationPreferencesApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn get_notification_preferences(mut conn: DbConn, auth: AuthUser) -> Result<Response, StatusCode> {
+    let preferences = preferences(&mut conn, auth.id).await?;
+    Ok(Json(ApiResponse::success(preferences, "Preferences retrieved successfully")).into_response())
+}
+
+/// Choose how the caller receives some kinds of notification
+///
+/// Each kind can be shown in the app, sent in the email digest, both or
+/// neither. Changes apply to notifications created afterwards.
+#[utoipa::path(
+    put,
+    path = "/api/notifications/preferences",
+    request_body = UpdateNotificationPreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences of every kind", body = NotificationPreferencesApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Notifications"
+)]
+pub async fn update_notification_preferences(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Json(payload): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.preferences.is_empty()
+        || payload.preferences.iter().any(|preference| preference.validate().is_err())
+    {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    for preference in &payload.preferences {
+        sqlx::query!(
+            r#"
+            INSERT INTO notification_preferences (user_id, kind, in_app, email_digest)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, kind)
+            DO UPDATE SET in_app = EXCLUDED.in_app, email_digest = EXCLUDED.email_digest, updated_at = NOW()
+            "#,
+            auth.id,
+            preference.kind,
+            preference.in_app,
+            preference.email_digest
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save notification preferences: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let preferences = preferences(&mut conn, auth.id).await?;
+    Ok(Json(ApiResponse::success(preferences, "Preferences updated successfully")).into_response())
+}
+
+/// Unread in-app notifications of a user by kind, every kind included
+async fn unread_counts(conn: &mut PgConnection, user_id: i32) -> Result<BTreeMap<String, i64>, StatusCode> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT n.kind, COUNT(*) AS "count!"
+        FROM notifications n
+        JOIN posts p ON p.id = n.post_id
+        WHERE n.user_id = $1 AND n.in_app AND n.read_at IS NULL AND p.deleted_at IS NULL
+        GROUP BY n.kind
+        "#,
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut counts: BTreeMap<String, i64> = NotificationKind::ALL
+        .iter()
+        .map(|kind| (kind.to_string(), 0))
+        .collect();
+    for row in rows {
+        counts.insert(row.kind, row.count);
+    }
+
+    Ok(counts)
+}
+
+/// Effective preferences of a user, defaults filled in for kinds never set
+async fn preferences(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> Result<Vec<NotificationPreferenceResponse>, StatusCode> {
+    let saved = sqlx::query!(
+        "SELECT kind, in_app, email_digest FROM notification_preferences WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(NotificationKind::ALL
+        .iter()
+        .map(|kind| {
+            let saved = saved.iter().find(|row| row.kind == kind.as_str());
+            NotificationPreferenceResponse {
+                kind: kind.to_string(),
+                in_app: saved.is_none_or(|row| row.in_app),
+                email_digest: saved.is_some_and(|row| row.email_digest),
+            }
+        })
+        .collect())
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
@@ -1,74 +1,217 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Path, Query, RawQuery, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use serde_json::Value;
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    database::models::Post,
+    auth::{forbidden, AuthUser},
+    database::{connection::DbConn, models::{Post, User}},
+    handlers::conditional::{self, IfMatch},
+    services::{
+        collection_service,
+        post_service::{PostService, PostStatus},
+        render_service::{self, render_html, ContentFormat},
+        slug_service,
+        tag_service,
+    },
     models::{
-        responses::{ApiResponse, PaginatedPostResponse, PostResponse},
-        requests::{CreatePostRequest, PaginationParams, UpdatePostRequest},
+        responses::{
+            select_fields, ApiResponse, PaginatedResponse, PostResponse,
+            PostSearchResultResponse, PostSeriesResponse, PostWithUserResponse,
+        },
+        requests::{
+            CreatePostRequest, DeleteParams, PaginationParams, PatchPostRequest,
+            PostQueryParams, PostSearchParams, PostFilterParams, UpdatePostRequest,
+        },
     },
     AppState,
 };
 
+/// Fields of `PostResponse` that may be requested through `?fields=`
+const POST_FIELDS: &[&str] = &[
+    "id",
+    "slug",
+    "title",
+    "content",
+    "content_format",
+    "content_html",
+    "excerpt",
+    "reading_time_minutes",
+    "tags",
+    "comment_count",
+    "reaction_counts",
+    "my_reaction",
+    "bookmark_count",
+    "is_bookmarked",
+    "attachments",
+    "user_id",
+    "status",
+    "is_published",
+    "published_at",
+    "publish_at",
+    "created_at",
+    "updated_at",
+    "series",
+];
+
+/// Relations that may be embedded through `?include=`
+const POST_RELATIONS: &[&str] = &["author"];
+
+fn check_post_query(query: &PostQueryParams) -> Result<(), String> {
+    if let Some(relation) = query
+        .include_list()
+        .into_iter()
+        .find(|relation| !POST_RELATIONS.contains(relation))
+    {
+        return Err(format!("Unknown include: {}", relation));
+    }
+
+    if let Some(field) = query
+        .field_list()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|field| !POST_FIELDS.contains(field))
+    {
+        return Err(format!("Unknown field: {}", field));
+    }
+
+    Ok(())
+}
+
+/// Internal id of the caller and whether it may see every unpublished post
+fn viewer(auth: Option<&AuthUser>) -> (Option<i32>, bool) {
+    (auth.map(|auth| auth.id), auth.is_some_and(AuthUser::is_admin))
+}
+
+fn shape_post<T: serde::Serialize>(post: T, query: &PostQueryParams) -> Value {
+    let keep: &[&str] = if query.includes("author") { &["user"] } else { &[] };
+    select_fields(post, query.field_list().as_deref(), keep)
+}
+
 /// Create a new post
 #[utoipa::path(
     post,
     path = "/api/posts",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the original response when the request is retried")
+    ),
     request_body = CreatePostRequest,
     responses(
         (status = 201, description = "Post created successfully", body = PostApiResponse),
-        (status = 400, description = "Invalid input")
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 409, description = "The slug is already taken, or a request with the same Idempotency-Key is in progress"),
+        (status = 422, description = "Idempotency-Key reused with a different body")
     ),
+    security(("bearer_auth" = [])),
     tag = "Posts"
 )]
 pub async fn create_post(
-    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
     Json(payload): Json<CreatePostRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<PostResponse>>), StatusCode> {
+    // Plain text unless asked otherwise; a format we cannot render is an error
+    let content_format = match payload.content_format.as_deref() {
+        None => ContentFormat::Plain,
+        Some(value) => match ContentFormat::parse(value) {
+            Some(format) => format,
+            None => {
+                return Ok((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::error(&format!("Unknown content format: {}", value))),
+                ));
+            }
+        },
+    };
+
     // Validate input
-    if let Err(_) = payload.validate() {
+    if payload.validate().is_err() {
         return Ok((
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::error("Invalid input data")),
         ));
     }
 
-    // For demo purposes, we'll use user_id = 1
-    // In a real app, this would come from authentication middleware
-    let user_id = 1;
+    let content_html = render_html(&payload.content, content_format);
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let post = sqlx::query_as!(
-        Post,
+    // The first revision is recorded in the same statement
+    let post_id = sqlx::query_scalar!(
         r#"
-        INSERT INTO posts (title, content, user_id, is_published, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, NOW(), NOW())
-        RETURNING id, title, content, user_id, is_published, created_at, updated_at
+        WITH created AS (
+            INSERT INTO posts (title, content, content_format, content_html, user_id, publish_at,
+                               created_at, updated_at)
+            VALUES ($1, $2, $5, $6, $3, $4, NOW(), NOW())
+            RETURNING *
+        ), first_revision AS (
+            INSERT INTO post_revisions (post_id, revision, title, content, author_id)
+            SELECT id, revision, title, content, user_id FROM created
+        )
+        SELECT id AS "id!" FROM created
         "#,
         payload.title,
         payload.content,
-        user_id,
-        payload.is_published.unwrap_or(false)
+        auth.id,
+        payload.publish_at,
+        content_format.as_str(),
+        content_html
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         tracing::error!("Failed to create post: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let response = PostResponse {
-        id: post.id,
-        title: post.title,
-        content: post.content,
-        user_id: post.user_id,
-        is_published: post.is_published,
-        created_at: post.created_at,
-        updated_at: post.updated_at,
-    };
+    // A custom slug must be free, a generated one is numbered until it is
+    match payload.slug.as_deref() {
+        Some(slug) => {
+            let claimed = slug_service::claim_slug(&mut tx, post_id, slug)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to assign slug: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            if !claimed {
+                return Ok((
+                    StatusCode::CONFLICT,
+                    Json(ApiResponse::error("Slug is already taken")),
+                ));
+            }
+        }
+        None => {
+            slug_service::assign_slug(&mut tx, post_id, &payload.title)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to assign slug: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+        }
+    }
+
+    if let Some(tags) = &payload.tags {
+        tag_service::set_post_tags(&mut tx, post_id, tags)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to tag post: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    let post = load_post(&mut tx, post_id, Some(auth.id)).await?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let response = PostResponse::from(post);
 
     Ok((
         StatusCode::CREATED,
@@ -77,241 +220,1231 @@ pub async fn create_post(
 }
 
 /// Get all posts with pagination
+///
+/// Unpublished posts are only listed for their author and admins.
 #[utoipa::path(
     get,
     path = "/api/posts",
-    params(PaginationParams),
+    params(PaginationParams, PostFilterParams, PostQueryParams),
     responses(
-        (status = 200, description = "List of posts", body = PostsApiResponse)
+        (status = 200, description = "List of posts, `PostWithUserResponse` items when `include=author`", body = PostsApiResponse),
+        (status = 400, description = "Unknown status, include or field")
     ),
+    security((), ("bearer_auth" = [])),
     tag = "Posts"
 )]
 pub async fn get_posts(
-    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
     Query(params): Query<PaginationParams>,
-) -> Result<Json<ApiResponse<PaginatedPostResponse>>, StatusCode> {
-    let page = params.page.unwrap_or(1);
-    let limit = params.limit.unwrap_or(10);
-    let offset = (page - 1) * limit;
+    Query(filter): Query<PostFilterParams>,
+    Query(query): Query<PostQueryParams>,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<Value>>>), StatusCode> {
+    list_posts(&mut conn, auth.as_ref(), None, &params, &filter, &query).await
+}
+
+/// Get the posts of a user
+///
+/// Unpublished posts are only listed for the user themselves and admins.
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/posts",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        PaginationParams,
+        PostFilterParams,
+        PostQueryParams
+    ),
+    responses(
+        (status = 200, description = "Posts of the user", body = PostsApiResponse),
+        (status = 400, description = "Unknown status, include or field"),
+        (status = 404, description = "User not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn get_user_posts(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<PostFilterParams>,
+    Query(query): Query<PostQueryParams>,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<Value>>>), StatusCode> {
+    let author = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE public_id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(author) = author else {
+        return Ok((StatusCode::NOT_FOUND, Json(ApiResponse::error("User not found"))));
+    };
+
+    list_posts(&mut conn, auth.as_ref(), Some(author), &params, &filter, &query).await
+}
+
+/// Get the caller's own posts, unpublished ones included
+#[utoipa::path(
+    get,
+    path = "/api/users/me/posts",
+    params(PaginationParams, PostFilterParams, PostQueryParams),
+    responses(
+        (status = 200, description = "Posts of the caller", body = PostsApiResponse),
+        (status = 400, description = "Unknown status, include or field"),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn get_my_posts(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<PostFilterParams>,
+    Query(query): Query<PostQueryParams>,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<Value>>>), StatusCode> {
+    list_posts(&mut conn, Some(&auth), Some(auth.id), &params, &filter, &query).await
+}
+
+/// Lists posts visible to the caller, optionally limited to one author
+async fn list_posts(
+    conn: &mut PgConnection,
+    auth: Option<&AuthUser>,
+    author: Option<i32>,
+    params: &PaginationParams,
+    filter: &PostFilterParams,
+    query: &PostQueryParams,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<Value>>>), StatusCode> {
+    if let Err(message) = check_post_query(query) {
+        return Ok((StatusCode::BAD_REQUEST, Json(ApiResponse::error(&message))));
+    }
+
+    let status = match filter.status.as_deref() {
+        None => None,
+        Some(value) => match PostStatus::parse(value) {
+            Some(status) => Some(status.as_str()),
+            None => {
+                return Ok((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::error(&format!("Unknown status: {}", value))),
+                ));
+            }
+        },
+    };
+
+    let tag = filter.tag.as_deref().map(tag_service::normalize_tag);
+    let (viewer, is_admin) = viewer(auth);
+
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
 
     // Get total count
-    let total_result = sqlx::query!("SELECT COUNT(*) as count FROM posts")
-        .fetch_one(&state.db)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
+    let total_result = sqlx::query!(
+        "SELECT COUNT(*) as count FROM posts p
+         WHERE p.deleted_at IS NULL
+           AND (p.is_published OR p.user_id = $1 OR $2)
+           AND ($3::int4 IS NULL OR p.user_id = $3)
+           AND ($4::text IS NULL OR p.status = $4)
+           AND ($5::text IS NULL OR EXISTS (
+               SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+               WHERE pt.post_id = p.id AND t.name = $5
+           ))",
+        viewer,
+        is_admin,
+        author,
+        status,
+        tag
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     let total = total_result.count.unwrap_or(0) as u64;
 
     // Get paginated posts
-    let posts = sqlx::query_as!(
-        Post,
-        "SELECT id, title, content, user_id, is_published, created_at, updated_at 
-         FROM posts 
-         ORDER BY created_at DESC 
+    let ids = sqlx::query_scalar!(
+        "SELECT p.id FROM posts p
+         WHERE p.deleted_at IS NULL
+           AND (p.is_published OR p.user_id = $3 OR $4)
+           AND ($5::int4 IS NULL OR p.user_id = $5)
+           AND ($6::text IS NULL OR p.status = $6)
+           AND ($7::text IS NULL OR EXISTS (
+               SELECT 1 FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+               WHERE pt.post_id = p.id AND t.name = $7
+           ))
+         ORDER BY p.created_at DESC
          LIMIT $1 OFFSET $2",
         limit as i64,
-        offset as i64
+        offset as i64,
+        viewer,
+        is_admin,
+        author,
+        status,
+        tag
     )
-    .fetch_all(&state.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let post_responses: Vec<PostResponse> = posts
+    let posts = load_posts(conn, &ids, viewer).await?;
+    let data: Vec<Value> = if query.includes("author") {
+        with_authors(conn, posts)
+            .await?
+            .into_iter()
+            .map(|post| shape_post(post, query))
+            .collect()
+    } else {
+        posts
+            .into_iter()
+            .map(|post| shape_post(PostResponse::from(post), query))
+            .collect()
+    };
+
+    let total_pages = params.total_pages(total);
+
+    let response = PaginatedResponse {
+        data,
+        page,
+        limit,
+        total,
+        total_pages,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(ApiResponse::success(response, "Posts retrieved successfully")),
+    ))
+}
+
+/// Full-text search over post titles and content
+#[utoipa::path(
+    get,
+    path = "/api/posts/search",
+    params(PostSearchParams, PaginationParams),
+    responses(
+        (status = 200, description = "Matching posts, best match first", body = PostSearchApiResponse),
+        (status = 400, description = "Empty or invalid search query")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn search_posts(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Query(search): Query<PostSearchParams>,
+    Query(params): Query<PaginationParams>,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<PostSearchResultResponse>>>), StatusCode> {
+    if search.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Search query must be between 1 and 200 characters")),
+        ));
+    }
+
+    let ts_query = match search.ts_query() {
+        Some(ts_query) => ts_query,
+        None => {
+            return Ok((
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Search query must contain at least one word")),
+            ));
+        }
+    };
+
+    let (viewer, is_admin) = viewer(auth.as_ref());
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.deleted_at IS NULL
+          AND p.search_vector @@ to_tsquery('simple', $1)
+          AND ($2::uuid IS NULL OR u.public_id = $2)
+          AND ($3::bool IS NULL OR p.is_published = $3)
+          AND (p.is_published OR p.user_id = $4 OR $5)
+        "#,
+        ts_query,
+        search.author,
+        search.published,
+        viewer,
+        is_admin
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to count search results: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })? as u64;
+
+    let hits = sqlx::query!(
+        r#"
+        SELECT p.id, ts_rank_cd(p.search_vector, to_tsquery('simple', $1)) AS "rank!"
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.deleted_at IS NULL
+          AND p.search_vector @@ to_tsquery('simple', $1)
+          AND ($2::uuid IS NULL OR u.public_id = $2)
+          AND ($3::bool IS NULL OR p.is_published = $3)
+          AND (p.is_published OR p.user_id = $6 OR $7)
+        ORDER BY 2 DESC, p.created_at DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        ts_query,
+        search.author,
+        search.published,
+        limit as i64,
+        offset as i64,
+        viewer,
+        is_admin
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to search posts: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let ids: Vec<i32> = hits.iter().map(|hit| hit.id).collect();
+    let ranks: HashMap<i32, f32> = hits.iter().map(|hit| (hit.id, hit.rank)).collect();
+    let posts = load_posts(&mut conn, &ids, viewer).await?;
+
+    // Headlines are cut from the plain text and escaped, so markup in the
+    // content cannot reach the page
+    let texts: Vec<String> = posts
+        .iter()
+        .map(|post| render_service::headline_text(&post.content, &post.content_format))
+        .collect();
+    let headlines = render_service::headlines(&mut conn, &ts_query, &texts)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to build search headlines: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let data = posts
         .into_iter()
-        .map(|post| PostResponse {
-            id: post.id,
-            title: post.title,
-            content: post.content,
-            user_id: post.user_id,
-            is_published: post.is_published,
-            created_at: post.created_at,
-            updated_at: post.updated_at,
+        .zip(headlines)
+        .map(|(post, headline)| PostSearchResultResponse {
+            rank: ranks.get(&post.id).copied().unwrap_or_default(),
+            post: PostResponse::from(post),
+            headline,
         })
         .collect();
 
-    let total_pages = (total as f64 / limit as f64).ceil() as u64;
+    let total_pages = params.total_pages(total);
 
-    let response = PaginatedPostResponse {
-        data: post_responses,
+    let response = PaginatedResponse {
+        data,
         page,
         limit,
         total,
         total_pages,
     };
 
-    Ok(Json(ApiResponse::success(
-        response,
-        "Posts retrieved successfully",
-    )))
+    Ok((
+        StatusCode::OK,
+        Json(ApiResponse::success(response, "Search completed successfully")),
+    ))
 }
 
 /// Get post by ID
+///
+/// A post in a collection comes with `series`: its place in each collection
+/// and the previous and next posts, among those the caller can read. Such a
+/// post is never answered with `304 Not Modified`.
 #[utoipa::path(
     get,
     path = "/api/posts/{id}",
     params(
-        ("id" = i32, Path, description = "Post ID")
+        ("id" = Uuid, Path, description = "Post ID"),
+        PostQueryParams
     ),
     responses(
-        (status = 200, description = "Post found", body = PostApiResponse),
+        (status = 200, description = "Post found, `PostWithUserResponse` when `include=author`", body = PostApiResponse),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
+        (status = 400, description = "Unknown include or field"),
         (status = 404, description = "Post not found")
     ),
+    security((), ("bearer_auth" = [])),
     tag = "Posts"
 )]
 pub async fn get_post_by_id(
-    State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<PostResponse>>, StatusCode> {
-    let post = sqlx::query_as!(
-        Post,
-        "SELECT id, title, content, user_id, is_published, created_at, updated_at 
-         FROM posts WHERE id = $1",
-        id
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<PostQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Err(message) = check_post_query(&query) {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&message)),
+        )
+            .into_response());
+    }
+
+    post_response(&mut conn, auth.as_ref(), id, &query, &headers).await
+}
+
+/// Get post by slug
+///
+/// A former slug of the post answers `301 Moved Permanently` with the
+/// current one in `Location`. Posts in a collection come with `series`, as
+/// for `GET /api/posts/{id}`.
+#[utoipa::path(
+    get,
+    path = "/api/posts/by-slug/{slug}",
+    params(
+        ("slug" = String, Path, description = "Current or former slug of the post"),
+        PostQueryParams
+    ),
+    responses(
+        (status = 200, description = "Post found, `PostWithUserResponse` when `include=author`", body = PostApiResponse),
+        (status = 301, description = "Former slug, `Location` holds the current one"),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
+        (status = 400, description = "Unknown include or field"),
+        (status = 404, description = "Post not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn get_post_by_slug(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(slug): Path<String>,
+    Query(query): Query<PostQueryParams>,
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Err(message) = check_post_query(&query) {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&message)),
+        )
+            .into_response());
+    }
+
+    let (viewer, is_admin) = viewer(auth.as_ref());
+    let post = sqlx::query!(
+        "SELECT p.public_id, p.slug
+         FROM post_slugs s
+         JOIN posts p ON p.id = s.post_id
+         WHERE s.slug = $1 AND p.deleted_at IS NULL
+           AND (p.is_published OR p.user_id = $2 OR $3)",
+        slug,
+        viewer,
+        is_admin
     )
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     match post {
+        Some(post) if post.slug == slug => {
+            post_response(&mut conn, auth.as_ref(), post.public_id, &query, &headers).await
+        }
         Some(post) => {
-            let response = PostResponse {
-                id: post.id,
-                title: post.title,
-                content: post.content,
-                user_id: post.user_id,
-                is_published: post.is_published,
-                created_at: post.created_at,
-                updated_at: post.updated_at,
+            let location = match raw_query {
+                Some(raw_query) => format!("/api/posts/by-slug/{}?{}", post.slug, raw_query),
+                None => format!("/api/posts/by-slug/{}", post.slug),
             };
-
-            Ok(Json(ApiResponse::success(
-                response,
-                "Post found successfully",
-            )))
+            Ok((StatusCode::MOVED_PERMANENTLY, [(header::LOCATION, location)]).into_response())
         }
-        None => Ok(Json(ApiResponse::error("Post not found"))),
+        None => Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found")),
+        )
+            .into_response()),
+    }
+}
+
+/// Builds the response of a single post read, honouring `include`,
+/// `fields` and the conditional request headers
+async fn post_response(
+    conn: &mut PgConnection,
+    auth: Option<&AuthUser>,
+    id: Uuid,
+    query: &PostQueryParams,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    // Unpublished posts are only visible to their author and admins
+    let Some(post_id) = visible_post_id(conn, id, auth).await? else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found")),
+        )
+            .into_response());
+    };
+
+    let post = load_post(conn, post_id, viewer(auth).0).await?;
+    let (tag, post) = post_representation(conn, auth, post, query).await?;
+    let validators = conditional::representation_headers(&tag);
+
+    if conditional::is_representation_unchanged(headers, &tag) {
+        return Ok((StatusCode::NOT_MODIFIED, validators).into_response());
     }
+
+    Ok((
+        StatusCode::OK,
+        validators,
+        Json(ApiResponse::success(post, "Post found successfully")),
+    )
+        .into_response())
 }
 
-/// Update post by ID
+/// Body of a single post read and its representation tag. Writes answer
+/// with the same representation, so their `ETag` matches the next read.
+async fn post_representation(
+    conn: &mut PgConnection,
+    auth: Option<&AuthUser>,
+    post: Post,
+    query: &PostQueryParams,
+) -> Result<(String, Value), StatusCode> {
+    let (viewer, is_admin) = viewer(auth);
+    let series = post_series(conn, post.id, viewer, is_admin).await?;
+    let updated_at = post.updated_at;
+    let post = if query.includes("author") {
+        let mut post = with_authors(conn, vec![post])
+            .await?
+            .pop()
+            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+        post.series = series;
+        shape_post(post, query)
+    } else {
+        let mut post = PostResponse::from(post);
+        post.series = series;
+        shape_post(post, query)
+    };
+
+    // Counters, the caller's reaction and bookmark, attachments and the
+    // neighbours in a series change without the post changing, so the ETag
+    // covers the whole body
+    let body = serde_json::to_vec(&post).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((conditional::representation_tag(&updated_at, &body), post))
+}
+
+/// Replace post by ID
+///
+/// Only the author or an admin can edit a post, and only admins can change
+/// the title or content of a post that is no longer a draft. A changed
+/// title or content is stored as a new revision, attributed to the caller.
 #[utoipa::path(
     put,
     path = "/api/posts/{id}",
     params(
-        ("id" = i32, Path, description = "Post ID")
+        ("id" = Uuid, Path, description = "Post ID")
     ),
     request_body = UpdatePostRequest,
     responses(
         (status = 200, description = "Post updated successfully", body = PostApiResponse),
         (status = 404, description = "Post not found"),
-        (status = 400, description = "Invalid input")
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can edit the post, only admins can change the content of a post past draft"),
+        (status = 409, description = "The slug is already taken"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
     ),
+    security(("bearer_auth" = [])),
     tag = "Posts"
 )]
 pub async fn update_post(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<UpdatePostRequest>,
-) -> Result<Json<ApiResponse<PostResponse>>, StatusCode> {
+) -> Result<Response, StatusCode> {
+    if let Err(response) = editable_post_id(&mut conn, &auth, id).await? {
+        return Ok(response);
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
     // Validate input
-    if let Err(_) = payload.validate() {
-        return Ok(Json(ApiResponse::error("Invalid input data")));
+    if payload.validate().is_err() {
+        return Ok(invalid_input());
     }
 
-    // Check if post exists
-    let existing_post = sqlx::query!(
-        "SELECT id FROM posts WHERE id = $1",
-        id
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let previous = locked_post(&mut tx, id).await?;
+    if let Some(previous) = &previous {
+        let changes_content = previous.title != payload.title
+            || previous.content != payload.content
+            || payload
+                .content_format
+                .as_ref()
+                .is_some_and(|format| *format != previous.content_format);
+        if changes_content && !may_edit_content(previous, &auth)? {
+            return Ok(content_locked(previous));
+        }
+    }
+    let previous_title = previous.map(|previous| previous.title);
+
+    let updated_id = sqlx::query_scalar!(
+        r#"
+        WITH updated AS (
+            UPDATE posts
+            SET title = $2::varchar,
+                content = $3,
+                content_format = COALESCE($7, content_format),
+                content_html = CASE
+                    WHEN content IS DISTINCT FROM $3
+                      OR content_format IS DISTINCT FROM COALESCE($7, content_format) THEN NULL
+                    ELSE content_html
+                END,
+                publish_at = $4,
+                revision = revision + CASE
+                    WHEN title IS DISTINCT FROM $2::varchar OR content IS DISTINCT FROM $3 THEN 1 ELSE 0
+                END,
+                updated_at = NOW()
+            WHERE public_id = $1 AND deleted_at IS NULL
+              AND ($5::timestamptz[] IS NULL OR updated_at = ANY($5))
+            RETURNING *
+        ), new_revision AS (
+            INSERT INTO post_revisions (post_id, revision, title, content, author_id)
+            SELECT id, revision, title, content, $6 FROM updated
+            ON CONFLICT (post_id, revision) DO NOTHING
+        )
+        SELECT id AS "id!" FROM updated
+        "#,
+        id,
+        payload.title,
+        payload.content,
+        payload.publish_at,
+        if_match.versions(),
+        auth.id,
+        payload.content_format
     )
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *tx)
     .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .map_err(|e| {
+        tracing::error!("Failed to update post: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let updated_post = match updated_id {
+        Some(post_id) => Some(load_post(&mut tx, post_id, Some(auth.id)).await?),
+        None => None,
+    };
+    let updated_post = retag_post(&mut tx, updated_post, payload.tags.as_deref()).await?;
+    let updated_post =
+        match reslug_post(&mut tx, updated_post, previous_title, payload.slug.as_deref()).await {
+            Ok(post) => post,
+            Err(response) => return Ok(response),
+        };
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    updated_post_response(&mut conn, &auth, id, updated_post).await
+}
 
-    if existing_post.is_none() {
-        return Ok(Json(ApiResponse::error("Post not found")));
+/// Partially update post by ID (JSON Merge Patch)
+///
+/// Only the author or an admin can edit a post, and only admins can change
+/// the title or content of a post that is no longer a draft. A changed
+/// title or content is stored as a new revision, attributed to the caller.
+#[utoipa::path(
+    patch,
+    path = "/api/posts/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body(content = PatchPostRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Post updated successfully", body = PostApiResponse),
+        (status = 404, description = "Post not found"),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can edit the post, only admins can change the content of a post past draft"),
+        (status = 409, description = "The slug is already taken"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn patch_post(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<PatchPostRequest>,
+) -> Result<Response, StatusCode> {
+    if let Err(response) = editable_post_id(&mut conn, &auth, id).await? {
+        return Ok(response);
     }
 
-    // Update fields individually if provided
-    if let Some(title) = &payload.title {
-        sqlx::query!("UPDATE posts SET title = $1, updated_at = NOW() WHERE id = $2", title, id)
-            .execute(&state.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
     }
-    if let Some(content) = &payload.content {
-        sqlx::query!("UPDATE posts SET content = $1, updated_at = NOW() WHERE id = $2", content, id)
-            .execute(&state.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some(field) = payload.nulled_required_fields().first() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Field '{}' cannot be null", field))),
+        )
+            .into_response());
     }
-    if let Some(is_published) = payload.is_published {
-        sqlx::query!("UPDATE posts SET is_published = $1, updated_at = NOW() WHERE id = $2", is_published, id)
-            .execute(&state.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Validate the fields that are present
+    if payload.validate().is_err() {
+        return Ok(invalid_input());
     }
 
-    // Get the updated post data
-    let updated_post = sqlx::query_as!(
-        Post,
-        "SELECT id, title, content, user_id, is_published, created_at, updated_at 
-         FROM posts WHERE id = $1",
-        id
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let previous = locked_post(&mut tx, id).await?;
+    if let Some(previous) = &previous {
+        let changes = |field: &Option<Option<String>>, current: &String| {
+            field.as_ref().is_some_and(|value| value.as_ref() != Some(current))
+        };
+        let changes_content = changes(&payload.title, &previous.title)
+            || changes(&payload.content, &previous.content)
+            || changes(&payload.content_format, &previous.content_format);
+        if changes_content && !may_edit_content(previous, &auth)? {
+            return Ok(content_locked(previous));
+        }
+    }
+    let previous_title = previous.map(|previous| previous.title);
+
+    // Absent fields keep their value
+    let updated_id = sqlx::query_scalar!(
+        r#"
+        WITH updated AS (
+            UPDATE posts
+            SET title = COALESCE($2, title),
+                content = COALESCE($3, content),
+                content_format = COALESCE($8, content_format),
+                content_html = CASE
+                    WHEN content IS DISTINCT FROM COALESCE($3, content)
+                      OR content_format IS DISTINCT FROM COALESCE($8, content_format) THEN NULL
+                    ELSE content_html
+                END,
+                publish_at = CASE WHEN $4 THEN $5 ELSE publish_at END,
+                revision = revision + CASE
+                    WHEN title IS DISTINCT FROM COALESCE($2, title)
+                      OR content IS DISTINCT FROM COALESCE($3, content) THEN 1 ELSE 0
+                END,
+                updated_at = NOW()
+            WHERE public_id = $1 AND deleted_at IS NULL
+              AND ($6::timestamptz[] IS NULL OR updated_at = ANY($6))
+            RETURNING *
+        ), new_revision AS (
+            INSERT INTO post_revisions (post_id, revision, title, content, author_id)
+            SELECT id, revision, title, content, $7 FROM updated
+            ON CONFLICT (post_id, revision) DO NOTHING
+        )
+        SELECT id AS "id!" FROM updated
+        "#,
+        id,
+        payload.title.flatten(),
+        payload.content.flatten(),
+        payload.publish_at.is_some(),
+        payload.publish_at.flatten(),
+        if_match.versions(),
+        auth.id,
+        payload.content_format.flatten()
     )
-    .fetch_one(&state.db)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to update post: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let updated_post = match updated_id {
+        Some(post_id) => Some(load_post(&mut tx, post_id, Some(auth.id)).await?),
+        None => None,
+    };
+    let tags = payload.tags.map(Option::unwrap_or_default);
+    let updated_post = retag_post(&mut tx, updated_post, tags.as_deref()).await?;
+    let slug = payload.slug.flatten();
+    let updated_post = match reslug_post(&mut tx, updated_post, previous_title, slug.as_deref()).await {
+        Ok(post) => post,
+        Err(response) => return Ok(response),
+    };
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    updated_post_response(&mut conn, &auth, id, updated_post).await
+}
+
+fn invalid_input() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::<()>::error("Invalid input data")),
+    )
+        .into_response()
+}
+
+/// Builds the response of a conditional write, telling a missing post
+/// apart from a failed `If-Match` when no row was touched
+async fn missing_post_response(
+    conn: &mut PgConnection,
+    id: Uuid,
+    include_trashed: bool,
+) -> Result<Response, StatusCode> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM posts WHERE public_id = $1 AND ($2 OR deleted_at IS NULL))",
+        id,
+        include_trashed
+    )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or(false);
+
+    if exists {
+        Ok(conditional::precondition_failed())
+    } else {
+        Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found")),
+        )
+            .into_response())
+    }
+}
+
+/// Internal id of the post if the caller may read it
+pub(crate) async fn visible_post_id(
+    conn: &mut PgConnection,
+    id: Uuid,
+    auth: Option<&AuthUser>,
+) -> Result<Option<i32>, StatusCode> {
+    sqlx::query_scalar!(
+        "SELECT id FROM posts
+         WHERE public_id = $1 AND deleted_at IS NULL
+           AND (is_published OR user_id = $2 OR $3)",
+        id,
+        auth.map(|auth| auth.id),
+        auth.is_some_and(AuthUser::is_admin)
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Loads posts picked by another query, see `PostService::find_posts`
+pub(crate) async fn load_posts(
+    conn: &mut PgConnection,
+    ids: &[i32],
+    viewer: Option<i32>,
+) -> Result<Vec<Post>, StatusCode> {
+    PostService::find_posts(conn, ids, viewer).await.map_err(|e| {
+        tracing::error!("Failed to load posts: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Loads a post picked by another query, see `PostService::find_post`
+pub(crate) async fn load_post(
+    conn: &mut PgConnection,
+    id: i32,
+    viewer: Option<i32>,
+) -> Result<Post, StatusCode> {
+    PostService::find_post(conn, id, viewer)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load post: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Pairs posts with their authors for `include=author`
+async fn with_authors(
+    conn: &mut PgConnection,
+    posts: Vec<Post>,
+) -> Result<Vec<PostWithUserResponse>, StatusCode> {
+    let author_ids: Vec<i32> = posts.iter().map(|post| post.user_id).collect();
+    let authors = sqlx::query_as!(
+        User,
+        "SELECT id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at
+         FROM users WHERE id = ANY($1)",
+        &author_ids
+    )
+    .fetch_all(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let response = PostResponse {
-        id: updated_post.id,
-        title: updated_post.title,
-        content: updated_post.content,
-        user_id: updated_post.user_id,
-        is_published: updated_post.is_published,
-        created_at: updated_post.created_at,
-        updated_at: updated_post.updated_at,
+    let authors: HashMap<i32, User> = authors.into_iter().map(|user| (user.id, user)).collect();
+    posts
+        .into_iter()
+        .map(|post| {
+            let author = authors.get(&post.user_id).cloned().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+            Ok(PostWithUserResponse::from((post, author)))
+        })
+        .collect()
+}
+
+/// Internal id of a post the caller may edit, being its author or an
+/// admin. Posts the caller cannot see are not found.
+pub(crate) async fn editable_post_id(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+) -> Result<Result<i32, Response>, StatusCode> {
+    let Some(post_id) = visible_post_id(conn, id, Some(auth)).await? else {
+        return Ok(Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found")),
+        )
+            .into_response()));
     };
 
-    Ok(Json(ApiResponse::success(
-        response,
-        "Post updated successfully",
-    )))
+    let author_id = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", post_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if author_id != auth.id && !auth.is_admin() {
+        return Ok(Err(forbidden("Only the author or an admin can edit this post")));
+    }
+
+    Ok(Ok(post_id))
+}
+
+/// Collections a post is part of with its neighbours in each, `None` when
+/// it is in no collection
+async fn post_series(
+    conn: &mut PgConnection,
+    post_id: i32,
+    viewer: Option<i32>,
+    is_admin: bool,
+) -> Result<Option<Vec<PostSeriesResponse>>, StatusCode> {
+    let series = collection_service::series_of(conn, post_id, viewer, is_admin)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load post series: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok((!series.is_empty()).then(|| series.into_iter().map(PostSeriesResponse::from).collect()))
+}
+
+/// Replaces the tags of an updated post when the request sent them
+async fn retag_post(
+    conn: &mut PgConnection,
+    post: Option<Post>,
+    tags: Option<&[String]>,
+) -> Result<Option<Post>, StatusCode> {
+    match (post, tags) {
+        (Some(mut post), Some(tags)) => {
+            post.tags = tag_service::set_post_tags(conn, post.id, tags)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to tag post: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            Ok(Some(post))
+        }
+        (post, _) => Ok(post),
+    }
+}
+
+/// Editable state of a post before an update
+pub(crate) struct LockedPost {
+    pub id: i32,
+    user_id: i32,
+    status: String,
+    pub title: String,
+    content: String,
+    content_format: String,
+}
+
+/// Current state of a live post, locking the post until the transaction ends
+pub(crate) async fn locked_post(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<LockedPost>, StatusCode> {
+    sqlx::query_as!(
+        LockedPost,
+        "SELECT id, user_id, status, title, content, content_format FROM posts
+         WHERE public_id = $1 AND deleted_at IS NULL FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub(crate) fn may_edit_content(post: &LockedPost, auth: &AuthUser) -> Result<bool, StatusCode> {
+    let status = PostStatus::parse(&post.status).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(PostService::may_edit_content(status, post.user_id == auth.id, auth.is_admin()))
+}
+
+pub(crate) fn content_locked(post: &LockedPost) -> Response {
+    forbidden(&format!(
+        "Only admins can change the title or content of a post that is {}",
+        post.status
+    ))
+}
+
+/// Moves the slug of an updated post to the one the request sent, or along
+/// with the title when it sent none. `Err` is the response to send instead.
+async fn reslug_post(
+    conn: &mut PgConnection,
+    post: Option<Post>,
+    previous_title: Option<String>,
+    slug: Option<&str>,
+) -> Result<Option<Post>, Response> {
+    let Some(mut post) = post else {
+        return Ok(None);
+    };
+
+    let claimed = match slug {
+        Some(slug) => {
+            let claimed = slug_service::claim_slug(conn, post.id, slug).await;
+            if matches!(claimed, Ok(true)) {
+                post.slug = slug.to_string();
+            }
+            claimed
+        }
+        None => {
+            let previous_title = previous_title.unwrap_or_else(|| post.title.clone());
+            slug_service::follow_title(conn, &mut post, &previous_title)
+                .await
+                .map(|_| true)
+        }
+    };
+
+    match claimed {
+        Ok(true) => Ok(Some(post)),
+        Ok(false) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("Slug is already taken")),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to update slug: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+async fn updated_post_response(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+    post: Option<Post>,
+) -> Result<Response, StatusCode> {
+    match post {
+        Some(post) => written_post_response(conn, auth, post, "Post updated successfully").await,
+        None => missing_post_response(conn, id, false).await,
+    }
+}
+
+/// Response of a write that changed a post, carrying the same body and
+/// `ETag` a read of the post would
+pub(crate) async fn written_post_response(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    mut post: Post,
+    message: &str,
+) -> Result<Response, StatusCode> {
+    render_service::cache_html(conn, &mut post)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to cache rendered post: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let (tag, post) =
+        post_representation(conn, Some(auth), post, &PostQueryParams::default()).await?;
+
+    Ok((
+        StatusCode::OK,
+        conditional::representation_headers(&tag),
+        Json(ApiResponse::success(post, message)),
+    )
+        .into_response())
 }
 
 /// Delete post by ID
+///
+/// Moves the post to the trash, which its author or an admin may do;
+/// `force=true` deletes it permanently and is reserved for admins.
 #[utoipa::path(
     delete,
     path = "/api/posts/{id}",
     params(
-        ("id" = i32, Path, description = "Post ID")
+        ("id" = Uuid, Path, description = "Post ID"),
+        DeleteParams
     ),
     responses(
         (status = 200, description = "Post deleted successfully", body = StringApiResponse),
-        (status = 404, description = "Post not found")
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can delete the post, only admins can force delete"),
+        (status = 404, description = "Post not found"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
     ),
+    security(("bearer_auth" = [])),
     tag = "Posts"
 )]
 pub async fn delete_post(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let result = sqlx::query!(
-        "DELETE FROM posts WHERE id = $1",
-        id
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeleteParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let force = params.force.unwrap_or(false);
+    if force && !auth.is_admin() {
+        return Ok(forbidden("Only admins can permanently delete posts"));
+    }
+
+    let post = sqlx::query!(
+        "SELECT user_id, is_published FROM posts WHERE public_id = $1 AND ($2 OR deleted_at IS NULL)",
+        id,
+        force
     )
-    .execute(&state.db)
+    .fetch_optional(&mut *conn)
     .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Other users' unpublished posts are not found rather than forbidden
+    let Some(post) =
+        post.filter(|post| post.is_published || post.user_id == auth.id || auth.is_admin())
+    else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found")),
+        )
+            .into_response());
+    };
+
+    if post.user_id != auth.id && !auth.is_admin() {
+        return Ok(forbidden("Only the author or an admin can delete this post"));
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
+    let result = if force {
+        sqlx::query!(
+            "DELETE FROM posts
+             WHERE public_id = $1 AND ($2::timestamptz[] IS NULL OR updated_at = ANY($2))",
+            id,
+            if_match.versions()
+        )
+        .execute(&mut *conn)
+        .await
+    } else {
+        sqlx::query!(
+            "UPDATE posts SET deleted_at = NOW()
+             WHERE public_id = $1 AND deleted_at IS NULL
+               AND ($2::timestamptz[] IS NULL OR updated_at = ANY($2))",
+            id,
+            if_match.versions()
+        )
+        .execute(&mut *conn)
+        .await
+    }
     .map_err(|e| {
         tracing::error!("Failed to delete post: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
     if result.rows_affected() == 0 {
-        return Ok(Json(ApiResponse::error("Post not found")));
+        return missing_post_response(&mut conn, id, force).await;
+    }
+
+    let message = if force {
+        "Post permanently deleted"
+    } else {
+        "Post moved to trash"
+    };
+
+    Ok(Json(ApiResponse::success("Post deleted".to_string(), message)).into_response())
+}
+
+/// Restore a post from the trash
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/restore",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post restored successfully", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can restore the post"),
+        (status = 404, description = "Post not found in trash"),
+        (status = 409, description = "The author is in the trash")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Posts"
+)]
+pub async fn restore_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let trashed = sqlx::query!(
+        r#"
+        SELECT p.user_id, u.deleted_at IS NOT NULL AS "author_trashed!"
+        FROM posts p
+        JOIN users u ON u.id = p.user_id
+        WHERE p.public_id = $1 AND p.deleted_at IS NOT NULL
+        "#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(trashed) = trashed else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Post not found in trash")),
+        )
+            .into_response());
+    };
+
+    if trashed.user_id != auth.id && !auth.is_admin() {
+        return Ok(forbidden("Only the author or an admin can restore this post"));
+    }
+
+    if trashed.author_trashed {
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error("Restore the author before restoring the post")),
+        )
+            .into_response());
     }
 
+    let post_id = sqlx::query_scalar!(
+        "UPDATE posts SET deleted_at = NULL WHERE public_id = $1 RETURNING id",
+        id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to restore post: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let post = load_post(&mut conn, post_id, Some(auth.id)).await?;
+
     Ok(Json(ApiResponse::success(
-        "Post deleted".to_string(),
-        "Post deleted successfully",
-    )))
-}
\ No newline at end of file
+        PostResponse::from(post),
+        "Post restored successfully",
+    ))
+    .into_response())
+}
@@ -0,0 +1,111 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::Connection;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    database::connection::DbConn,
+    handlers::post::visible_post_id,
+    models::{
+        requests::ReactionRequest,
+        responses::{reaction_counts, ApiResponse, ReactionResponse},
+    },
+    services::reaction_service::{self, ReactionKind},
+};
+
+/// React to a post
+///
+/// Replaces the caller's previous reaction, if any. Sending the same
+/// reaction again changes nothing.
+#[utoipa::path(
+    put,
+    path = "/api/posts/{id}/reaction",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body = ReactionRequest,
+    responses(
+        (status = 200, description = "Reaction saved", body = ReactionApiResponse),
+        (status = 400, description = "Unknown reaction"),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Reactions"
+)]
+pub async fn set_reaction(
+    conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReactionRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Unknown reaction: {}", payload.kind))),
+        )
+            .into_response());
+    }
+
+    react(conn, auth, id, ReactionKind::parse(&payload.kind)).await
+}
+
+/// Remove the caller's reaction to a post
+///
+/// Succeeds whether or not the caller had reacted.
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/reaction",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Reaction removed", body = ReactionApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Reactions"
+)]
+pub async fn remove_reaction(
+    conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    react(conn, auth, id, None).await
+}
+
+async fn react(
+    mut conn: DbConn,
+    auth: AuthUser,
+    id: Uuid,
+    kind: Option<ReactionKind>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, Some(&auth)).await? else {
+        return Ok((StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Post not found")))
+            .into_response());
+    };
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let counts = reaction_service::set_reaction(&mut tx, post_id, auth.id, kind)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save reaction: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let response = ReactionResponse {
+        reaction_counts: reaction_counts(counts),
+        my_reaction: kind.map(|kind| kind.to_string()),
+    };
+    let message = if kind.is_some() { "Reaction saved" } else { "Reaction removed" };
+
+    Ok(Json(ApiResponse::success(response, message)).into_response())
+}
@@ -0,0 +1,311 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    database::connection::DbConn,
+    handlers::{
+        conditional::{self, IfMatch},
+        post::{
+            content_locked, editable_post_id, load_post, locked_post, may_edit_content,
+            visible_post_id, written_post_response,
+        },
+    },
+    models::{
+        requests::RevisionDiffParams,
+        responses::{
+            ApiResponse, DiffLineResponse, PostRevisionDiffResponse, PostRevisionResponse,
+            PostRevisionSummaryResponse,
+        },
+    },
+    services::{
+        revision_service::{diff_lines, DiffOp},
+        slug_service,
+    },
+    AppState,
+};
+
+/// List the revisions of a post, newest first
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/revisions",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Revisions of the post", body = PostRevisionsApiResponse),
+        (status = 404, description = "Post not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Revisions"
+)]
+pub async fn get_post_revisions(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, auth.as_ref()).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let revisions = sqlx::query_as!(
+        PostRevisionSummaryResponse,
+        r#"
+        SELECT r.revision, r.title, u.public_id AS "author_id?", r.created_at
+        FROM post_revisions r
+        LEFT JOIN users u ON u.id = r.author_id
+        WHERE r.post_id = $1
+        ORDER BY r.revision DESC
+        "#,
+        post_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(revisions, "Revisions retrieved successfully")).into_response())
+}
+
+/// Get one revision of a post
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/revisions/{rev}",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("rev" = i32, Path, description = "Revision number")
+    ),
+    responses(
+        (status = 200, description = "Revision found", body = PostRevisionApiResponse),
+        (status = 404, description = "Post or revision not found")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Revisions"
+)]
+pub async fn get_post_revision(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path((id, rev)): Path<(Uuid, i32)>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, auth.as_ref()).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    match fetch_revision(&mut conn, post_id, rev).await? {
+        Some(revision) => Ok(Json(ApiResponse::success(
+            revision,
+            "Revision found successfully",
+        ))
+        .into_response()),
+        None => Ok(not_found("Revision not found")),
+    }
+}
+
+/// Line-based diff of the content of two revisions
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/revisions/diff",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        RevisionDiffParams
+    ),
+    responses(
+        (status = 200, description = "Diff from `from` to `to`", body = PostRevisionDiffApiResponse),
+        (status = 404, description = "Post or revision not found"),
+        (status = 422, description = "Revisions are too large to diff")
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Revisions"
+)]
+pub async fn diff_post_revisions(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RevisionDiffParams>,
+) -> Result<Response, StatusCode> {
+    let Some(post_id) = visible_post_id(&mut conn, id, auth.as_ref()).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    let (Some(from), Some(to)) = (
+        fetch_revision(&mut conn, post_id, params.from).await?,
+        fetch_revision(&mut conn, post_id, params.to).await?,
+    ) else {
+        return Ok(not_found("Revision not found"));
+    };
+
+    let Some(lines) = diff_lines(&from.content, &to.content) else {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ApiResponse::<()>::error("Revisions are too large to diff")),
+        )
+            .into_response());
+    };
+
+    let count = |op| lines.iter().filter(|line| line.op == op).count();
+    let response = PostRevisionDiffResponse {
+        from_revision: from.revision,
+        to_revision: to.revision,
+        additions: count(DiffOp::Insert),
+        deletions: count(DiffOp::Delete),
+        lines: lines
+            .iter()
+            .map(|line| DiffLineResponse {
+                op: line.op.as_str().to_string(),
+                text: line.text.to_string(),
+            })
+            .collect(),
+        from_title: from.title,
+        to_title: to.title,
+    };
+
+    Ok(Json(ApiResponse::success(response, "Diff computed successfully")).into_response())
+}
+
+/// Restore an old revision
+///
+/// Copies the title and content of the revision back onto the post,
+/// which records them as a new revision. Like an edit of the content,
+/// only admins may restore revisions of a post that is no longer a draft.
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/revisions/{rev}/restore",
+    params(
+        ("id" = Uuid, Path, description = "Post ID"),
+        ("rev" = i32, Path, description = "Revision number to restore")
+    ),
+    responses(
+        (status = 200, description = "Revision restored", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can restore revisions, only admins can restore revisions of a post past draft"),
+        (status = 404, description = "Post or revision not found"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Revisions"
+)]
+pub async fn restore_post_revision(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path((id, rev)): Path<(Uuid, i32)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if let Err(response) = editable_post_id(&mut conn, &auth, id).await? {
+        return Ok(response);
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Locked so the slug follows the title the restore replaces
+    let Some(post) = locked_post(&mut tx, id).await? else {
+        return Ok(not_found("Post not found"));
+    };
+
+    // Restoring replaces the title and content, like an edit
+    if !may_edit_content(&post, &auth)? {
+        return Ok(content_locked(&post));
+    }
+
+    let restored = sqlx::query_scalar!(
+        r#"
+        WITH source AS (
+            SELECT title, content FROM post_revisions WHERE post_id = $1 AND revision = $2
+        ), updated AS (
+            UPDATE posts
+            SET title = source.title,
+                content = source.content,
+                content_html = CASE
+                    WHEN posts.content IS DISTINCT FROM source.content THEN NULL
+                    ELSE posts.content_html
+                END,
+                revision = posts.revision + CASE
+                    WHEN posts.title IS DISTINCT FROM source.title
+                      OR posts.content IS DISTINCT FROM source.content THEN 1 ELSE 0
+                END,
+                updated_at = NOW()
+            FROM source
+            WHERE posts.id = $1 AND posts.deleted_at IS NULL
+              AND ($3::timestamptz[] IS NULL OR posts.updated_at = ANY($3))
+            RETURNING posts.*
+        ), new_revision AS (
+            INSERT INTO post_revisions (post_id, revision, title, content, author_id)
+            SELECT id, revision, title, content, $4 FROM updated
+            ON CONFLICT (post_id, revision) DO NOTHING
+        )
+        SELECT id AS "id!" FROM updated
+        "#,
+        post.id,
+        rev,
+        if_match.versions(),
+        auth.id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to restore revision: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let restored = match restored {
+        Some(post_id) => {
+            let mut restored = load_post(&mut tx, post_id, Some(auth.id)).await?;
+            slug_service::follow_title(&mut tx, &mut restored, &post.title)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to update slug: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            Some(restored)
+        }
+        None => None,
+    };
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match restored {
+        Some(post) => {
+            let message = format!("Revision {} restored", rev);
+            written_post_response(&mut conn, &auth, post, &message).await
+        }
+        None if fetch_revision(&mut conn, post.id, rev).await?.is_none() => {
+            Ok(not_found("Revision not found"))
+        }
+        None => Ok(conditional::precondition_failed()),
+    }
+}
+
+async fn fetch_revision(
+    conn: &mut PgConnection,
+    post_id: i32,
+    rev: i32,
+) -> Result<Option<PostRevisionResponse>, StatusCode> {
+    sqlx::query_as!(
+        PostRevisionResponse,
+        r#"
+        SELECT r.revision, r.title, r.content, u.public_id AS "author_id?", r.created_at
+        FROM post_revisions r
+        LEFT JOIN users u ON u.id = r.author_id
+        WHERE r.post_id = $1 AND r.revision = $2
+        "#,
+        post_id,
+        rev
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
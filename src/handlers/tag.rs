@@ -0,0 +1,251 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use validator::Validate;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    database::connection::DbConn,
+    models::{
+        requests::{MergeTagRequest, RenameTagRequest},
+        responses::{ApiResponse, TagResponse},
+    },
+    services::tag_service::normalize_tag,
+};
+
+/// List tags with their number of published posts
+///
+/// Admins also see tags that are only used on unpublished posts.
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    responses(
+        (status = 200, description = "Tags, most used first", body = TagsApiResponse)
+    ),
+    security((), ("bearer_auth" = [])),
+    tag = "Tags"
+)]
+pub async fn get_tags(
+    mut conn: DbConn,
+    auth: Option<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<TagResponse>>>, StatusCode> {
+    let tags = sqlx::query_as!(
+        TagResponse,
+        r#"
+        SELECT t.name, COUNT(p.id) AS "post_count!"
+        FROM tags t
+        LEFT JOIN post_tags pt ON pt.tag_id = t.id
+        LEFT JOIN posts p ON p.id = pt.post_id AND p.is_published AND p.deleted_at IS NULL
+        GROUP BY t.id
+        HAVING COUNT(p.id) > 0 OR $1
+        ORDER BY COUNT(p.id) DESC, t.name
+        "#,
+        auth.is_some_and(|auth| auth.is_admin())
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(tags, "Tags retrieved successfully")))
+}
+
+/// Rename a tag (admin only)
+#[utoipa::path(
+    patch,
+    path = "/api/tags/{name}",
+    params(
+        ("name" = String, Path, description = "Tag name")
+    ),
+    request_body = RenameTagRequest,
+    responses(
+        (status = 200, description = "Tag renamed", body = TagApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can rename tags"),
+        (status = 404, description = "Tag not found"),
+        (status = 409, description = "A tag with the new name already exists")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Tags"
+)]
+pub async fn rename_tag(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(name): Path<String>,
+    Json(payload): Json<RenameTagRequest>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can rename tags"));
+    }
+
+    let new_name = normalize_tag(&payload.name);
+    if payload.validate().is_err() || new_name.is_empty() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let renamed = sqlx::query_scalar!(
+        "UPDATE tags SET name = $2 WHERE name = $1 RETURNING id",
+        normalize_tag(&name),
+        new_name
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+
+    let tag_id = match renamed {
+        Ok(Some(tag_id)) => tag_id,
+        Ok(None) => return Ok(not_found()),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            return Ok(conflict(&format!(
+                "Tag '{}' already exists, merge into it instead",
+                new_name
+            )));
+        }
+        Err(e) => {
+            tracing::error!("Failed to rename tag: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // The tags are part of each post's representation
+    touch_tagged_posts(&mut tx, tag_id).await?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let tag = fetch_tag(&mut conn, &new_name).await?;
+    Ok(Json(ApiResponse::success(tag, "Tag renamed successfully")).into_response())
+}
+
+/// Merge a tag into another (admin only)
+///
+/// Posts tagged with the merged tag get the target tag instead, and the
+/// merged tag is deleted.
+#[utoipa::path(
+    post,
+    path = "/api/tags/{name}/merge",
+    params(
+        ("name" = String, Path, description = "Tag to merge away")
+    ),
+    request_body = MergeTagRequest,
+    responses(
+        (status = 200, description = "Tags merged, returns the target tag", body = TagApiResponse),
+        (status = 400, description = "Invalid input or a tag merged into itself"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can merge tags"),
+        (status = 404, description = "Tag not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Tags"
+)]
+pub async fn merge_tag(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(name): Path<String>,
+    Json(payload): Json<MergeTagRequest>,
+) -> Result<Response, StatusCode> {
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can merge tags"));
+    }
+
+    if payload.validate().is_err() {
+        return Ok(bad_request("Invalid input data"));
+    }
+
+    let (source_name, target_name) = (normalize_tag(&name), normalize_tag(&payload.into));
+    if source_name == target_name {
+        return Ok(bad_request("A tag cannot be merged into itself"));
+    }
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let tags = sqlx::query!(
+        "SELECT id, name FROM tags WHERE name = $1 OR name = $2 FOR UPDATE",
+        source_name,
+        target_name
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let find = |wanted: &str| tags.iter().find(|tag| tag.name == wanted).map(|tag| tag.id);
+    let (Some(source_id), Some(target_id)) = (find(&source_name), find(&target_name)) else {
+        return Ok(not_found());
+    };
+
+    touch_tagged_posts(&mut tx, source_id).await?;
+
+    sqlx::query!(
+        "INSERT INTO post_tags (post_id, tag_id)
+         SELECT post_id, $2 FROM post_tags WHERE tag_id = $1
+         ON CONFLICT DO NOTHING",
+        source_id,
+        target_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to merge tags: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!("DELETE FROM tags WHERE id = $1", source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let tag = fetch_tag(&mut conn, &target_name).await?;
+    Ok(Json(ApiResponse::success(
+        tag,
+        &format!("Tag '{}' merged into '{}'", source_name, target_name),
+    ))
+    .into_response())
+}
+
+/// Bumps `updated_at` of the posts carrying a tag so their validators change
+async fn touch_tagged_posts(conn: &mut PgConnection, tag_id: i32) -> Result<(), StatusCode> {
+    sqlx::query!(
+        "UPDATE posts SET updated_at = NOW()
+         WHERE id IN (SELECT post_id FROM post_tags WHERE tag_id = $1)",
+        tag_id
+    )
+    .execute(conn)
+    .await
+    .map(|_| ())
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn fetch_tag(conn: &mut PgConnection, name: &str) -> Result<TagResponse, StatusCode> {
+    sqlx::query_as!(
+        TagResponse,
+        r#"
+        SELECT t.name, COUNT(p.id) AS "post_count!"
+        FROM tags t
+        LEFT JOIN post_tags pt ON pt.tag_id = t.id
+        LEFT JOIN posts p ON p.id = pt.post_id AND p.is_published AND p.deleted_at IS NULL
+        WHERE t.name = $1
+        GROUP BY t.id
+        "#,
+        name
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn bad_request(message: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+fn not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error("Tag not found"))).into_response()
+}
+
+fn conflict(message: &str) -> Response {
+    (StatusCode::CONFLICT, Json(ApiResponse::<()>::error(message))).into_response()
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::{
+    auth::AuthUser,
+    config::AppState,
+    database::{connection::DbConn, models::TrashedItem},
+    models::{
+        requests::PaginationParams,
+        responses::{ApiResponse, PaginatedTrashResponse, TrashItemResponse},
+    },
+};
+
+/// List soft-deleted users and posts
+///
+/// Admins see everything in the trash, other users only their own posts.
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Trashed items, most recently deleted first", body = TrashApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Trash"
+)]
+pub async fn get_trash(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<ApiResponse<PaginatedTrashResponse>>, StatusCode> {
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM users WHERE deleted_at IS NOT NULL AND $1)
+          + (SELECT COUNT(*) FROM posts WHERE deleted_at IS NOT NULL AND ($1 OR user_id = $2))
+          AS "count!"
+        "#,
+        auth.is_admin(),
+        auth.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let items = sqlx::query_as!(
+        TrashedItem,
+        r#"
+        SELECT kind AS "kind!", public_id AS "public_id!", label AS "label!", deleted_at AS "deleted_at!"
+        FROM (
+            SELECT 'user' AS kind, public_id, username AS label, deleted_at
+            FROM users
+            WHERE deleted_at IS NOT NULL AND $1
+            UNION ALL
+            SELECT 'post', public_id, title, deleted_at
+            FROM posts
+            WHERE deleted_at IS NOT NULL AND ($1 OR user_id = $2)
+        ) trash
+        ORDER BY deleted_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        auth.is_admin(),
+        auth.id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let retention = chrono::Duration::days(state.config.trash_retention_days as i64);
+    let data = items
+        .into_iter()
+        .map(|item| TrashItemResponse {
+            kind: item.kind,
+            id: item.public_id,
+            label: item.label,
+            deleted_at: item.deleted_at,
+            purge_at: item.deleted_at + retention,
+        })
+        .collect();
+
+    let total_pages = params.total_pages(total);
+
+    let response = PaginatedTrashResponse {
+        data,
+        page,
+        limit,
+        total,
+        total_pages,
+    };
+
+    Ok(Json(ApiResponse::success(
+        response,
+        "Trash retrieved successfully",
+    )))
+}
@@ -1,37 +1,49 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
+use chrono::{DateTime, Utc};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
+    auth::{forbidden, AuthUser},
     config::AppState,
-    database::models::User,
+    database::{connection::DbConn, models::User},
+    handlers::conditional::{self, IfMatch},
     models::{
-        requests::{CreateUserRequest, PaginationParams, UpdateUserRequest},
+        requests::{
+            CreateUserRequest, DeleteParams, PaginationParams, PatchUserRequest, UpdateUserRequest,
+        },
         responses::{ApiResponse, PaginatedUserResponse, UserResponse},
     },
+    services::trash_service,
 };
 
 /// Create a new user
 #[utoipa::path(
     post,
     path = "/api/users",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the original response when the request is retried")
+    ),
     request_body = CreateUserRequest,
     responses(
         (status = 201, description = "User created successfully", body = UserApiResponse),
         (status = 400, description = "Invalid input"),
-        (status = 409, description = "User already exists")
+        (status = 409, description = "User already exists or a request with the same Idempotency-Key is in progress"),
+        (status = 422, description = "Idempotency-Key reused with a different body")
     ),
     tag = "Users"
 )]
 pub async fn create_user(
-    State(state): State<AppState>,
+    mut conn: DbConn,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<ApiResponse<UserResponse>>), StatusCode> {
     // Validate input
-    if let Err(_) = payload.validate() {
+    if payload.validate().is_err() {
         return Ok((
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::error("Invalid input data")),
@@ -43,7 +55,7 @@ pub async fn create_user(
         "SELECT id FROM users WHERE email = $1",
         payload.email
     )
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -59,30 +71,23 @@ pub async fn create_user(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     // Create user
-    let user_result = sqlx::query!(
+    let user = sqlx::query_as!(
+        User,
         r#"
         INSERT INTO users (email, username, password_hash, full_name)
         VALUES ($1, $2, $3, $4)
-        RETURNING id, email, username, password_hash, full_name, is_active, created_at, updated_at
+        RETURNING id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at
         "#,
         payload.email,
         payload.username,
         password_hash,
         payload.full_name
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let response = UserResponse {
-        id: user_result.id,
-        email: user_result.email,
-        username: user_result.username,
-        full_name: user_result.full_name,
-        is_active: user_result.is_active,
-        created_at: user_result.created_at,
-        updated_at: user_result.updated_at,
-    };
+    let response = UserResponse::from(user);
 
     Ok((
         StatusCode::CREATED,
@@ -101,47 +106,39 @@ pub async fn create_user(
     tag = "Users"
 )]
 pub async fn get_users(
-    State(state): State<AppState>,
+    mut conn: DbConn,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<ApiResponse<PaginatedUserResponse>>, StatusCode> {
-    let page = params.page.unwrap_or(1);
-    let limit = params.limit.unwrap_or(10);
-    let offset = (page - 1) * limit;
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
 
     // Get total count
     let total_result = sqlx::query!(
-        "SELECT COUNT(*) as count FROM users"
+        "SELECT COUNT(*) as count FROM users WHERE deleted_at IS NULL"
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let total = total_result.count.unwrap_or(0) as u64;
-    let total_pages = (total as f64 / limit as f64).ceil() as u64;
+    let total_pages = params.total_pages(total);
 
     // Get users with pagination
     let users = sqlx::query_as!(
         User,
-        "SELECT id, email, username, password_hash, full_name, is_active, created_at, updated_at 
-         FROM users ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+        "SELECT id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at 
+         FROM users WHERE deleted_at IS NULL ORDER BY created_at DESC LIMIT $1 OFFSET $2",
         limit as i64,
         offset as i64
     )
-    .fetch_all(&state.db)
+    .fetch_all(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let user_responses: Vec<UserResponse> = users
         .into_iter()
-        .map(|user| UserResponse {
-            id: user.id,
-            email: user.email,
-            username: user.username,
-            full_name: user.full_name,
-            is_active: user.is_active,
-            created_at: user.created_at,
-            updated_at: user.updated_at,
-        })
+        .map(UserResponse::from)
         .collect();
 
     let response = PaginatedUserResponse {
@@ -163,162 +160,472 @@ pub async fn get_users(
     get,
     path = "/api/users/{id}",
     params(
-        ("id" = i32, Path, description = "User ID")
+        ("id" = Uuid, Path, description = "User ID")
     ),
     responses(
         (status = 200, description = "User found", body = UserApiResponse),
+        (status = 304, description = "Not modified since the given `If-None-Match`/`If-Modified-Since`"),
         (status = 404, description = "User not found")
     ),
     tag = "Users"
 )]
 pub async fn get_user_by_id(
-    State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<UserResponse>>, StatusCode> {
+    mut conn: DbConn,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let user = sqlx::query_as!(
         User,
-        "SELECT id, email, username, password_hash, full_name, is_active, created_at, updated_at 
-         FROM users WHERE id = $1",
+        "SELECT id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at 
+         FROM users WHERE public_id = $1 AND deleted_at IS NULL",
         id
     )
-    .fetch_optional(&state.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     match user {
+        Some(user) if conditional::is_not_modified(&headers, &user.updated_at) => {
+            Ok(conditional::not_modified(&user.updated_at))
+        }
         Some(user) => {
-            let response = UserResponse {
-                id: user.id,
-                email: user.email,
-                username: user.username,
-                full_name: user.full_name,
-                is_active: user.is_active,
-                created_at: user.created_at,
-                updated_at: user.updated_at,
-            };
-
-            Ok(Json(ApiResponse::success(
-                response,
-                "User found successfully",
-            )))
+            let validators = conditional::validator_headers(&user.updated_at);
+            let response = UserResponse::from(user);
+
+            Ok((
+                validators,
+                Json(ApiResponse::success(response, "User found successfully")),
+            )
+                .into_response())
         }
-        None => Ok(Json(ApiResponse::error("User not found"))),
+        None => Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("User not found")),
+        )
+            .into_response()),
     }
 }
 
-/// Update user by ID
+/// Replace user by ID
 #[utoipa::path(
     put,
     path = "/api/users/{id}",
     params(
-        ("id" = i32, Path, description = "User ID")
+        ("id" = Uuid, Path, description = "User ID")
     ),
     request_body = UpdateUserRequest,
     responses(
         (status = 200, description = "User updated successfully", body = UserApiResponse),
         (status = 404, description = "User not found"),
-        (status = 400, description = "Invalid input")
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the user or an admin can update the user"),
+        (status = 409, description = "Email already in use"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
     ),
+    security(("bearer_auth" = [])),
     tag = "Users"
 )]
 pub async fn update_user(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateUserRequest>,
-) -> Result<Json<ApiResponse<UserResponse>>, StatusCode> {
+) -> Result<Response, StatusCode> {
+    if !may_manage_user(&mut conn, &auth, id).await? {
+        return Ok(forbidden("Only the user or an admin can update this user"));
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
     // Validate input
-    if let Err(_) = payload.validate() {
-        return Ok(Json(ApiResponse::error("Invalid input data")));
+    if payload.validate().is_err() {
+        return Ok(invalid_input());
     }
 
-    // Check if user exists
-    let existing_user = sqlx::query!(
-        "SELECT id FROM users WHERE id = $1",
-        id
+    let result = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users 
+        SET email = $2,
+            username = $3,
+            full_name = $4,
+            is_active = $5,
+            updated_at = NOW()
+        WHERE public_id = $1 AND deleted_at IS NULL
+          AND ($6::timestamptz[] IS NULL OR updated_at = ANY($6))
+        RETURNING id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at
+        "#,
+        id,
+        payload.email,
+        payload.username,
+        payload.full_name,
+        payload.is_active,
+        if_match.versions()
     )
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .fetch_optional(&mut *conn)
+    .await;
+
+    updated_user_response(&mut conn, id, result).await
+}
 
-    if existing_user.is_none() {
-        return Ok(Json(ApiResponse::error("User not found")));
+/// Partially update user by ID (JSON Merge Patch)
+#[utoipa::path(
+    patch,
+    path = "/api/users/{id}",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body(content = PatchUserRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "User updated successfully", body = UserApiResponse),
+        (status = 404, description = "User not found"),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the user or an admin can update the user"),
+        (status = 409, description = "Email already in use"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn patch_user(
+    State(state): State<AppState>,
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<PatchUserRequest>,
+) -> Result<Response, StatusCode> {
+    if !may_manage_user(&mut conn, &auth, id).await? {
+        return Ok(forbidden("Only the user or an admin can update this user"));
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
+    if let Some(field) = payload.nulled_required_fields().first() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(&format!("Field '{}' cannot be null", field))),
+        )
+            .into_response());
+    }
+
+    // Validate the fields that are present
+    if payload.validate().is_err() {
+        return Ok(invalid_input());
     }
 
-    // Simple update - update all fields
-    let updated_user = sqlx::query!(
+    // Absent fields keep their value, `null` clears nullable ones
+    let result = sqlx::query_as!(
+        User,
         r#"
         UPDATE users 
         SET email = COALESCE($2, email),
             username = COALESCE($3, username),
-            full_name = COALESCE($4, full_name),
-            is_active = COALESCE($5, is_active),
+            full_name = CASE WHEN $4 THEN $5 ELSE full_name END,
+            is_active = COALESCE($6, is_active),
             updated_at = NOW()
-        WHERE id = $1
-        RETURNING id, email, username, password_hash, full_name, is_active, created_at, updated_at
+        WHERE public_id = $1 AND deleted_at IS NULL
+          AND ($7::timestamptz[] IS NULL OR updated_at = ANY($7))
+        RETURNING id, public_id, email, username, password_hash, full_name, role, is_active, created_at, updated_at
         "#,
         id,
-        payload.email,
-        payload.username,
-        payload.full_name,
-        payload.is_active
+        payload.email.flatten(),
+        payload.username.flatten(),
+        payload.full_name.is_some(),
+        payload.full_name.flatten(),
+        payload.is_active.flatten(),
+        if_match.versions()
+    )
+    .fetch_optional(&mut *conn)
+    .await;
+
+    updated_user_response(&mut conn, id, result).await
+}
+
+/// Whether the caller is the user with this public id or an admin
+async fn may_manage_user(conn: &mut PgConnection, auth: &AuthUser, id: Uuid) -> Result<bool, StatusCode> {
+    if auth.is_admin() {
+        return Ok(true);
+    }
+
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND public_id = $2) AS "is_self!""#,
+        auth.id,
+        id
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut *conn)
     .await
-    .map_err(|e| {
-        tracing::error!("Failed to update user: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
 
-    let response = UserResponse {
-        id: updated_user.id,
-        email: updated_user.email,
-        username: updated_user.username,
-        full_name: updated_user.full_name,
-        is_active: updated_user.is_active,
-        created_at: updated_user.created_at,
-        updated_at: updated_user.updated_at,
-    };
+fn invalid_input() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::<()>::error("Invalid input data")),
+    )
+        .into_response()
+}
 
-    Ok(Json(ApiResponse::success(
-        response,
-        "User updated successfully",
-    )))
+/// Builds the response of a conditional write, telling a missing user
+/// apart from a failed `If-Match` when no row was touched
+async fn missing_user_response(
+    conn: &mut PgConnection,
+    id: Uuid,
+    include_trashed: bool,
+) -> Result<Response, StatusCode> {
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE public_id = $1 AND ($2 OR deleted_at IS NULL))",
+        id,
+        include_trashed
+    )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .unwrap_or(false);
+
+    if exists {
+        Ok(conditional::precondition_failed())
+    } else {
+        Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("User not found")),
+        )
+            .into_response())
+    }
+}
+
+/// Names the field a unique violation on `users` is about
+fn conflict_message(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("users_email_key") => "User with this email already exists",
+        Some(name) if name.contains("username") => "User with this username already exists",
+        _ => "User conflicts with an existing user",
+    }
+}
+
+async fn updated_user_response(
+    conn: &mut PgConnection,
+    id: Uuid,
+    result: Result<Option<User>, sqlx::Error>,
+) -> Result<Response, StatusCode> {
+    match result {
+        Ok(Some(user)) => Ok((
+            StatusCode::OK,
+            conditional::validator_headers(&user.updated_at),
+            Json(ApiResponse::success(
+                UserResponse::from(user),
+                "User updated successfully",
+            )),
+        )
+            .into_response()),
+        Ok(None) => missing_user_response(conn, id, false).await,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => Ok((
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(conflict_message(e.constraint()))),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to update user: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
 }
 
 /// Delete user by ID
+///
+/// Moves the user and their posts to the trash, which users may do to their
+/// own account; `force=true` deletes them permanently and is reserved for
+/// admins.
 #[utoipa::path(
     delete,
     path = "/api/users/{id}",
     params(
-        ("id" = i32, Path, description = "User ID")
+        ("id" = Uuid, Path, description = "User ID"),
+        DeleteParams
     ),
     responses(
         (status = 200, description = "User deleted successfully", body = StringApiResponse),
-        (status = 404, description = "User not found")
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the user or an admin can delete the user, only admins can force delete"),
+        (status = 404, description = "User not found"),
+        (status = 412, description = "`If-Match` does not match the current version"),
+        (status = 428, description = "`If-Match` is required")
     ),
+    security(("bearer_auth" = [])),
     tag = "Users"
 )]
 pub async fn delete_user(
     State(state): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let result = sqlx::query!(
-        "DELETE FROM users WHERE id = $1",
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DeleteParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let force = params.force.unwrap_or(false);
+    if force && !auth.is_admin() {
+        return Ok(forbidden("Only admins can permanently delete users"));
+    }
+
+    if !may_manage_user(&mut conn, &auth, id).await? {
+        return Ok(forbidden("Only the user or an admin can delete this user"));
+    }
+
+    let if_match = IfMatch::from_headers(&headers);
+    if state.config.require_if_match && if_match.is_absent() {
+        return Ok(conditional::precondition_required());
+    }
+
+    let rows_affected = if force {
+        force_delete_user(&mut conn, id, if_match.versions()).await
+    } else {
+        // The user's posts go to the trash with the same timestamp so that
+        // restoring the user brings them back too
+        sqlx::query!(
+            r#"
+            WITH trashed AS (
+                UPDATE users
+                SET deleted_at = NOW()
+                WHERE public_id = $1 AND deleted_at IS NULL
+                  AND ($2::timestamptz[] IS NULL OR updated_at = ANY($2))
+                RETURNING id, deleted_at
+            ), trashed_posts AS (
+                UPDATE posts
+                SET deleted_at = trashed.deleted_at
+                FROM trashed
+                WHERE posts.user_id = trashed.id AND posts.deleted_at IS NULL
+            )
+            SELECT id FROM trashed
+            "#,
+            id,
+            if_match.versions()
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map(|rows| rows.len() as u64)
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to delete user: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if rows_affected == 0 {
+        return missing_user_response(&mut conn, id, force).await;
+    }
+
+    let message = if force {
+        "User permanently deleted"
+    } else {
+        "User moved to trash"
+    };
+
+    Ok(Json(ApiResponse::success("User deleted".to_string(), message)).into_response())
+}
+
+/// Deletes a user for good when `versions` is empty or matches, returning
+/// the number of users deleted
+async fn force_delete_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    versions: Option<&[DateTime<Utc>]>,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    let user_ids = sqlx::query_scalar!(
+        "SELECT id FROM users
+         WHERE public_id = $1 AND ($2::timestamptz[] IS NULL OR updated_at = ANY($2))
+         FOR UPDATE",
+        id,
+        versions
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let deleted = trash_service::delete_users(&mut tx, &user_ids).await?;
+
+    tx.commit().await?;
+
+    Ok(deleted)
+}
+
+/// Restore a user from the trash
+///
+/// Posts that were trashed together with the user are restored as well.
+#[utoipa::path(
+    post,
+    path = "/api/users/{id}/restore",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User restored successfully", body = UserApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can restore users"),
+        (status = 404, description = "User not found in trash")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Users"
+)]
+pub async fn restore_user(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    // Trashed users cannot sign in, so only admins can bring them back
+    if !auth.is_admin() {
+        return Ok(forbidden("Only admins can restore users"));
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        WITH target AS (
+            SELECT id, deleted_at FROM users
+            WHERE public_id = $1 AND deleted_at IS NOT NULL
+            FOR UPDATE
+        ), restored_posts AS (
+            UPDATE posts
+            SET deleted_at = NULL
+            FROM target
+            WHERE posts.user_id = target.id AND posts.deleted_at = target.deleted_at
+        )
+        UPDATE users
+        SET deleted_at = NULL
+        FROM target
+        WHERE users.id = target.id
+        RETURNING users.id, users.public_id, users.email, users.username, users.password_hash,
+                  users.full_name, users.role, users.is_active, users.created_at, users.updated_at
+        "#,
         id
     )
-    .execute(&state.db)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|e| {
-        tracing::error!("Failed to delete user: {}", e);
+        tracing::error!("Failed to restore user: {}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    if result.rows_affected() == 0 {
-        return Ok(Json(ApiResponse::error("User not found")));
+    match user {
+        Some(user) => Ok(Json(ApiResponse::success(
+            UserResponse::from(user),
+            "User restored successfully",
+        ))
+        .into_response()),
+        None => Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("User not found in trash")),
+        )
+            .into_response()),
     }
-
-    Ok(Json(ApiResponse::success(
-        "User deleted".to_string(),
-        "User deleted successfully",
-    )))
-}
\ No newline at end of file
+}
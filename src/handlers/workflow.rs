@@ -0,0 +1,454 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::{Connection, PgConnection};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::{forbidden, AuthUser},
+    database::connection::DbConn,
+    handlers::post::{load_post, load_posts},
+    models::{
+        requests::{PaginationParams, RejectPostRequest},
+        responses::{ApiResponse, PaginatedResponse, PostResponse, PostStatusChangeResponse},
+    },
+    services::post_service::{PostAction, PostService, PostStatus, TransitionError},
+};
+
+/// Submit a draft for review
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/submit",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post is in review", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author can submit the post"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is not a draft")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn submit_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    transition_post(&mut conn, &auth, id, PostAction::Submit, None).await
+}
+
+/// Approve a post in review
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/approve",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post approved", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can approve posts"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is not in review")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn approve_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    transition_post(&mut conn, &auth, id, PostAction::Approve, None).await
+}
+
+/// Send a post in review back to draft with feedback for the author
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/reject",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    request_body = RejectPostRequest,
+    responses(
+        (status = 200, description = "Post returned to draft", body = PostApiResponse),
+        (status = 400, description = "Invalid input"),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only admins can reject posts"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is not in review")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn reject_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectPostRequest>,
+) -> Result<Response, StatusCode> {
+    if payload.validate().is_err() {
+        return Ok((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("Invalid input data")),
+        )
+            .into_response());
+    }
+
+    transition_post(&mut conn, &auth, id, PostAction::Reject, Some(&payload.comment)).await
+}
+
+/// Publish an approved post
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/publish",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post published", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can publish the post"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is not approved")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn publish_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    transition_post(&mut conn, &auth, id, PostAction::Publish, None).await
+}
+
+/// Take a published post back to approved, so it can be published again
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/unpublish",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post unpublished", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can unpublish the post"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is not published")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn unpublish_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    transition_post(&mut conn, &auth, id, PostAction::Unpublish, None).await
+}
+
+/// Archive a post
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/archive",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Post archived", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can archive the post"),
+        (status = 404, description = "Post not found"),
+        (status = 409, description = "Post is in review or already archived")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn archive_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    transition_post(&mut conn, &auth, id, PostAction::Archive, None).await
+}
+
+/// Get the status changes of a post, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/history",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Status history", body = PostHistoryApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 404, description = "Post not found")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn get_post_history(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let post = sqlx::query!(
+        "SELECT id FROM posts
+         WHERE public_id = $1 AND deleted_at IS NULL
+           AND (is_published OR user_id = $2 OR $3)",
+        id,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(post) = post else {
+        return Ok(post_not_found());
+    };
+
+    let history = sqlx::query_as!(
+        PostStatusChangeResponse,
+        "SELECT h.from_status, h.to_status, u.public_id AS \"actor_id?\", h.comment, h.created_at
+         FROM post_status_history h
+         LEFT JOIN users u ON u.id = h.actor_id
+         WHERE h.post_id = $1
+         ORDER BY h.created_at, h.id",
+        post.id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ApiResponse::success(history, "Post history retrieved successfully")).into_response())
+}
+
+/// List scheduled posts, next to be published first
+///
+/// Admins see every scheduled post, other users their own.
+#[utoipa::path(
+    get,
+    path = "/api/posts/scheduled",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "Scheduled posts", body = PostsApiResponse),
+        (status = 401, description = "Authentication required")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn get_scheduled_posts(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+) -> Result<(StatusCode, Json<ApiResponse<PaginatedResponse<PostResponse>>>), StatusCode> {
+    let page = params.page();
+    let limit = params.limit();
+    let offset = params.offset();
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM posts
+        WHERE publish_at IS NOT NULL AND deleted_at IS NULL
+          AND status IN ('draft', 'in_review', 'approved')
+          AND (user_id = $1 OR $2)
+        "#,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? as u64;
+
+    let ids = sqlx::query_scalar!(
+        "SELECT id FROM posts
+         WHERE publish_at IS NOT NULL AND deleted_at IS NULL
+           AND status IN ('draft', 'in_review', 'approved')
+           AND (user_id = $1 OR $2)
+         ORDER BY publish_at, id
+         LIMIT $3 OFFSET $4",
+        auth.id,
+        auth.is_admin(),
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let posts = load_posts(&mut conn, &ids, Some(auth.id)).await?;
+
+    let total_pages = params.total_pages(total);
+
+    let response = PaginatedResponse {
+        data: posts.into_iter().map(PostResponse::from).collect(),
+        page,
+        limit,
+        total,
+        total_pages,
+    };
+
+    Ok((
+        StatusCode::OK,
+        Json(ApiResponse::success(response, "Scheduled posts retrieved successfully")),
+    ))
+}
+
+/// Cancel the scheduled publication of a post
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/schedule",
+    params(
+        ("id" = Uuid, Path, description = "Post ID")
+    ),
+    responses(
+        (status = 200, description = "Schedule cancelled", body = PostApiResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "Only the author or an admin can cancel the schedule"),
+        (status = 404, description = "Post not found or not scheduled"),
+        (status = 409, description = "Post was published in the meantime")
+    ),
+    security(("bearer_auth" = [])),
+    tag = "Workflow"
+)]
+pub async fn cancel_scheduled_post(
+    mut conn: DbConn,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let scheduled = sqlx::query_scalar!(
+        "SELECT user_id FROM posts
+         WHERE public_id = $1 AND publish_at IS NOT NULL AND deleted_at IS NULL
+           AND status IN ('draft', 'in_review', 'approved')",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(author_id) = scheduled else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("Scheduled post not found")),
+        )
+            .into_response());
+    };
+
+    if author_id != auth.id && !auth.is_admin() {
+        return Ok(forbidden("Only the author or an admin can cancel this schedule"));
+    }
+
+    // The scheduler may have published the post since the check above
+    let post_id = sqlx::query_scalar!(
+        "UPDATE posts
+         SET publish_at = NULL, updated_at = NOW()
+         WHERE public_id = $1 AND publish_at IS NOT NULL
+           AND status IN ('draft', 'in_review', 'approved')
+         RETURNING id",
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to cancel scheduled post: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let post = match post_id {
+        Some(post_id) => Some(load_post(&mut conn, post_id, Some(auth.id)).await?),
+        None => None,
+    };
+
+    match post {
+        Some(post) => Ok(Json(ApiResponse::success(
+            PostResponse::from(post),
+            "Scheduled publication cancelled",
+        ))
+        .into_response()),
+        None => Ok(conflict("Post was published before the schedule could be cancelled")),
+    }
+}
+
+async fn transition_post(
+    conn: &mut PgConnection,
+    auth: &AuthUser,
+    id: Uuid,
+    action: PostAction,
+    comment: Option<&str>,
+) -> Result<Response, StatusCode> {
+    // Other users' unpublished posts are not found rather than forbidden
+    let post = sqlx::query!(
+        "SELECT id, user_id, status FROM posts
+         WHERE public_id = $1 AND deleted_at IS NULL
+           AND (is_published OR user_id = $2 OR $3)",
+        id,
+        auth.id,
+        auth.is_admin()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let Some(post) = post else {
+        return Ok(post_not_found());
+    };
+
+    let from = PostStatus::parse(&post.status).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let to = match PostService::transition(action, from, post.user_id == auth.id, auth.is_admin()) {
+        Ok(to) => to,
+        Err(TransitionError::Forbidden) => {
+            return Ok(forbidden(&format!(
+                "You are not allowed to {} this post",
+                action.as_str()
+            )));
+        }
+        Err(TransitionError::InvalidTransition { from, action }) => {
+            return Ok(conflict(&format!(
+                "Cannot {} a post that is {}",
+                action.as_str(),
+                from
+            )));
+        }
+    };
+
+    let mut tx = conn.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let updated = PostService::apply_transition(&mut tx, post.id, from, to, auth.id, comment)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to {} post: {}", action.as_str(), e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Someone else moved the post on in the meantime
+    let Some(updated) = updated else {
+        return Ok(conflict("Post status changed concurrently, please retry"));
+    };
+
+    Ok(Json(ApiResponse::success(
+        PostResponse::from(updated),
+        &format!("Post is now {}", to),
+    ))
+    .into_response())
+}
+
+fn post_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ApiResponse::<()>::error("Post not found")),
+    )
+        .into_response()
+}
+
+fn conflict(message: &str) -> Response {
+    (StatusCode::CONFLICT, Json(ApiResponse::<()>::error(message))).into_response()
+}
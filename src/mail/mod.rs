@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::AppConfig;
+
+pub mod smtp;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MailError {
+    #[error("invalid message: {0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Backend(String),
+}
+
+/// Plain text message to a single recipient
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Succeeds once the transport accepted the message for delivery
+    async fn send(&self, email: Email) -> Result<(), MailError>;
+}
+
+/// Transport selected by `MAIL_BACKEND`, `None` when mail is turned off
+pub fn from_config(config: &AppConfig) -> Result<Option<Arc<dyn Mailer>>, Box<dyn std::error::Error>> {
+    match config.mail_backend.as_str() {
+        "none" => Ok(None),
+        "smtp" => {
+            let host = config
+                .smtp_host
+                .as_deref()
+                .ok_or("SMTP_HOST is required when MAIL_BACKEND=smtp")?;
+            let from = config
+                .mail_from
+                .as_deref()
+                .ok_or("MAIL_FROM is required when MAIL_BACKEND=smtp")?;
+            let credentials = config.smtp_username.clone().zip(config.smtp_password.clone());
+
+            Ok(Some(Arc::new(smtp::SmtpMailer::new(
+                host,
+                config.smtp_port,
+                config.smtp_starttls,
+                credentials,
+                from,
+            )?)))
+        }
+        other => Err(format!("Unknown MAIL_BACKEND: {}", other).into()),
+    }
+}
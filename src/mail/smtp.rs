@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::{authentication::Credentials, Error},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use super::{Email, MailError, Mailer};
+
+/// Sends mail through an SMTP relay
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Without `starttls` the connection is not encrypted, which only
+    /// suits a relay on the local network such as a development mail catcher
+    pub fn new(
+        host: &str,
+        port: u16,
+        starttls: bool,
+        credentials: Option<(String, String)>,
+        from: &str,
+    ) -> Result<Self, MailError> {
+        let mut builder = if starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| MailError::Backend(e.to_string()))?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        }
+        .port(port);
+
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let from = from
+            .parse()
+            .map_err(|e| MailError::Invalid(format!("sender address: {}", e)))?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+/// The server's reply may quote the recipient, so only its code is kept
+fn backend_error(e: Error) -> MailError {
+    match e.status() {
+        Some(code) => MailError::Backend(format!("SMTP server replied {}", code)),
+        None => MailError::Backend(e.to_string()),
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        let to: Mailbox = email
+            .to
+            .parse()
+            .map_err(|_| MailError::Invalid("recipient address".to_string()))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| MailError::Invalid(e.to_string()))?;
+
+        self.transport.send(message).await.map_err(backend_error)?;
+        Ok(())
+    }
+}
@@ -1,45 +1,157 @@
-use axum::Router;
-use std::net::SocketAddr;
+use axum::{middleware::from_fn_with_state, Router};
 use tower_http::cors::CorsLayer;
-use tracing_subscriber;
-use utoipa::OpenApi;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
 use utoipa_swagger_ui::SwaggerUi;
 
 mod api;
+mod auth;
 mod config;
 mod database;
 mod handlers;
+mod mail;
+mod middleware;
 mod models;
 mod routes;
 mod services;
+mod storage;
 
 use config::AppState;
 use api::implement_apis::api_router;
-use handlers::{health, post, user};
-use routes::health::health_router;
+use handlers::{
+    attachment, batch, bookmark, bulk, collection, comment, event, feed, follow, health, moderation, notification,
+    post, reaction, revision, tag, trash, user, workflow,
+};
+use routes::{feeds::feed_router, health::health_router};
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         health::health_check,
+        handlers::auth::login,
         user::create_user,
         user::get_users,
         user::get_user_by_id,
         user::update_user,
+        user::patch_user,
         user::delete_user,
+        user::restore_user,
         post::create_post,
         post::get_posts,
+        post::search_posts,
+        bulk::bulk_posts,
+        post::get_user_posts,
+        post::get_my_posts,
         post::get_post_by_id,
+        post::get_post_by_slug,
         post::update_post,
+        post::patch_post,
         post::delete_post,
+        post::restore_post,
+        workflow::submit_post,
+        workflow::approve_post,
+        workflow::reject_post,
+        workflow::publish_post,
+        workflow::unpublish_post,
+        workflow::archive_post,
+        workflow::get_post_history,
+        workflow::get_scheduled_posts,
+        workflow::cancel_scheduled_post,
+        comment::get_post_comments,
+        comment::create_comment,
+        comment::update_comment,
+        comment::delete_comment,
+        comment::hide_comment,
+        comment::unhide_comment,
+        reaction::set_reaction,
+        reaction::remove_reaction,
+        bookmark::add_bookmark,
+        bookmark::remove_bookmark,
+        bookmark::get_my_bookmarks,
+        follow::follow_user,
+        follow::unfollow_user,
+        follow::get_followers,
+        follow::get_following,
+        follow::get_feed,
+        revision::get_post_revisions,
+        revision::get_post_revision,
+        revision::diff_post_revisions,
+        revision::restore_post_revision,
+        attachment::upload_attachments,
+        attachment::get_attachment,
+        attachment::get_attachment_thumbnail,
+        attachment::delete_attachment,
+        moderation::report_post,
+        moderation::report_comment,
+        moderation::get_moderation_queue,
+        moderation::moderate_post,
+        moderation::moderate_comment,
+        moderation::get_moderation_log,
+        moderation::get_my_warnings,
+        notification::get_notifications,
+        notification::mark_notification_read,
+        notification::mark_all_notifications_read,
+        notification::get_notification_preferences,
+        notification::update_notification_preferences,
+        collection::create_collection,
+        collection::get_collections,
+        collection::get_collection,
+        collection::patch_collection,
+        collection::delete_collection,
+        collection::add_collection_post,
+        collection::remove_collection_post,
+        collection::reorder_collection,
+        collection::mark_post_read,
+        collection::mark_post_unread,
+        tag::get_tags,
+        tag::rename_tag,
+        tag::merge_tag,
+        trash::get_trash,
+        feed::get_posts_feed,
+        feed::get_user_posts_feed,
+        feed::get_tag_posts_feed,
+        event::stream_events,
+        batch::execute_batch,
     ),
     components(
         schemas(
             models::requests::CreateUserRequest,
             models::requests::UpdateUserRequest,
+            models::requests::PatchUserRequest,
             models::requests::CreatePostRequest,
             models::requests::UpdatePostRequest,
+            models::requests::PatchPostRequest,
             models::requests::PaginationParams,
+            models::requests::PostSearchParams,
+            models::requests::PostFilterParams,
+            models::requests::PostFeedParams,
+            models::requests::RejectPostRequest,
+            models::requests::BulkPostRequest,
+            models::requests::BulkPostFilter,
+            models::requests::ReportRequest,
+            models::requests::ModerationActionRequest,
+            models::requests::ModerationLogParams,
+            models::requests::NotificationListParams,
+            models::requests::EventStreamParams,
+            models::requests::NotificationPreferenceRequest,
+            models::requests::UpdateNotificationPreferencesRequest,
+            models::requests::CreateCollectionRequest,
+            models::requests::PatchCollectionRequest,
+            models::requests::AddCollectionPostRequest,
+            models::requests::ReorderCollectionRequest,
+            models::requests::RevisionDiffParams,
+            models::requests::CreateCommentRequest,
+            models::requests::UpdateCommentRequest,
+            models::requests::CommentListParams,
+            models::requests::ReactionRequest,
+            models::requests::RenameTagRequest,
+            models::requests::MergeTagRequest,
+            models::requests::LoginRequest,
+            models::requests::DeleteParams,
+            models::requests::BatchRequest,
+            models::requests::BatchOperation,
             models::responses::UserResponse,
             models::responses::PostResponse,
             models::responses::PostWithUserResponse,
@@ -51,12 +163,98 @@ use routes::health::health_router;
             models::responses::PostsApiResponse,
             models::responses::StringApiResponse,
             models::responses::HealthApiResponse,
+            models::responses::PostSearchResultResponse,
+            models::responses::PaginatedPostSearchResponse,
+            models::responses::PostSearchApiResponse,
+            models::responses::PostStatusChangeResponse,
+            models::responses::PostHistoryApiResponse,
+            models::responses::PostRevisionSummaryResponse,
+            models::responses::PostRevisionResponse,
+            models::responses::DiffLineResponse,
+            models::responses::PostRevisionDiffResponse,
+            models::responses::PostRevisionsApiResponse,
+            models::responses::PostRevisionApiResponse,
+            models::responses::PostRevisionDiffApiResponse,
+            models::responses::CommentResponse,
+            models::responses::PaginatedCommentResponse,
+            models::responses::CommentApiResponse,
+            models::responses::CommentsApiResponse,
+            models::responses::ReactionResponse,
+            models::responses::ReactionApiResponse,
+            models::responses::BookmarkResponse,
+            models::responses::BookmarkApiResponse,
+            models::responses::FollowResponse,
+            models::responses::FollowApiResponse,
+            models::responses::FollowedUserResponse,
+            models::responses::PaginatedFollowResponse,
+            models::responses::FollowsApiResponse,
+            models::responses::PostFeedResponse,
+            models::responses::PostFeedApiResponse,
+            models::responses::AttachmentResponse,
+            models::responses::AttachmentsApiResponse,
+            models::responses::BulkPostResponse,
+            models::responses::BulkPostFailure,
+            models::responses::BulkPostApiResponse,
+            models::responses::ReportResponse,
+            models::responses::ReportApiResponse,
+            models::responses::ModerationQueueItemResponse,
+            models::responses::PaginatedModerationQueueResponse,
+            models::responses::ModerationQueueApiResponse,
+            models::responses::ModerationActionResponse,
+            models::responses::ModerationActionApiResponse,
+            models::responses::PaginatedModerationLogResponse,
+            models::responses::ModerationLogApiResponse,
+            models::responses::NotificationResponse,
+            models::responses::NotificationListResponse,
+            models::responses::NotificationsApiResponse,
+            models::responses::NotificationReadResponse,
+            models::responses::NotificationReadApiResponse,
+            models::responses::NotificationPreferenceResponse,
+            models::responses::NotificationPreferencesApiResponse,
+            models::responses::CollectionResponse,
+            models::responses::CollectionDetailResponse,
+            models::responses::CollectionPostResponse,
+            models::responses::PaginatedCollectionResponse,
+            models::responses::CollectionApiResponse,
+            models::responses::CollectionsApiResponse,
+            models::responses::PostSeriesResponse,
+            models::responses::SeriesPostResponse,
+            models::responses::PostReadResponse,
+            models::responses::PostReadApiResponse,
+            models::responses::TagResponse,
+            models::responses::TagApiResponse,
+            models::responses::TagsApiResponse,
+            models::responses::LoginResponse,
+            models::responses::LoginApiResponse,
+            models::responses::TrashItemResponse,
+            models::responses::PaginatedTrashResponse,
+            models::responses::TrashApiResponse,
+            models::responses::BatchApiResponse,
+            models::responses::BatchResponse,
+            models::responses::BatchOperationResult,
         )
     ),
+    modifiers(&SecurityAddon),
     tags(
+        (name = "Auth", description = "Authentication endpoints"),
         (name = "Users", description = "User management endpoints"),
         (name = "Posts", description = "Post management endpoints"),
         (name = "Productions", description = "Production management endpoints"),
+        (name = "Workflow", description = "Editorial review, publishing and scheduling of posts"),
+        (name = "Comments", description = "Threaded comments on posts and their moderation"),
+        (name = "Reactions", description = "Reactions to posts"),
+        (name = "Bookmarks", description = "Posts saved by users"),
+        (name = "Follows", description = "Followed authors and the feed of their posts"),
+        (name = "Collections", description = "Ordered series of posts and reading progress through them"),
+        (name = "Revisions", description = "Revision history of posts"),
+        (name = "Attachments", description = "Files attached to posts"),
+        (name = "Moderation", description = "Reports of posts and comments and their moderation"),
+        (name = "Notifications", description = "Comments, mentions and reviews users are told about"),
+        (name = "Tags", description = "Tags of posts"),
+        (name = "Feeds", description = "Atom, RSS and JSON feeds of published posts"),
+        (name = "Events", description = "Live stream of post and comment changes"),
+        (name = "Trash", description = "Soft-deleted users and posts"),
+        (name = "Batch", description = "Batch execution endpoints"),
         (name = "Health", description = "Health check endpoints")
     ),
     info(
@@ -67,6 +265,24 @@ use routes::health::health_router;
 )]
 struct ApiDoc;
 
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer_auth",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
@@ -78,18 +294,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize database connection
     let db_pool = database::connection::create_pool(&config.database_url).await?;
 
+    // Apply pending migrations when asked to
+    if config.run_migrations {
+        sqlx::migrate!("./migrations").run(&db_pool).await?;
+    }
+
+    // Make sure there is an admin to manage the other accounts
+    if let (Some(email), Some(password)) = (&config.admin_email, &config.admin_password) {
+        services::user_service::bootstrap_admin(&db_pool, email, password).await?;
+    }
+
+    // Storage backend for attachments
+    let storage = storage::from_config(&config).await?;
+
+    // Mail transport for notification digests
+    let mailer = mail::from_config(&config)?;
+
     // Create application state
+    let (events, _) = tokio::sync::broadcast::channel(services::event_service::EVENT_BUS_CAPACITY);
     let app_state = AppState {
         db: db_pool,
         config,
+        storage,
+        events,
     };
 
+    let addr = app_state.config.server_address();
+
+    // Background maintenance tasks
+    tokio::spawn(middleware::idempotency::purge_expired_keys(
+        app_state.db.clone(),
+    ));
+    tokio::spawn(services::trash_service::purge_expired_trash(
+        app_state.db.clone(),
+        app_state.config.trash_retention_days,
+    ));
+    tokio::spawn(services::scheduler_service::publish_scheduled_posts(
+        app_state.db.clone(),
+        app_state.config.publish_scheduler_interval_seconds,
+    ));
+    tokio::spawn(services::render_service::render_missing_html(
+        app_state.db.clone(),
+    ));
+    tokio::spawn(services::attachment_service::purge_deleted_files(
+        app_state.db.clone(),
+        app_state.storage.clone(),
+    ));
+    match mailer {
+        Some(mailer) => {
+            tokio::spawn(services::notification_service::send_email_digests(
+                app_state.db.clone(),
+                mailer,
+                app_state.config.notification_digest_interval_seconds,
+            ));
+        }
+        None => tracing::info!("MAIL_BACKEND is none, email digests are not sent"),
+    }
+    tokio::spawn(services::event_service::listen_for_events(
+        app_state.db.clone(),
+        app_state.events.clone(),
+    ));
+    tokio::spawn(services::event_service::prune_event_log(
+        app_state.db.clone(),
+        app_state.config.event_log_size,
+    ));
+
     // Build our application with centralized routes
     let app = Router::new()
         // API routes với prefix /api
-        .nest("/api", api_router())
+        .nest(
+            "/api",
+            api_router().layer(from_fn_with_state(
+                app_state.clone(),
+                middleware::idempotency::idempotency,
+            )),
+        )
         // Health check
         .nest("/health", health_router())
+        // Atom, RSS and JSON feeds
+        .nest("/feeds", feed_router())
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         // Add CORS layer
@@ -97,14 +380,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Add shared state
         .with_state(app_state);
 
-    // Parse the server address
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    
     tracing::info!("Server running on http://{}", addr);
     tracing::info!("Swagger UI available at http://{}/swagger-ui", addr);
 
     // Start the server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
     axum::serve(listener, app).await?;
 
     Ok(())
@@ -0,0 +1,428 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use sqlx::PgPool;
+
+use crate::{config::AppState, models::responses::ApiResponse};
+
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// Same limit as axum's default JSON body limit. Multipart uploads, which
+/// may be larger, are not covered by idempotency keys.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Routes below this prefix are never covered: their responses carry
+/// credentials, which must not be written to the database
+const EXCLUDED_PREFIX: &str = "/auth";
+
+/// Makes POST requests carrying an `Idempotency-Key` header safe to retry.
+///
+/// The first request with a key claims it and stores the response; retries
+/// with the same body replay that response, a different body gets 422 and a
+/// retry racing the original request gets 409. Keys are scoped to the
+/// caller's `Authorization` header and the request path, so clients never
+/// share responses. Authentication routes and multipart uploads are not
+/// covered.
+pub async fn idempotency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if !is_covered(&request) {
+        return Ok(next.run(request).await);
+    }
+
+    let Some(key) = request
+        .headers()
+        .get(IDEMPOTENCY_KEY)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Ok(error_response(
+            StatusCode::BAD_REQUEST,
+            "Idempotency-Key must be between 1 and 255 characters",
+        ));
+    }
+
+    let scope = Scope {
+        key,
+        principal: request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string(),
+        path: request.uri().path().to_string(),
+    };
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+
+    let claimed = claim(&state.db, &scope, &body, state.config.idempotency_ttl_seconds)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to claim idempotency key: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if !claimed {
+        let stored = stored_response(&state.db, &scope, &body)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        return replay(stored);
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let status = response.status();
+
+    // Server errors are not remembered so the client can retry them
+    if status.is_server_error() {
+        release(&state.db, &scope).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to buffer response for idempotency key: {}", e);
+            release(&state.db, &scope).await;
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+
+    if let Err(e) = store(&state.db, &scope, status, content_type, &body).await {
+        tracing::error!("Failed to store idempotent response: {}", e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// What a key is stored under: the key itself, the `Authorization` header
+/// of the caller (hashed in the database) and the path it was used on
+struct Scope {
+    key: String,
+    principal: String,
+    path: String,
+}
+
+/// Response stored for a key, compared against the retried request
+struct StoredResponse {
+    same_request: bool,
+    status_code: Option<i32>,
+    content_type: Option<String>,
+    response_body: Option<Vec<u8>>,
+}
+
+/// Claims the key, taking over an expired entry if there is one. Returns
+/// `false` when the key is already held.
+async fn claim(
+    db: &PgPool,
+    scope: &Scope,
+    body: &[u8],
+    ttl_seconds: u64,
+) -> Result<bool, sqlx::Error> {
+    let claimed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO idempotency_keys (key, principal, request_path, request_hash, expires_at)
+        VALUES ($1, md5($2), $3, md5($4::bytea),
+                NOW() + make_interval(secs => $5))
+        ON CONFLICT (key, principal, request_path) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash,
+            status_code = NULL,
+            content_type = NULL,
+            response_body = NULL,
+            created_at = NOW(),
+            expires_at = EXCLUDED.expires_at
+        WHERE idempotency_keys.expires_at < NOW()
+        RETURNING key
+        "#,
+        scope.key,
+        scope.principal,
+        scope.path,
+        body,
+        ttl_seconds as f64
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(claimed.is_some())
+}
+
+async fn stored_response(
+    db: &PgPool,
+    scope: &Scope,
+    body: &[u8],
+) -> Result<Option<StoredResponse>, sqlx::Error> {
+    sqlx::query_as!(
+        StoredResponse,
+        r#"
+        SELECT request_hash = md5($4::bytea) AS "same_request!", status_code, content_type, response_body
+        FROM idempotency_keys
+        WHERE key = $1 AND principal = md5($2)
+          AND request_path = $3
+        "#,
+        scope.key,
+        scope.principal,
+        scope.path,
+        body
+    )
+    .fetch_optional(db)
+    .await
+}
+
+async fn store(
+    db: &PgPool,
+    scope: &Scope,
+    status: StatusCode,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE idempotency_keys
+         SET status_code = $4, content_type = $5, response_body = $6
+         WHERE key = $1 AND principal = md5($2)
+           AND request_path = $3",
+        scope.key,
+        scope.principal,
+        scope.path,
+        status.as_u16() as i32,
+        content_type,
+        body
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+async fn release(db: &PgPool, scope: &Scope) {
+    if let Err(e) = sqlx::query!(
+        "DELETE FROM idempotency_keys
+         WHERE key = $1 AND principal = md5($2)
+           AND request_path = $3",
+        scope.key,
+        scope.principal,
+        scope.path
+    )
+    .execute(db)
+    .await
+    {
+        tracing::error!("Failed to release idempotency key: {}", e);
+    }
+}
+
+/// Answers a retry of a request whose key is already held
+fn replay(stored: Option<StoredResponse>) -> Result<Response, StatusCode> {
+    let Some(stored) = stored else {
+        return Ok(in_progress());
+    };
+
+    if !stored.same_request {
+        return Ok(error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key was already used with a different request body",
+        ));
+    }
+
+    let (Some(status_code), Some(response_body)) = (stored.status_code, stored.response_body)
+    else {
+        return Ok(in_progress());
+    };
+
+    let mut response = Response::new(Body::from(response_body));
+    *response.status_mut() =
+        StatusCode::from_u16(status_code as u16).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let headers = response.headers_mut();
+    if let Some(content_type) = stored
+        .content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    Ok(response)
+}
+
+/// Whether the request may use an idempotency key. The path is relative
+/// to `/api`, where the middleware is mounted.
+fn is_covered(request: &Request) -> bool {
+    let path = request.uri().path();
+    let excluded = path
+        .strip_prefix(EXCLUDED_PREFIX)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+    request.method() == Method::POST && !excluded && !is_multipart(request)
+}
+
+fn is_multipart(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/"))
+}
+
+fn in_progress() -> Response {
+    error_response(
+        StatusCode::CONFLICT,
+        "A request with this Idempotency-Key is still being processed",
+    )
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
+/// Periodically deletes idempotency keys whose replay window has passed
+pub async fn purge_expired_keys(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+
+    loop {
+        interval.tick().await;
+
+        match sqlx::query!("DELETE FROM idempotency_keys WHERE expires_at < NOW()")
+            .execute(&db)
+            .await
+        {
+            Ok(result) if result.rows_affected() > 0 => {
+                tracing::info!("Purged {} expired idempotency keys", result.rows_affected());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to purge idempotency keys: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, path: &str, content_type: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn scope(principal: &str, path: &str) -> Scope {
+        Scope {
+            key: "retry-1".to_string(),
+            principal: principal.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    fn stored(status_code: Option<i32>, same_request: bool) -> StoredResponse {
+        StoredResponse {
+            same_request,
+            status_code,
+            content_type: Some("application/json".to_string()),
+            response_body: status_code.map(|_| br#"{"success":true}"#.to_vec()),
+        }
+    }
+
+    async fn body_of(response: Response) -> Vec<u8> {
+        to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+    }
+
+    #[test]
+    fn authentication_routes_are_not_covered() {
+        for path in ["/auth/login", "/auth", "/auth/refresh"] {
+            assert!(!is_covered(&request(Method::POST, path, "application/json")), "{path}");
+        }
+        assert!(is_covered(&request(Method::POST, "/authors", "application/json")));
+        assert!(is_covered(&request(Method::POST, "/posts", "application/json")));
+    }
+
+    #[test]
+    fn only_json_posts_are_covered() {
+        assert!(!is_covered(&request(Method::GET, "/posts", "application/json")));
+        assert!(!is_covered(&request(Method::PUT, "/posts/1", "application/json")));
+        assert!(!is_covered(&request(
+            Method::POST,
+            "/posts/1/attachments",
+            "multipart/form-data; boundary=x"
+        )));
+    }
+
+    #[tokio::test]
+    async fn stored_responses_are_replayed() {
+        let response = replay(Some(stored(Some(201), true))).unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[IDEMPOTENT_REPLAYED], "true");
+        assert_eq!(body_of(response).await, br#"{"success":true}"#);
+    }
+
+    #[test]
+    fn keys_reused_with_another_body_are_rejected() {
+        let response = replay(Some(stored(Some(201), false))).unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn unfinished_requests_are_in_progress() {
+        assert_eq!(replay(Some(stored(None, true))).unwrap().status(), StatusCode::CONFLICT);
+        // Released by the original request in the meantime
+        assert_eq!(replay(None).unwrap().status(), StatusCode::CONFLICT);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn keys_are_scoped_to_principal_and_path(db: PgPool) {
+        let original = scope("Bearer a", "/posts");
+        assert!(claim(&db, &original, b"{}", 60).await.unwrap());
+        store(&db, &original, StatusCode::CREATED, Some("application/json"), b"created")
+            .await
+            .unwrap();
+
+        // A retry replays the stored response
+        assert!(!claim(&db, &original, b"{}", 60).await.unwrap());
+        let response = replay(stored_response(&db, &original, b"{}").await.unwrap()).unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(body_of(response).await, b"created");
+
+        // The same key on another path or from another caller is a new request
+        let others = [scope("Bearer a", "/tags"), scope("Bearer b", "/posts"), scope("", "/posts")];
+        for other in others {
+            assert!(stored_response(&db, &other, b"{}").await.unwrap().is_none());
+            assert!(claim(&db, &other, b"{}", 60).await.unwrap());
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn released_and_expired_keys_can_be_claimed_again(db: PgPool) {
+        let released = scope("Bearer a", "/posts");
+        assert!(claim(&db, &released, b"{}", 60).await.unwrap());
+        release(&db, &released).await;
+        assert!(claim(&db, &released, b"{}", 60).await.unwrap());
+
+        let expired = scope("Bearer a", "/tags");
+        assert!(claim(&db, &expired, b"{}", 0).await.unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(claim(&db, &expired, b"{}", 60).await.unwrap());
+    }
+}
@@ -1,5 +1,2 @@
 pub mod requests;
-pub mod responses;
-
-pub use requests::*;
-pub use responses::*;
\ No newline at end of file
+pub mod responses;
\ No newline at end of file
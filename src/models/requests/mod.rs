@@ -1,6 +1,19 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
 use utoipa::{ToSchema, IntoParams};
-use validator::Validate;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::services::{
+    collection_service::MAX_COLLECTION_POSTS,
+    moderation_service::{ModerationAction, ReportReason},
+    notification_service::NotificationKind,
+    post_service::{BulkPostAction, MAX_BULK_POSTS},
+    reaction_service::ReactionKind,
+    render_service::ContentFormat,
+    slug_service,
+    tag_service::{self, MAX_TAGS_PER_POST, MAX_TAG_LENGTH},
+};
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserRequest {
@@ -17,20 +30,60 @@ pub struct CreateUserRequest {
     pub full_name: Option<String>,
 }
 
+/// Full replacement of a user's editable fields (`PUT`)
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateUserRequest {
     #[validate(email)]
-    pub email: Option<String>,
+    pub email: String,
     
     #[validate(length(min = 3, max = 50))]
-    pub username: Option<String>,
+    pub username: String,
     
     #[validate(length(min = 1, max = 100))]
     pub full_name: Option<String>,
     
-    pub is_active: Option<bool>,
+    pub is_active: bool,
+}
+
+/// JSON Merge Patch (RFC 7396) for a user: absent fields are left unchanged,
+/// `null` clears `full_name`
+#[derive(Debug, Default, Deserialize, Validate, ToSchema)]
+pub struct PatchUserRequest {
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(email)]
+    pub email: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(length(min = 3, max = 50))]
+    pub username: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>, nullable)]
+    #[validate(length(min = 1, max = 100))]
+    pub full_name: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<bool>)]
+    pub is_active: Option<Option<bool>>,
+}
+
+impl PatchUserRequest {
+    /// Fields set to `null` that cannot be cleared
+    pub fn nulled_required_fields(&self) -> Vec<&'static str> {
+        [
+            ("email", matches!(self.email, Some(None))),
+            ("username", matches!(self.username, Some(None))),
+            ("is_active", matches!(self.is_active, Some(None))),
+        ]
+        .into_iter()
+        .filter_map(|(field, nulled)| nulled.then_some(field))
+        .collect()
+    }
 }
 
+/// New posts start as drafts and go through the editorial workflow
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 200))]
@@ -38,23 +91,705 @@ pub struct CreatePostRequest {
     
     #[validate(length(min = 1))]
     pub content: String,
-    
-    pub is_published: Option<bool>,
+
+    /// Custom permalink, generated from the title when absent
+    #[validate(custom = "valid_slug")]
+    pub slug: Option<String>,
+
+    /// `plain` (default) or `markdown`
+    #[validate(custom = "known_content_format")]
+    pub content_format: Option<String>,
+
+    /// Tag names, created on first use
+    #[validate(length(max = "MAX_TAGS_PER_POST"), custom = "tag_names")]
+    pub tags: Option<Vec<String>>,
+
+    /// Publish automatically at this time once the post is approved
+    #[schema(value_type = Option<String>, format = "date-time")]
+    #[validate(custom = "in_future")]
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
+/// Full replacement of a post's editable fields (`PUT`)
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdatePostRequest {
     #[validate(length(min = 1, max = 200))]
-    pub title: Option<String>,
+    pub title: String,
     
     #[validate(length(min = 1))]
-    pub content: Option<String>,
-    
-    pub is_published: Option<bool>,
+    pub content: String,
+
+    /// New permalink; when absent the slug follows a changed title. The
+    /// previous slug keeps redirecting to the post
+    #[validate(custom = "valid_slug")]
+    pub slug: Option<String>,
+
+    /// `plain` or `markdown`, absent keeps the current format
+    #[validate(custom = "known_content_format")]
+    pub content_format: Option<String>,
+
+    /// Replaces the tags of the post, absent keeps them
+    #[validate(length(max = "MAX_TAGS_PER_POST"), custom = "tag_names")]
+    pub tags: Option<Vec<String>>,
+
+    /// Publish automatically at this time once the post is approved,
+    /// `null` cancels the schedule
+    #[schema(value_type = Option<String>, format = "date-time")]
+    #[validate(custom = "in_future")]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+/// JSON Merge Patch (RFC 7396) for a post: absent fields are left unchanged
+#[derive(Debug, Default, Deserialize, Validate, ToSchema)]
+pub struct PatchPostRequest {
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<Option<String>>,
+
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(length(min = 1))]
+    pub content: Option<Option<String>>,
+
+    /// New permalink; when absent the slug follows a changed title. The
+    /// previous slug keeps redirecting to the post
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(custom = "valid_slug")]
+    pub slug: Option<Option<String>>,
+
+    /// `plain` or `markdown`
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(custom = "known_content_format")]
+    pub content_format: Option<Option<String>>,
+
+    /// Replaces the tags of the post, `null` removes them all
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<Vec<String>>, nullable)]
+    #[validate(length(max = "MAX_TAGS_PER_POST"), custom = "tag_names")]
+    pub tags: Option<Option<Vec<String>>>,
+
+    /// `null` cancels the schedule
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>, format = "date-time", nullable)]
+    #[validate(custom = "in_future")]
+    pub publish_at: Option<Option<DateTime<Utc>>>,
+}
+
+impl PatchPostRequest {
+    /// Fields set to `null` that cannot be cleared
+    pub fn nulled_required_fields(&self) -> Vec<&'static str> {
+        [
+            ("title", matches!(self.title, Some(None))),
+            ("content", matches!(self.content, Some(None))),
+            ("slug", matches!(self.slug, Some(None))),
+            ("content_format", matches!(self.content_format, Some(None))),
+        ]
+        .into_iter()
+        .filter_map(|(field, nulled)| nulled.then_some(field))
+        .collect()
+    }
 }
 
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct LoginRequest {
+    #[validate(email)]
+    pub email: String,
+
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct DeleteParams {
+    /// Permanently delete instead of moving to the trash (admins only)
+    pub force: Option<bool>,
+}
+
+/// Items per page when the request asks for none
+const DEFAULT_PAGE_SIZE: u64 = 10;
+/// Most items a page may hold
+const MAX_PAGE_SIZE: u64 = 100;
+
 #[derive(Debug, Deserialize, Validate, ToSchema, IntoParams)]
 pub struct PaginationParams {
+    /// Page number, starting at 1
     pub page: Option<u64>,
+    /// Items per page, 10 by default and at most 100
     pub limit: Option<u64>,
-}
\ No newline at end of file
+}
+
+impl PaginationParams {
+    /// Requested page, at least 1
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Requested page size, between 1 and `MAX_PAGE_SIZE`
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Items before the requested page, small enough to bind as a
+    /// Postgres `bigint`
+    pub fn offset(&self) -> u64 {
+        (self.page() - 1)
+            .saturating_mul(self.limit())
+            .min(i64::MAX as u64)
+    }
+
+    /// Number of pages `total` items fill
+    pub fn total_pages(&self, total: u64) -> u64 {
+        total.div_ceil(self.limit())
+    }
+}
+
+/// Page of the personal feed
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct PostFeedParams {
+    /// Posts per page, 10 by default and at most 100
+    pub limit: Option<u64>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Filters of the post listings
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct PostFilterParams {
+    /// `draft`, `in_review`, `approved`, `published` or `archived`; unpublished
+    /// posts of other users are only listed for admins
+    pub status: Option<String>,
+    /// Only posts with this tag
+    pub tag: Option<String>,
+}
+
+/// Revisions to compare
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct RevisionDiffParams {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RejectPostRequest {
+    /// Feedback for the author
+    #[validate(length(min = 1, max = 2000))]
+    pub comment: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub content: String,
+    /// Comment this one replies to
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateCommentRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub content: String,
+}
+
+/// Shape of the comment listing
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct CommentListParams {
+    /// `tree` (default) pages through top-level comments and nests their
+    /// replies, `flat` pages through all comments oldest first
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReactionRequest {
+    /// `like`, `love`, `insightful`, `funny` or `celebrate`
+    #[validate(custom = "known_reaction")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct BulkPostRequest {
+    /// `publish`, `unpublish`, `retag` or `delete`
+    #[validate(custom = "known_bulk_action")]
+    pub action: String,
+    /// Posts to act on; give either `ids` or `filter`
+    #[validate(length(min = 1, max = "MAX_BULK_POSTS"))]
+    pub ids: Option<Vec<Uuid>>,
+    /// Act on every post matching the filter that the caller may change
+    pub filter: Option<BulkPostFilter>,
+    /// `retag` only: tags added to every post
+    #[validate(length(max = "MAX_TAGS_PER_POST"), custom = "tag_names")]
+    pub add_tags: Option<Vec<String>>,
+    /// `retag` only: tags removed from every post
+    #[validate(length(max = "MAX_TAGS_PER_POST"), custom = "tag_names")]
+    pub remove_tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct BulkPostFilter {
+    /// `draft`, `in_review`, `approved`, `published` or `archived`
+    pub status: Option<String>,
+    /// Only posts with this tag
+    pub tag: Option<String>,
+    /// Only posts of this user
+    pub author_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReportRequest {
+    /// `spam` or `inappropriate`
+    #[validate(custom = "known_report_reason")]
+    pub reason: String,
+    /// Anything the moderators should know
+    #[validate(length(max = 1000))]
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ModerationActionRequest {
+    /// `dismiss`, `hide`, `delete` or `warn`
+    #[validate(custom = "known_moderation_action")]
+    pub action: String,
+    /// Kept in the audit trail; the message for the author when warning
+    #[validate(length(min = 1, max = 2000))]
+    pub note: Option<String>,
+}
+
+/// Filters of the moderation audit trail
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct ModerationLogParams {
+    /// Only entries about this post or comment
+    pub target_id: Option<Uuid>,
+}
+
+/// Topics of an event stream and where to resume it
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct EventStreamParams {
+    /// Comma-separated `posts`, `comments` or `post:<id>`; `posts,comments`
+    /// by default
+    pub topics: Option<String>,
+    /// Id of the last event received, for clients that cannot send the
+    /// `Last-Event-ID` header
+    pub last_event_id: Option<i64>,
+}
+
+/// Filters of the notification list
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct NotificationListParams {
+    /// Only notifications not read yet
+    pub unread_only: Option<bool>,
+    /// Only notifications of this kind: `comment`, `mention` or `review`
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct NotificationPreferenceRequest {
+    /// `comment`, `mention` or `review`
+    #[validate(custom = "known_notification_kind")]
+    pub kind: String,
+    /// Show notifications of this kind in the app
+    pub in_app: bool,
+    /// Send notifications of this kind in the email digest
+    pub email_digest: bool,
+}
+
+/// Delivery preferences to change; kinds left out keep their settings
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub preferences: Vec<NotificationPreferenceRequest>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateCollectionRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+    #[validate(length(max = 5000))]
+    pub description: Option<String>,
+    /// Posts of the collection, in order
+    #[validate(length(max = "MAX_COLLECTION_POSTS"), custom = "distinct_ids")]
+    pub post_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PatchCollectionRequest {
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<Option<String>>,
+
+    /// `null` removes the description
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>, nullable)]
+    #[validate(length(max = 5000))]
+    pub description: Option<Option<String>>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AddCollectionPostRequest {
+    pub post_id: Uuid,
+    /// 1-based place in the collection; the post is appended when left out
+    #[validate(range(min = 1))]
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReorderCollectionRequest {
+    /// Every post of the collection, in the new order
+    #[validate(length(min = 1, max = "MAX_COLLECTION_POSTS"), custom = "distinct_ids")]
+    pub post_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RenameTagRequest {
+    /// New name of the tag
+    #[validate(length(min = 1, max = "MAX_TAG_LENGTH"))]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MergeTagRequest {
+    /// Existing tag that takes over the posts of the merged one
+    #[validate(length(min = 1, max = "MAX_TAG_LENGTH"))]
+    pub into: String,
+}
+
+/// Query options shared by the post read endpoints
+#[derive(Debug, Default, Deserialize, ToSchema, IntoParams)]
+pub struct PostQueryParams {
+    /// Comma-separated related resources to embed (supported: `author`)
+    pub include: Option<String>,
+    /// Comma-separated list of post fields to return, e.g. `id,title`
+    pub fields: Option<String>,
+}
+
+impl PostQueryParams {
+    pub fn include_list(&self) -> Vec<&str> {
+        split_list(self.include.as_deref()).collect()
+    }
+
+    pub fn includes(&self, relation: &str) -> bool {
+        self.include_list().contains(&relation)
+    }
+
+    pub fn field_list(&self) -> Option<Vec<&str>> {
+        self.fields
+            .as_deref()
+            .map(|fields| split_list(Some(fields)).collect())
+    }
+}
+
+/// Full-text search over posts
+#[derive(Debug, Deserialize, Validate, ToSchema, IntoParams)]
+pub struct PostSearchParams {
+    /// Words to search for. `"quoted words"` match as a phrase, a trailing `*`
+    /// matches a prefix (`rus*`) and a leading `-` excludes a word
+    #[validate(length(min = 1, max = 200))]
+    pub q: String,
+    /// Only posts by this author
+    pub author: Option<Uuid>,
+    /// Only published (`true`) or unpublished (`false`) posts
+    pub published: Option<bool>,
+}
+
+impl PostSearchParams {
+    /// Builds a `to_tsquery` expression from `q`, keeping only word
+    /// characters so user input can never produce a tsquery syntax error.
+    /// Returns `None` when `q` contains no searchable words.
+    pub fn ts_query(&self) -> Option<String> {
+        let mut terms = Vec::new();
+
+        for (index, chunk) in self.q.split('"').enumerate() {
+            // Odd chunks sit between quotes
+            if index % 2 == 1 {
+                let words: Vec<&str> = search_words(chunk).collect();
+                if !words.is_empty() {
+                    terms.push(format!("({})", words.join(" <-> ")));
+                }
+                continue;
+            }
+
+            for token in chunk.split_whitespace() {
+                let (negated, token) = match token.strip_prefix('-') {
+                    Some(rest) => (true, rest),
+                    None => (false, token),
+                };
+                let prefix = token.ends_with('*');
+
+                let words: Vec<&str> = search_words(token).collect();
+                if words.is_empty() {
+                    continue;
+                }
+
+                let mut term = words.join(" <-> ");
+                if prefix {
+                    term.push_str(":*");
+                }
+                terms.push(if negated {
+                    format!("!({})", term)
+                } else {
+                    format!("({})", term)
+                });
+            }
+        }
+
+        // A query made only of exclusions would match nothing useful
+        if terms.iter().all(|term| term.starts_with('!')) {
+            return None;
+        }
+
+        Some(terms.join(" & "))
+    }
+}
+
+fn search_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+fn in_future(value: &DateTime<Utc>) -> Result<(), ValidationError> {
+    if *value > Utc::now() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("in_future"))
+    }
+}
+
+fn known_content_format(value: &str) -> Result<(), ValidationError> {
+    match ContentFormat::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("content_format")),
+    }
+}
+
+fn valid_slug(value: &str) -> Result<(), ValidationError> {
+    if slug_service::is_valid_slug(value) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("slug"))
+    }
+}
+
+fn known_reaction(value: &str) -> Result<(), ValidationError> {
+    match ReactionKind::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("reaction")),
+    }
+}
+
+fn known_bulk_action(value: &str) -> Result<(), ValidationError> {
+    match BulkPostAction::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("bulk_action")),
+    }
+}
+
+fn known_report_reason(value: &str) -> Result<(), ValidationError> {
+    match ReportReason::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("report_reason")),
+    }
+}
+
+fn known_moderation_action(value: &str) -> Result<(), ValidationError> {
+    match ModerationAction::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("moderation_action")),
+    }
+}
+
+fn known_notification_kind(value: &str) -> Result<(), ValidationError> {
+    match NotificationKind::parse(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("notification_kind")),
+    }
+}
+
+fn distinct_ids(ids: &[Uuid]) -> Result<(), ValidationError> {
+    let mut seen = std::collections::HashSet::new();
+    if ids.iter().all(|id| seen.insert(id)) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("distinct_ids"))
+    }
+}
+
+fn tag_names(names: &[String]) -> Result<(), ValidationError> {
+    let valid = names.iter().all(|name| {
+        let length = tag_service::normalize_tag(name).chars().count();
+        (1..=MAX_TAG_LENGTH).contains(&length)
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("tag_names"))
+    }
+}
+
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// A single API call inside a batch request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchOperation {
+    /// HTTP method, e.g. `POST`
+    pub method: String,
+    /// API path, e.g. `/api/posts` or `/api/users/1`
+    pub path: String,
+    /// JSON request body
+    #[schema(value_type = Option<Object>)]
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchRequest {
+    /// Run all operations in one transaction and roll back on the first failure
+    #[serde(default)]
+    pub atomic: bool,
+
+    pub operations: Vec<BatchOperation>,
+}
+
+/// Distinguishes an absent field (`None`) from an explicit `null` (`Some(None)`)
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(page: Option<u64>, limit: Option<u64>) -> PaginationParams {
+        PaginationParams { page, limit }
+    }
+
+    #[test]
+    fn pagination_defaults_to_the_first_page_of_ten() {
+        let params = pagination(None, None);
+        assert_eq!((params.page(), params.limit(), params.offset()), (1, 10, 0));
+    }
+
+    #[test]
+    fn page_zero_is_the_first_page() {
+        let params = pagination(Some(0), Some(20));
+        assert_eq!((params.page(), params.offset()), (1, 0));
+    }
+
+    #[test]
+    fn limit_is_kept_between_one_and_a_hundred() {
+        assert_eq!(pagination(None, Some(0)).limit(), 1);
+        assert_eq!(pagination(None, Some(1000)).limit(), 100);
+        assert_eq!(pagination(Some(3), Some(25)).offset(), 50);
+    }
+
+    #[test]
+    fn huge_pages_do_not_overflow() {
+        let params = pagination(Some(u64::MAX), Some(u64::MAX));
+        assert_eq!(params.offset(), i64::MAX as u64);
+    }
+
+    #[test]
+    fn total_pages_rounds_up() {
+        let params = pagination(None, Some(0));
+        assert_eq!(params.total_pages(3), 3);
+        let params = pagination(None, Some(10));
+        assert_eq!(params.total_pages(0), 0);
+        assert_eq!(params.total_pages(10), 1);
+        assert_eq!(params.total_pages(11), 2);
+    }
+
+    #[test]
+    fn unknown_content_formats_are_invalid() {
+        let formats = [
+            ("plain", true),
+            ("markdown", true),
+            ("html", false),
+            ("Markdown", false),
+            ("", false),
+        ];
+        for (format, valid) in formats {
+            let create = CreatePostRequest {
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                slug: None,
+                content_format: Some(format.to_string()),
+                tags: None,
+                publish_at: None,
+            };
+            assert_eq!(create.validate().is_ok(), valid, "{format}");
+
+            let patch = PatchPostRequest {
+                content_format: Some(Some(format.to_string())),
+                ..Default::default()
+            };
+            assert_eq!(patch.validate().is_ok(), valid, "{format}");
+        }
+    }
+
+    fn ts_query(q: &str) -> Option<String> {
+        PostSearchParams {
+            q: q.to_string(),
+            author: None,
+            published: None,
+        }
+        .ts_query()
+    }
+
+    #[test]
+    fn words_must_all_match() {
+        assert_eq!(ts_query("rust  axum").as_deref(), Some("(rust) & (axum)"));
+    }
+
+    #[test]
+    fn quoted_words_match_as_a_phrase() {
+        assert_eq!(
+            ts_query(r#"web "rust async" api"#).as_deref(),
+            Some("(web) & (rust <-> async) & (api)")
+        );
+        // An unclosed quote runs to the end
+        assert_eq!(ts_query(r#"web "rust async"#).as_deref(), Some("(web) & (rust <-> async)"));
+    }
+
+    #[test]
+    fn trailing_star_matches_a_prefix() {
+        assert_eq!(ts_query("rus*").as_deref(), Some("(rus:*)"));
+    }
+
+    #[test]
+    fn leading_minus_excludes_a_word() {
+        assert_eq!(ts_query("rust -java").as_deref(), Some("(rust) & !(java)"));
+        assert_eq!(ts_query("rust -jav*").as_deref(), Some("(rust) & !(jav:*)"));
+    }
+
+    #[test]
+    fn only_exclusions_is_no_query() {
+        assert_eq!(ts_query("-java -go"), None);
+    }
+
+    #[test]
+    fn no_words_is_no_query() {
+        assert_eq!(ts_query("!!! & | ()"), None);
+        assert_eq!(ts_query(r#""""#), None);
+    }
+
+    #[test]
+    fn tsquery_syntax_is_not_passed_through() {
+        assert_eq!(ts_query("a&b|!c:A").as_deref(), Some("(a <-> b <-> c <-> A)"));
+        assert_eq!(ts_query("x') OR ('1").as_deref(), Some("(x) & (OR) & (1)"));
+    }
+
+    #[test]
+    fn unicode_words_are_kept() {
+        assert_eq!(ts_query("Việt Nam").as_deref(), Some("(Việt) & (Nam)"));
+    }
+}
@@ -1,10 +1,21 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::database::models::{
+    Attachment, CollectionPost, CollectionSummary, Comment, ModerationLogEntry, Notification, Post,
+    PostSeries, QueuedReport, User,
+};
+use crate::services::render_service;
 
 // Custom DateTime wrapper for OpenAPI
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(transparent)]
+#[allow(dead_code)]
 pub struct ApiDateTime(#[schema(value_type = String, format = "date-time")] pub DateTime<Utc>);
 
 impl From<DateTime<Utc>> for ApiDateTime {
@@ -67,6 +78,188 @@ pub struct PostsApiResponse {
     pub data: Option<PaginatedPostResponse>,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostSearchApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedPostSearchResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostHistoryApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<Vec<PostStatusChangeResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<Vec<PostRevisionSummaryResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PostRevisionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionDiffApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PostRevisionDiffResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<CommentResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedCommentResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReactionApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<ReactionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BookmarkApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<BookmarkResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AttachmentsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<Vec<AttachmentResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FollowApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<FollowResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FollowsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedFollowResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostFeedApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PostFeedResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<NotificationListResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationReadApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<NotificationReadResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferencesApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<Vec<NotificationPreferenceResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CollectionApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<CollectionDetailResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CollectionsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedCollectionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostReadApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PostReadResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkPostApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<BulkPostResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReportApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<ReportResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationQueueApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedModerationQueueResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationActionApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<ModerationActionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationLogApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedModerationLogResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TagApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<TagResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TagsApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<Vec<TagResponse>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct StringApiResponse {
     pub success: bool,
@@ -81,6 +274,27 @@ pub struct HealthApiResponse {
     pub data: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LoginApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<LoginResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TrashApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<PaginatedTrashResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchApiResponse {
+    pub success: bool,
+    pub message: String,
+    pub data: Option<BatchResponse>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
@@ -109,12 +323,450 @@ pub struct PaginatedPostResponse {
     pub total_pages: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedPostSearchResponse {
+    pub data: Vec<PostSearchResultResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostStatusChangeResponse {
+    pub from_status: String,
+    pub to_status: String,
+    /// User who made the change, `null` if that user was deleted
+    pub actor_id: Option<Uuid>,
+    pub comment: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionSummaryResponse {
+    pub revision: i32,
+    pub title: String,
+    /// User who wrote the revision, `null` if unknown or deleted
+    pub author_id: Option<Uuid>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionResponse {
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    /// User who wrote the revision, `null` if unknown or deleted
+    pub author_id: Option<Uuid>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DiffLineResponse {
+    /// `equal`, `insert` or `delete`
+    pub op: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostRevisionDiffResponse {
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub from_title: String,
+    pub to_title: String,
+    /// Number of inserted content lines
+    pub additions: usize,
+    /// Number of deleted content lines
+    pub deletions: usize,
+    pub lines: Vec<DiffLineResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedCommentResponse {
+    pub data: Vec<CommentResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentResponse {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    /// `null` once the comment is deleted
+    pub user_id: Option<Uuid>,
+    /// `null` when the comment is deleted, or hidden by a moderator and the
+    /// caller is neither its author nor an admin
+    pub content: Option<String>,
+    pub is_hidden: bool,
+    pub is_deleted: bool,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(value_type = String, format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+    /// Direct replies, only returned in tree mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<Vec<CommentResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReactionResponse {
+    /// Number of reactions of each kind, e.g. `{"like": 3}`
+    #[schema(value_type = Object)]
+    pub reaction_counts: BTreeMap<String, i64>,
+    pub my_reaction: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BookmarkResponse {
+    pub is_bookmarked: bool,
+    pub bookmark_count: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AttachmentResponse {
+    pub id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    /// Images only
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Where the file is downloaded from
+    pub url: String,
+    /// Scaled-down JPEG of an image, `null` for other files
+    pub thumbnail_url: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FollowResponse {
+    pub is_following: bool,
+    pub follower_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedFollowResponse {
+    pub data: Vec<FollowedUserResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+/// User in a follower or following list
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FollowedUserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub full_name: Option<String>,
+    /// When the follow started
+    #[schema(value_type = String, format = "date-time")]
+    pub followed_at: DateTime<Utc>,
+}
+
+/// A page of the personal feed
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostFeedResponse {
+    /// Published posts of followed authors, newest first
+    pub data: Vec<PostResponse>,
+    /// Pass as `cursor` to get the next page, `null` on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    /// `comment`, `mention` or `review`
+    pub kind: String,
+    /// User whose action triggered the notification, `null` once deleted
+    pub actor_id: Option<Uuid>,
+    pub actor_username: Option<String>,
+    pub post_id: Uuid,
+    pub post_title: String,
+    /// Comment that was written, for comments and mentions in comments
+    pub comment_id: Option<Uuid>,
+    /// Outcome of a review: `approved` or `rejected`
+    pub detail: Option<String>,
+    /// Reviewer's comment
+    pub note: Option<String>,
+    pub is_read: bool,
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub read_at: Option<DateTime<Utc>>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub data: Vec<NotificationResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+    /// Unread notifications, whatever the filters
+    pub unread_count: i64,
+    /// Unread notifications by kind
+    pub unread_counts: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationReadResponse {
+    /// Notifications marked as read by the request
+    pub marked: u64,
+    /// Unread notifications left
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferenceResponse {
+    pub kind: String,
+    pub in_app: bool,
+    pub email_digest: bool,
+}
+
+/// Collection with the caller's progress through it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CollectionResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    /// Owner of the collection
+    pub user_id: Uuid,
+    /// Posts of the collection the caller can read
+    pub post_count: i64,
+    /// Of those, the posts the caller marked as read
+    pub read_count: i64,
+    /// Whether the caller read every post, false for an empty collection
+    pub is_completed: bool,
+    /// First post the caller has not read yet, `null` once completed
+    pub next_post_id: Option<Uuid>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    /// Also moves when posts are added, removed or reordered
+    #[schema(value_type = String, format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CollectionDetailResponse {
+    #[serde(flatten)]
+    pub collection: CollectionResponse,
+    /// Posts the caller can read, in order
+    pub posts: Vec<CollectionPostResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedCollectionResponse {
+    pub data: Vec<CollectionResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CollectionPostResponse {
+    /// 1-based place among the posts the caller can read
+    pub position: i64,
+    pub id: Uuid,
+    pub slug: String,
+    pub title: String,
+    pub user_id: Uuid,
+    pub status: String,
+    pub is_published: bool,
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// Whether the caller marked the post as read
+    pub is_read: bool,
+    #[schema(value_type = String, format = "date-time")]
+    pub added_at: DateTime<Utc>,
+}
+
+/// Place of a post in a collection, for moving through the series
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostSeriesResponse {
+    pub collection_id: Uuid,
+    pub title: String,
+    /// 1-based place of the post among the posts the caller can read
+    pub position: i64,
+    pub total: i64,
+    /// Post before this one, `null` for the first
+    pub previous: Option<SeriesPostResponse>,
+    /// Post after this one, `null` for the last
+    pub next: Option<SeriesPostResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SeriesPostResponse {
+    pub id: Uuid,
+    pub slug: String,
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostReadResponse {
+    pub is_read: bool,
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkPostResponse {
+    pub action: String,
+    /// Posts the action was applied to
+    pub succeeded: Vec<Uuid>,
+    /// Posts left unchanged, with the reason
+    pub failed: Vec<BulkPostFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkPostFailure {
+    pub id: Uuid,
+    /// Status the single-post endpoint would have answered with
+    pub status: u16,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReportResponse {
+    pub id: Uuid,
+    /// `post` or `comment`
+    pub target_type: String,
+    pub target_id: Uuid,
+    pub reason: String,
+    pub details: Option<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedModerationQueueResponse {
+    pub data: Vec<ModerationQueueItemResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+/// A post or comment with open reports
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationQueueItemResponse {
+    /// `post` or `comment`
+    pub target_type: String,
+    pub target_id: Uuid,
+    /// The reported post, or the post of the reported comment
+    pub post_id: Uuid,
+    pub post_title: String,
+    /// Beginning of the reported content
+    pub excerpt: Option<String>,
+    pub author_id: Uuid,
+    pub is_hidden: bool,
+    /// Number of open reports, one per reporting user
+    pub report_count: i64,
+    /// Open reports by reason, e.g. `{"spam": 3}`
+    #[schema(value_type = Object)]
+    pub reason_counts: BTreeMap<String, i64>,
+    /// Details given by the reporters, oldest first
+    pub details: Vec<String>,
+    #[schema(value_type = String, format = "date-time")]
+    pub first_reported_at: DateTime<Utc>,
+    #[schema(value_type = String, format = "date-time")]
+    pub last_reported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedModerationLogResponse {
+    pub data: Vec<ModerationActionResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+/// Entry of the moderation audit trail
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerationActionResponse {
+    /// `post` or `comment`
+    pub target_type: String,
+    pub target_id: Uuid,
+    /// `dismiss`, `hide`, `delete`, `warn` or `auto_hide`
+    pub action: String,
+    pub author_id: Option<Uuid>,
+    /// `null` for actions taken automatically
+    pub moderator_id: Option<Uuid>,
+    pub note: Option<String>,
+    /// Number of open reports the action closed
+    pub reports_resolved: i32,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TagResponse {
+    pub name: String,
+    /// Number of published posts with the tag
+    pub post_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchOperationResult {
+    pub status: u16,
+    #[schema(value_type = Object)]
+    pub body: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchResponse {
+    pub atomic: bool,
+    /// False when an atomic batch was rolled back
+    pub committed: bool,
+    /// One entry per executed operation, in request order
+    pub results: Vec<BatchOperationResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PaginatedTrashResponse {
+    pub data: Vec<TrashItemResponse>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LoginResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub user: UserResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TrashItemResponse {
+    /// `user` or `post`
+    pub kind: String,
+    pub id: Uuid,
+    /// Username or post title
+    pub label: String,
+    #[schema(value_type = String, format = "date-time")]
+    pub deleted_at: DateTime<Utc>,
+    /// When the item will be purged for good
+    #[schema(value_type = String, format = "date-time")]
+    pub purge_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserResponse {
-    pub id: i32,
+    pub id: Uuid,
     pub email: String,
     pub username: String,
     pub full_name: Option<String>,
+    pub role: String,
     pub is_active: bool,
     #[schema(value_type = String, format = "date-time")]
     pub created_at: DateTime<Utc>,
@@ -124,27 +776,363 @@ pub struct UserResponse {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PostResponse {
-    pub id: i32,
+    pub id: Uuid,
+    /// URL-safe permalink, see `GET /api/posts/by-slug/{slug}`
+    pub slug: String,
     pub title: String,
     pub content: String,
-    pub user_id: i32,
+    /// `plain` or `markdown`
+    pub content_format: String,
+    /// Sanitized HTML rendering of `content`
+    pub content_html: String,
+    /// Start of the content as plain text
+    pub excerpt: String,
+    pub reading_time_minutes: u32,
+    /// Tag names, sorted
+    pub tags: Vec<String>,
+    /// Number of comments that are neither deleted nor hidden
+    pub comment_count: i32,
+    /// Number of reactions of each kind, e.g. `{"like": 3}`
+    #[schema(value_type = Object)]
+    pub reaction_counts: BTreeMap<String, i64>,
+    /// The caller's reaction, `null` for anonymous callers
+    pub my_reaction: Option<String>,
+    pub bookmark_count: i32,
+    /// Whether the caller bookmarked the post
+    pub is_bookmarked: bool,
+    pub attachments: Vec<AttachmentResponse>,
+    pub user_id: Uuid,
+    /// `draft`, `in_review`, `approved`, `published` or `archived`
+    pub status: String,
+    /// Derived from `status`, kept for older clients. False while the post
+    /// is hidden by moderation.
     pub is_published: bool,
+    /// When the post was last published, `null` for drafts
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// When the post will be published automatically once approved
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub publish_at: Option<DateTime<Utc>>,
     #[schema(value_type = String, format = "date-time")]
     pub created_at: DateTime<Utc>,
     #[schema(value_type = String, format = "date-time")]
     pub updated_at: DateTime<Utc>,
+    /// Collections the post is part of, with the previous and next posts.
+    /// Only on single post reads of a post in a collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<Vec<PostSeriesResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PostSearchResultResponse {
+    #[serde(flatten)]
+    pub post: PostResponse,
+    /// Relevance, higher is better
+    pub rank: f32,
+    /// Matching excerpt of the content as escaped plain text, with hits
+    /// wrapped in `<mark>` tags
+    pub headline: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PostWithUserResponse {
-    pub id: i32,
+    pub id: Uuid,
+    /// URL-safe permalink, see `GET /api/posts/by-slug/{slug}`
+    pub slug: String,
     pub title: String,
     pub content: String,
-    pub user_id: i32,
+    /// `plain` or `markdown`
+    pub content_format: String,
+    /// Sanitized HTML rendering of `content`
+    pub content_html: String,
+    /// Start of the content as plain text
+    pub excerpt: String,
+    pub reading_time_minutes: u32,
+    /// Tag names, sorted
+    pub tags: Vec<String>,
+    /// Number of comments that are neither deleted nor hidden
+    pub comment_count: i32,
+    /// Number of reactions of each kind, e.g. `{"like": 3}`
+    #[schema(value_type = Object)]
+    pub reaction_counts: BTreeMap<String, i64>,
+    /// The caller's reaction, `null` for anonymous callers
+    pub my_reaction: Option<String>,
+    pub bookmark_count: i32,
+    /// Whether the caller bookmarked the post
+    pub is_bookmarked: bool,
+    pub attachments: Vec<AttachmentResponse>,
+    pub user_id: Uuid,
+    /// `draft`, `in_review`, `approved`, `published` or `archived`
+    pub status: String,
+    /// Derived from `status`, kept for older clients. False while the post
+    /// is hidden by moderation.
     pub is_published: bool,
+    /// When the post was last published, `null` for drafts
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// When the post will be published automatically once approved
+    #[schema(value_type = Option<String>, format = "date-time")]
+    pub publish_at: Option<DateTime<Utc>>,
     #[schema(value_type = String, format = "date-time")]
     pub created_at: DateTime<Utc>,
     #[schema(value_type = String, format = "date-time")]
     pub updated_at: DateTime<Utc>,
+    /// Collections the post is part of, with the previous and next posts.
+    /// Only on single post reads of a post in a collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<Vec<PostSeriesResponse>>,
     pub user: UserResponse,
-}
\ No newline at end of file
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.public_id,
+            email: user.email,
+            username: user.username,
+            full_name: user.full_name,
+            role: user.role,
+            is_active: user.is_active,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
+impl From<Attachment> for AttachmentResponse {
+    fn from(attachment: Attachment) -> Self {
+        Self {
+            id: attachment.public_id,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size_bytes: attachment.size_bytes,
+            width: attachment.width,
+            height: attachment.height,
+            url: format!("/api/attachments/{}", attachment.public_id),
+            thumbnail_url: attachment
+                .thumbnail_key
+                .map(|_| format!("/api/attachments/{}/thumbnail", attachment.public_id)),
+            created_at: attachment.created_at,
+        }
+    }
+}
+
+impl From<QueuedReport> for ModerationQueueItemResponse {
+    fn from(item: QueuedReport) -> Self {
+        let mut reason_counts = BTreeMap::new();
+        for reason in &item.reasons {
+            *reason_counts.entry(reason.clone()).or_insert(0) += 1;
+        }
+
+        Self {
+            target_type: item.target_type,
+            target_id: item.target_id,
+            post_id: item.post_id,
+            post_title: item.post_title,
+            excerpt: item.excerpt,
+            author_id: item.author_id,
+            is_hidden: item.is_hidden,
+            report_count: item.reasons.len() as i64,
+            reason_counts,
+            details: item.details,
+            first_reported_at: item.first_reported_at,
+            last_reported_at: item.last_reported_at,
+        }
+    }
+}
+
+impl From<ModerationLogEntry> for ModerationActionResponse {
+    fn from(entry: ModerationLogEntry) -> Self {
+        Self {
+            target_type: entry.target_type,
+            target_id: entry.target_id,
+            action: entry.action,
+            author_id: entry.author_public_id,
+            moderator_id: entry.moderator_public_id,
+            note: entry.note,
+            reports_resolved: entry.reports_resolved,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+impl From<Notification> for NotificationResponse {
+    fn from(notification: Notification) -> Self {
+        Self {
+            id: notification.public_id,
+            kind: notification.kind,
+            actor_id: notification.actor_public_id,
+            actor_username: notification.actor_username,
+            post_id: notification.post_public_id,
+            post_title: notification.post_title,
+            comment_id: notification.comment_public_id,
+            detail: notification.detail,
+            note: notification.note,
+            is_read: notification.read_at.is_some(),
+            read_at: notification.read_at,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+impl From<CollectionSummary> for CollectionResponse {
+    fn from(collection: CollectionSummary) -> Self {
+        Self {
+            id: collection.public_id,
+            title: collection.title,
+            description: collection.description,
+            user_id: collection.user_public_id,
+            post_count: collection.post_count,
+            read_count: collection.read_count,
+            is_completed: collection.post_count > 0 && collection.read_count == collection.post_count,
+            next_post_id: collection.next_post_public_id,
+            created_at: collection.created_at,
+            updated_at: collection.updated_at,
+        }
+    }
+}
+
+impl From<CollectionPost> for CollectionPostResponse {
+    fn from(post: CollectionPost) -> Self {
+        Self {
+            position: post.position,
+            id: post.public_id,
+            slug: post.slug,
+            title: post.title,
+            user_id: post.user_public_id,
+            status: post.status,
+            is_published: post.is_published,
+            published_at: post.published_at,
+            is_read: post.read_at.is_some(),
+            added_at: post.added_at,
+        }
+    }
+}
+
+impl From<PostSeries> for PostSeriesResponse {
+    fn from(series: PostSeries) -> Self {
+        let previous = match (series.previous_public_id, series.previous_slug, series.previous_title) {
+            (Some(id), Some(slug), Some(title)) => Some(SeriesPostResponse { id, slug, title }),
+            _ => None,
+        };
+        let next = match (series.next_public_id, series.next_slug, series.next_title) {
+            (Some(id), Some(slug), Some(title)) => Some(SeriesPostResponse { id, slug, title }),
+            _ => None,
+        };
+
+        Self {
+            collection_id: series.collection_public_id,
+            title: series.title,
+            position: series.position,
+            total: series.total,
+            previous,
+            next,
+        }
+    }
+}
+
+impl From<Comment> for CommentResponse {
+    fn from(comment: Comment) -> Self {
+        Self {
+            id: comment.public_id,
+            parent_id: comment.parent_public_id,
+            user_id: comment.user_public_id,
+            content: comment.content,
+            is_hidden: comment.is_hidden,
+            is_deleted: comment.is_deleted,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            replies: None,
+        }
+    }
+}
+
+impl From<Post> for PostResponse {
+    fn from(post: Post) -> Self {
+        let rendered = render_service::rendered(&post.content, &post.content_format, post.content_html);
+
+        Self {
+            id: post.public_id,
+            slug: post.slug,
+            title: post.title,
+            content_format: post.content_format,
+            content_html: rendered.html,
+            excerpt: rendered.excerpt,
+            reading_time_minutes: rendered.reading_time_minutes,
+            content: post.content,
+            tags: post.tags,
+            comment_count: post.comment_count,
+            reaction_counts: reaction_counts(post.reaction_counts),
+            my_reaction: post.my_reaction,
+            bookmark_count: post.bookmark_count,
+            is_bookmarked: post.is_bookmarked,
+            attachments: attachments(post.attachments),
+            user_id: post.user_public_id,
+            status: post.status,
+            is_published: post.is_published,
+            published_at: post.published_at,
+            publish_at: post.publish_at,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            series: None,
+        }
+    }
+}
+
+impl From<(Post, User)> for PostWithUserResponse {
+    fn from((post, user): (Post, User)) -> Self {
+        let rendered = render_service::rendered(&post.content, &post.content_format, post.content_html);
+
+        Self {
+            id: post.public_id,
+            slug: post.slug,
+            title: post.title,
+            content_format: post.content_format,
+            content_html: rendered.html,
+            excerpt: rendered.excerpt,
+            reading_time_minutes: rendered.reading_time_minutes,
+            content: post.content,
+            tags: post.tags,
+            comment_count: post.comment_count,
+            reaction_counts: reaction_counts(post.reaction_counts),
+            my_reaction: post.my_reaction,
+            bookmark_count: post.bookmark_count,
+            is_bookmarked: post.is_bookmarked,
+            attachments: attachments(post.attachments),
+            user_id: post.user_public_id,
+            status: post.status,
+            is_published: post.is_published,
+            published_at: post.published_at,
+            publish_at: post.publish_at,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+            series: None,
+            user: UserResponse::from(user),
+        }
+    }
+}
+
+/// Reaction counters as stored on the post, without kinds that dropped to zero
+pub fn reaction_counts(counts: Value) -> BTreeMap<String, i64> {
+    let mut counts: BTreeMap<String, i64> = serde_json::from_value(counts).unwrap_or_default();
+    counts.retain(|_, count| *count > 0);
+    counts
+}
+
+/// Serializes a resource and keeps only the requested top-level fields.
+/// Embedded relations listed in `keep` survive the projection as well.
+pub fn select_fields<T: Serialize>(resource: T, fields: Option<&[&str]>, keep: &[&str]) -> Value {
+    let mut value = serde_json::to_value(resource).unwrap_or(Value::Null);
+
+    if let (Some(fields), Value::Object(map)) = (fields, &mut value) {
+        map.retain(|key, _| fields.contains(&key.as_str()) || keep.contains(&key.as_str()));
+    }
+
+    value
+}
+
+/// Attachments loaded with a post as a JSON array of `attachments` rows
+pub fn attachments(rows: Value) -> Vec<AttachmentResponse> {
+    let rows: Vec<Attachment> = serde_json::from_value(rows).unwrap_or_default();
+    rows.into_iter().map(AttachmentResponse::from).collect()
+}
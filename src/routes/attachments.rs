@@ -0,0 +1,12 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppState, handlers::attachment};
+
+pub fn attachment_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/:id",
+            get(attachment::get_attachment).delete(attachment::delete_attachment),
+        )
+        .route("/:id/thumbnail", get(attachment::get_attachment_thumbnail))
+}
@@ -0,0 +1,7 @@
+use axum::{routing::post, Router};
+
+use crate::{config::AppState, handlers::auth};
+
+pub fn auth_router() -> Router<AppState> {
+    Router::new().route("/login", post(auth::login))
+}
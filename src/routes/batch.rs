@@ -0,0 +1,7 @@
+use axum::{routing::post, Router};
+
+use crate::{config::AppState, handlers::batch};
+
+pub fn batch_router() -> Router<AppState> {
+    Router::new().route("/", post(batch::execute_batch))
+}
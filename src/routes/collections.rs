@@ -0,0 +1,18 @@
+use axum::{
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+
+use crate::{config::AppState, handlers::collection};
+
+pub fn collection_router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(collection::create_collection))
+        .route("/", get(collection::get_collections))
+        .route("/:id", get(collection::get_collection))
+        .route("/:id", patch(collection::patch_collection))
+        .route("/:id", delete(collection::delete_collection))
+        .route("/:id/posts", post(collection::add_collection_post))
+        .route("/:id/posts/:post_id", delete(collection::remove_collection_post))
+        .route("/:id/order", put(collection::reorder_collection))
+}
@@ -0,0 +1,7 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppState, handlers::event};
+
+pub fn event_router() -> Router<AppState> {
+    Router::new().route("/", get(event::stream_events))
+}
@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppState, handlers::feed};
+
+pub fn feed_router() -> Router<AppState> {
+    Router::new()
+        .route("/:file", get(feed::get_posts_feed))
+        .route("/users/:id/:file", get(feed::get_user_posts_feed))
+        .route("/tags/:name/:file", get(feed::get_tag_posts_feed))
+}
@@ -0,0 +1,7 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppState, handlers::follow};
+
+pub fn follow_feed_router() -> Router<AppState> {
+    Router::new().route("/", get(follow::get_feed))
+}
@@ -1,4 +1,15 @@
 pub mod users;
 pub mod posts;
 pub mod productions;
-pub mod health;
\ No newline at end of file
+pub mod health;
+pub mod batch;
+pub mod auth;
+pub mod trash;
+pub mod tags;
+pub mod events;
+pub mod feeds;
+pub mod follows;
+pub mod attachments;
+pub mod moderation;
+pub mod notifications;
+pub mod collections;
\ No newline at end of file
@@ -0,0 +1,14 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppState, handlers::moderation};
+
+pub fn moderation_router() -> Router<AppState> {
+    Router::new()
+        .route("/queue", get(moderation::get_moderation_queue))
+        .route("/actions", get(moderation::get_moderation_log))
+        .route("/posts/:id", post(moderation::moderate_post))
+        .route("/comments/:id", post(moderation::moderate_comment))
+}
@@ -0,0 +1,17 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{config::AppState, handlers::notification};
+
+pub fn notification_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(notification::get_notifications))
+        .route("/read", post(notification::mark_all_notifications_read))
+        .route("/:id/read", post(notification::mark_notification_read))
+        .route(
+            "/preferences",
+            get(notification::get_notification_preferences).put(notification::update_notification_preferences),
+        )
+}
@@ -1,15 +1,53 @@
 use axum::{
-    routing::{delete, get, post, put},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
     Router,
 };
 
-use crate::{config::AppState, handlers::post};
+use crate::{config::AppState, handlers::{attachment, bookmark, bulk, collection, comment, moderation, post, reaction, revision, workflow}};
 
 pub fn post_router() -> Router<AppState> {
     Router::new()
         .route("/", post(post::create_post))
         .route("/", get(post::get_posts))
+        .route("/search", get(post::search_posts))
+        .route("/bulk", post(bulk::bulk_posts))
+        .route("/scheduled", get(workflow::get_scheduled_posts))
+        .route("/by-slug/:slug", get(post::get_post_by_slug))
         .route("/:id", get(post::get_post_by_id))
         .route("/:id", put(post::update_post))
+        .route("/:id", patch(post::patch_post))
         .route("/:id", delete(post::delete_post))
+        .route("/:id/restore", post(post::restore_post))
+        .route("/:id/submit", post(workflow::submit_post))
+        .route("/:id/approve", post(workflow::approve_post))
+        .route("/:id/reject", post(workflow::reject_post))
+        .route("/:id/publish", post(workflow::publish_post))
+        .route("/:id/unpublish", post(workflow::unpublish_post))
+        .route("/:id/archive", post(workflow::archive_post))
+        .route("/:id/history", get(workflow::get_post_history))
+        .route("/:id/schedule", delete(workflow::cancel_scheduled_post))
+        .route("/:id/comments", get(comment::get_post_comments))
+        .route("/:id/comments", post(comment::create_comment))
+        .route("/:id/comments/:comment_id", patch(comment::update_comment))
+        .route("/:id/comments/:comment_id", delete(comment::delete_comment))
+        .route("/:id/comments/:comment_id/hide", post(comment::hide_comment))
+        .route("/:id/comments/:comment_id/unhide", post(comment::unhide_comment))
+        .route("/:id/comments/:comment_id/report", post(moderation::report_comment))
+        .route("/:id/report", post(moderation::report_post))
+        .route("/:id/reaction", put(reaction::set_reaction))
+        .route("/:id/reaction", delete(reaction::remove_reaction))
+        .route("/:id/bookmark", put(bookmark::add_bookmark))
+        .route("/:id/bookmark", delete(bookmark::remove_bookmark))
+        .route("/:id/read", put(collection::mark_post_read))
+        .route("/:id/read", delete(collection::mark_post_unread))
+        // Upload sizes are checked per file against ATTACHMENT_MAX_BYTES
+        .route(
+            "/:id/attachments",
+            post(attachment::upload_attachments).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/:id/revisions", get(revision::get_post_revisions))
+        .route("/:id/revisions/diff", get(revision::diff_post_revisions))
+        .route("/:id/revisions/:rev", get(revision::get_post_revision))
+        .route("/:id/revisions/:rev/restore", post(revision::restore_post_revision))
 }
\ No newline at end of file
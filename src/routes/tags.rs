@@ -0,0 +1,13 @@
+use axum::{
+    routing::{get, patch, post},
+    Router,
+};
+
+use crate::{config::AppState, handlers::tag};
+
+pub fn tag_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(tag::get_tags))
+        .route("/:name", patch(tag::rename_tag))
+        .route("/:name/merge", post(tag::merge_tag))
+}
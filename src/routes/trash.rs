@@ -0,0 +1,7 @@
+use axum::{routing::get, Router};
+
+use crate::{config::AppState, handlers::trash};
+
+pub fn trash_router() -> Router<AppState> {
+    Router::new().route("/", get(trash::get_trash))
+}
@@ -1,9 +1,9 @@
 use axum::{
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 
-use crate::{config::AppState, handlers::user};
+use crate::{config::AppState, handlers::{bookmark, follow, moderation, post, user}};
 
 pub fn user_router() -> Router<AppState> {
     Router::new()
@@ -11,5 +11,15 @@ pub fn user_router() -> Router<AppState> {
         .route("/", get(user::get_users))
         .route("/:id", get(user::get_user_by_id))
         .route("/:id", put(user::update_user))
+        .route("/:id", patch(user::patch_user))
         .route("/:id", delete(user::delete_user))
+        .route("/:id/restore", post(user::restore_user))
+        .route("/:id/posts", get(post::get_user_posts))
+        .route("/:id/follow", put(follow::follow_user))
+        .route("/:id/follow", delete(follow::unfollow_user))
+        .route("/:id/followers", get(follow::get_followers))
+        .route("/:id/following", get(follow::get_following))
+        .route("/me/posts", get(post::get_my_posts))
+        .route("/me/bookmarks", get(bookmark::get_my_bookmarks))
+        .route("/me/warnings", get(moderation::get_my_warnings))
 }
\ No newline at end of file
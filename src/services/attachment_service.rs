@@ -0,0 +1,200 @@
+// Attachment service kiểm tra file upload, tạo thumbnail và dọn file đã xoá khỏi storage
+
+use std::{io::Cursor, sync::Arc, time::Duration};
+
+use image::{ImageFormat, ImageReader, Limits};
+use sqlx::PgPool;
+
+use crate::storage::Storage;
+
+/// Files accepted in one upload request
+pub const MAX_FILES_PER_UPLOAD: usize = 10;
+
+/// Longest side of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// Decoding an image may not allocate more than this
+const MAX_DECODE_BYTES: u64 = 256 * 1024 * 1024;
+
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/jpeg";
+
+/// Content type of a file judged by its first bytes, `None` for types that
+/// cannot be attached
+pub fn detect_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+
+    match image::guess_format(data).ok()? {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+pub fn is_image(content_type: &str) -> bool {
+    content_type.starts_with("image/")
+}
+
+/// File name as sent by the client, without directories, relative path
+/// names or characters that would break a `Content-Disposition` header
+pub fn clean_file_name(name: &str) -> String {
+    let name: String = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect();
+
+    match name.trim() {
+        "" | "." | ".." => "file".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Dimensions of an image and its JPEG thumbnail
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Decodes an image and scales it down to fit `THUMBNAIL_SIZE`. CPU bound,
+/// call it from a blocking task.
+pub fn make_thumbnail(data: &[u8]) -> Result<Thumbnail, image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+
+    let mut reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    // JPEG has no alpha channel
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).into_rgb8();
+    let mut encoded = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut encoded, ImageFormat::Jpeg)?;
+
+    Ok(Thumbnail {
+        width: image.width(),
+        height: image.height(),
+        data: encoded.into_inner(),
+    })
+}
+
+/// Removes the files of deleted attachments from storage. Runs every
+/// minute for the lifetime of the server; failed deletions are retried.
+pub async fn purge_deleted_files(db: PgPool, storage: Arc<dyn Storage>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let keys = match sqlx::query_scalar!(
+            "SELECT storage_key FROM deleted_files ORDER BY deleted_at LIMIT 100"
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(keys) => keys,
+            Err(e) => {
+                tracing::error!("Failed to load deleted files: {}", e);
+                continue;
+            }
+        };
+
+        let mut removed = Vec::with_capacity(keys.len());
+        for key in keys {
+            match storage.delete(&key).await {
+                Ok(()) => removed.push(key),
+                Err(e) => tracing::warn!("Failed to delete stored file {}: {}", key, e),
+            }
+        }
+
+        if removed.is_empty() {
+            continue;
+        }
+
+        match sqlx::query!("DELETE FROM deleted_files WHERE storage_key = ANY($1)", &removed)
+            .execute(&db)
+            .await
+        {
+            Ok(result) => tracing::info!("Deleted {} stored files", result.rows_affected()),
+            Err(e) => tracing::error!("Failed to clear deleted files: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    fn encoded(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(RgbImage::new(width, height));
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn content_type_comes_from_the_data() {
+        assert_eq!(detect_content_type(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(detect_content_type(&encoded(4, 4, ImageFormat::Png)), Some("image/png"));
+        assert_eq!(detect_content_type(&encoded(4, 4, ImageFormat::Jpeg)), Some("image/jpeg"));
+    }
+
+    #[test]
+    fn unknown_data_has_no_content_type() {
+        assert_eq!(detect_content_type(b""), None);
+        assert_eq!(detect_content_type(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+        assert_eq!(detect_content_type(b"PK\x03\x04"), None);
+        // Only the leading magic counts
+        assert_eq!(detect_content_type(b"not a %PDF-"), None);
+    }
+
+    #[test]
+    fn file_names_lose_their_directories() {
+        assert_eq!(clean_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(clean_file_name("C:\\Users\\me\\report.pdf"), "report.pdf");
+        assert_eq!(clean_file_name("..\\..\\boot.ini"), "boot.ini");
+        assert_eq!(clean_file_name("photos/"), "file");
+        assert_eq!(clean_file_name(".."), "file");
+        assert_eq!(clean_file_name("uploads/."), "file");
+    }
+
+    #[test]
+    fn file_names_keep_unicode() {
+        assert_eq!(clean_file_name("ảnh đẹp.png"), "ảnh đẹp.png");
+        assert_eq!(clean_file_name("写真.jpg"), "写真.jpg");
+    }
+
+    #[test]
+    fn file_names_drop_header_breaking_characters() {
+        assert_eq!(clean_file_name("a\"b\r\nc.pdf"), "abc.pdf");
+        assert_eq!(clean_file_name("  \u{0}\t "), "file");
+        assert_eq!(clean_file_name(""), "file");
+        assert_eq!(clean_file_name(&"é".repeat(300)).chars().count(), 255);
+    }
+
+    #[test]
+    fn thumbnails_fit_the_size_and_keep_the_dimensions() {
+        let thumbnail = make_thumbnail(&encoded(800, 400, ImageFormat::Png)).unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (800, 400));
+
+        let scaled = image::load_from_memory(&thumbnail.data).unwrap();
+        assert_eq!(detect_content_type(&thumbnail.data), Some(THUMBNAIL_CONTENT_TYPE));
+        assert_eq!((scaled.width(), scaled.height()), (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2));
+    }
+
+    #[test]
+    fn thumbnails_of_broken_images_fail() {
+        let mut data = encoded(8, 8, ImageFormat::Png);
+        data.truncate(data.len() / 2);
+        assert!(make_thumbnail(&data).is_err());
+        assert!(make_thumbnail(b"%PDF-1.7\n").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+// Collection service quản lý thứ tự posts trong collection và điều hướng giữa các phần của series
+
+use sqlx::PgConnection;
+
+use crate::database::models::PostSeries;
+
+/// Posts a single collection may hold
+pub const MAX_COLLECTION_POSTS: usize = 200;
+
+/// Collections a post appears in, oldest first, with its place among the
+/// posts of each one the viewer can read and the posts before and after it
+pub async fn series_of(
+    conn: &mut PgConnection,
+    post_id: i32,
+    viewer: Option<i32>,
+    is_admin: bool,
+) -> Result<Vec<PostSeries>, sqlx::Error> {
+    sqlx::query_as!(
+        PostSeries,
+        r#"
+        WITH parts AS (
+            SELECT cp.collection_id, p.id,
+                   ROW_NUMBER() OVER w AS position,
+                   COUNT(*) OVER (PARTITION BY cp.collection_id) AS total,
+                   LAG(p.public_id) OVER w AS previous_public_id,
+                   LAG(p.slug) OVER w AS previous_slug,
+                   LAG(p.title) OVER w AS previous_title,
+                   LEAD(p.public_id) OVER w AS next_public_id,
+                   LEAD(p.slug) OVER w AS next_slug,
+                   LEAD(p.title) OVER w AS next_title
+            FROM collection_posts cp
+            JOIN posts p ON p.id = cp.post_id
+            WHERE cp.collection_id IN (SELECT collection_id FROM collection_posts WHERE post_id = $1)
+              AND p.deleted_at IS NULL
+              AND (p.is_published OR p.user_id = $2 OR $3)
+            WINDOW w AS (PARTITION BY cp.collection_id ORDER BY cp.position)
+        )
+        SELECT c.public_id AS collection_public_id, c.title,
+               parts.position AS "position!", parts.total AS "total!",
+               parts.previous_public_id AS "previous_public_id?",
+               parts.previous_slug AS "previous_slug?",
+               parts.previous_title AS "previous_title?",
+               parts.next_public_id AS "next_public_id?",
+               parts.next_slug AS "next_slug?",
+               parts.next_title AS "next_title?"
+        FROM parts
+        JOIN collections c ON c.id = parts.collection_id
+        WHERE parts.id = $1
+        ORDER BY c.created_at, c.id
+        "#,
+        post_id,
+        viewer,
+        is_admin
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Adds a post at a 1-based position, moving the posts from there on one
+/// place down. Without a position, or past the end, it is appended.
+pub async fn add_post(
+    conn: &mut PgConnection,
+    collection_id: i32,
+    post_id: i32,
+    position: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    // Posts deleted for good may have left gaps
+    set_order(conn, collection_id, &[]).await?;
+
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*)::int AS "count!" FROM collection_posts WHERE collection_id = $1"#,
+        collection_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    let position = position.map_or(count + 1, |position| position.clamp(1, count + 1));
+
+    sqlx::query!(
+        "UPDATE collection_posts SET position = position + 1 WHERE collection_id = $1 AND position >= $2",
+        collection_id,
+        position
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO collection_posts (collection_id, post_id, position) VALUES ($1, $2, $3)",
+        collection_id,
+        post_id,
+        position
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    touch(conn, collection_id).await
+}
+
+/// Takes a post out of a collection and closes the gap it leaves.
+/// Returns whether the post was in the collection.
+pub async fn remove_post(conn: &mut PgConnection, collection_id: i32, post_id: i32) -> Result<bool, sqlx::Error> {
+    let removed = sqlx::query!(
+        "DELETE FROM collection_posts WHERE collection_id = $1 AND post_id = $2",
+        collection_id,
+        post_id
+    )
+    .execute(&mut *conn)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if removed {
+        set_order(conn, collection_id, &[]).await?;
+        touch(conn, collection_id).await?;
+    }
+
+    Ok(removed)
+}
+
+/// Numbers the posts of a collection from 1: `post_ids` first, in the given
+/// order, then the other posts in their current order
+pub async fn set_order(conn: &mut PgConnection, collection_id: i32, post_ids: &[i32]) -> Result<(), sqlx::Error> {
+    // Positions are unique, checked once the whole statement ran
+    sqlx::query!(
+        r#"
+        UPDATE collection_posts cp SET position = ordered.position
+        FROM (
+            SELECT cp.post_id,
+                   ROW_NUMBER() OVER (ORDER BY listed.n, cp.position)::int AS position
+            FROM collection_posts cp
+            LEFT JOIN UNNEST($2::int4[]) WITH ORDINALITY AS listed(post_id, n) ON listed.post_id = cp.post_id
+            WHERE cp.collection_id = $1
+        ) ordered
+        WHERE cp.collection_id = $1 AND cp.post_id = ordered.post_id AND cp.position <> ordered.position
+        "#,
+        collection_id,
+        post_ids
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks a collection as changed, e.g. after its posts moved
+pub async fn touch(conn: &mut PgConnection, collection_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query!("UPDATE collections SET updated_at = NOW() WHERE id = $1", collection_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+// Comment service dựng cây comments từ danh sách phẳng, tạo, ẩn và xoá comment
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgConnection;
+
+use crate::{
+    database::models::Comment, models::responses::CommentResponse, services::notification_service,
+};
+
+/// How the comments of a post are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentListMode {
+    Tree,
+    Flat,
+}
+
+impl CommentListMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tree" => Some(CommentListMode::Tree),
+            "flat" => Some(CommentListMode::Flat),
+            _ => None,
+        }
+    }
+}
+
+/// Nests comments under their parents. `comments` holds whole threads in
+/// creation order; comments whose parent is not in the list become roots.
+pub fn build_tree(comments: Vec<Comment>) -> Vec<CommentResponse> {
+    let ids: HashSet<i32> = comments.iter().map(|comment| comment.id).collect();
+    let mut replies: HashMap<i32, Vec<CommentResponse>> = HashMap::new();
+    let mut roots = Vec::new();
+
+    // Replies are newer than their parent, so walking the list backwards
+    // completes every subtree before its root is reached
+    for comment in comments.into_iter().rev() {
+        let id = comment.id;
+        let parent_id = comment.parent_id.filter(|parent| ids.contains(parent));
+
+        let mut response = CommentResponse::from(comment);
+        let mut own_replies = replies.remove(&id).unwrap_or_default();
+        own_replies.reverse();
+        response.replies = Some(own_replies);
+
+        match parent_id {
+            Some(parent) => replies.entry(parent).or_default().push(response),
+            None => roots.push(response),
+        }
+    }
+
+    roots.reverse();
+    roots
+}
+
+/// Adds a comment to a post and notifies the post's author and the users
+/// it mentions. Returns the id of the comment.
+pub async fn create(
+    conn: &mut PgConnection,
+    post_id: i32,
+    parent_id: Option<i32>,
+    user_id: i32,
+    content: &str,
+) -> Result<i32, sqlx::Error> {
+    let comment_id = sqlx::query_scalar!(
+        r#"
+        WITH created AS (
+            INSERT INTO comments (post_id, parent_id, user_id, content)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+        ), counted AS (
+            UPDATE posts SET comment_count = comment_count + 1 WHERE id = $1
+        )
+        SELECT id AS "id!" FROM created
+        "#,
+        post_id,
+        parent_id,
+        user_id,
+        content
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    notification_service::comment_created(conn, post_id, comment_id, user_id, content).await?;
+
+    Ok(comment_id)
+}
+
+/// Hides a comment from other users or shows it again. `moderator_id` is
+/// `None` when the comment is hidden automatically. Returns false when the
+/// comment was already in that state.
+pub async fn set_hidden(
+    conn: &mut PgConnection,
+    comment_id: i32,
+    hide: bool,
+    moderator_id: Option<i32>,
+) -> Result<bool, sqlx::Error> {
+    // Only an actual change moves the post's comment count
+    let changed = sqlx::query_scalar!(
+        r#"
+        WITH changed AS (
+            UPDATE comments
+            SET is_hidden = $2,
+                hidden_by = CASE WHEN $2 THEN $3::int4 END,
+                hidden_at = CASE WHEN $2 THEN NOW() END
+            WHERE id = $1 AND is_hidden <> $2
+            RETURNING post_id, deleted_at
+        ), counted AS (
+            UPDATE posts SET comment_count = comment_count + CASE WHEN $2 THEN -1 ELSE 1 END
+            WHERE id IN (SELECT post_id FROM changed WHERE deleted_at IS NULL)
+        )
+        SELECT COUNT(*) AS "count!" FROM changed
+        "#,
+        comment_id,
+        hide,
+        moderator_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(changed > 0)
+}
+
+/// Deletes a comment. A comment with replies is kept as a placeholder so
+/// the thread stays intact; otherwise it is removed. Returns false when the
+/// comment was already deleted.
+pub async fn delete(conn: &mut PgConnection, comment_id: i32) -> Result<bool, sqlx::Error> {
+    let deleted = sqlx::query_scalar!(
+        r#"
+        WITH target AS (
+            SELECT c.id, c.post_id, c.is_hidden,
+                   EXISTS (SELECT 1 FROM comments r WHERE r.parent_id = c.id) AS has_replies
+            FROM comments c
+            WHERE c.id = $1 AND c.deleted_at IS NULL
+            FOR UPDATE
+        ), removed AS (
+            DELETE FROM comments WHERE id IN (SELECT id FROM target WHERE NOT has_replies)
+        ), tombstoned AS (
+            UPDATE comments SET deleted_at = NOW()
+            WHERE id IN (SELECT id FROM target WHERE has_replies)
+        ), counted AS (
+            UPDATE posts SET comment_count = comment_count - 1
+            WHERE id IN (SELECT post_id FROM target WHERE NOT is_hidden)
+        )
+        SELECT COUNT(*) AS "count!" FROM target
+        "#,
+        comment_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(deleted > 0)
+}
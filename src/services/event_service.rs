@@ -0,0 +1,223 @@
+// Event service nhận events của posts và comments qua Postgres LISTEN/NOTIFY và phát cho các stream SSE
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, database::models::PostEvent};
+
+/// Channel the event log triggers notify with the id of each new event
+const EVENT_CHANNEL: &str = "events";
+
+/// Events a slow stream may fall behind by before it is told to reset
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// Topics a single stream may subscribe to
+pub const MAX_TOPICS: usize = 20;
+
+/// What a stream subscribes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTopic {
+    /// Posts created, updated, published and deleted
+    Posts,
+    /// New comments on any post
+    Comments,
+    /// Everything about one post, its comments included
+    Post(Uuid),
+}
+
+impl EventTopic {
+    /// Parses `posts`, `comments` or `post:<id>`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "posts" => Some(EventTopic::Posts),
+            "comments" => Some(EventTopic::Comments),
+            _ => value
+                .strip_prefix("post:")
+                .and_then(|id| id.parse().ok())
+                .map(EventTopic::Post),
+        }
+    }
+
+    fn matches(&self, event: &PostEvent) -> bool {
+        match self {
+            EventTopic::Posts => event.kind.starts_with("post."),
+            EventTopic::Comments => event.kind.starts_with("comment."),
+            EventTopic::Post(id) => event.post_id == *id,
+        }
+    }
+}
+
+/// Topics of a stream and who reads it
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    topics: Vec<EventTopic>,
+    viewer_id: Option<i32>,
+    is_admin: bool,
+}
+
+impl Subscription {
+    pub fn new(topics: Vec<EventTopic>, viewer: Option<&AuthUser>) -> Self {
+        Self {
+            topics,
+            viewer_id: viewer.map(|viewer| viewer.id),
+            is_admin: viewer.is_some_and(AuthUser::is_admin),
+        }
+    }
+
+    /// Whether the event is on one of the topics and the viewer may see
+    /// it: events of unpublished posts go to their author and admins only
+    pub fn accepts(&self, event: &PostEvent) -> bool {
+        let visible = event.is_public
+            || self.is_admin
+            || (self.viewer_id.is_some() && event.owner_id == self.viewer_id);
+
+        visible && self.topics.iter().any(|topic| topic.matches(event))
+    }
+}
+
+/// Forwards the events committed by any instance to the streams of this one.
+///
+/// Listens on the channel the event log triggers notify. After losing the
+/// connection it reconnects and catches up from the log, so streams do not
+/// miss the events committed in the meantime.
+pub async fn listen_for_events(db: PgPool, bus: broadcast::Sender<Arc<PostEvent>>) {
+    let mut last_id: Option<i64> = None;
+
+    loop {
+        let mut listener = match connect(&db).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("Failed to listen for events: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        let caught_up = match last_id {
+            Some(last_id) => events_after(&db, last_id).await,
+            None => latest_event_id(&db).await.map(|id| {
+                last_id = Some(id);
+                Vec::new()
+            }),
+        };
+        // Events committed while connecting may be both read from the log
+        // and notified
+        let mut caught_up_ids = HashSet::new();
+        match caught_up {
+            Ok(events) => {
+                for event in events {
+                    last_id = Some(event.id);
+                    caught_up_ids.insert(event.id);
+                    // Sending fails only when no stream is open
+                    let _ = bus.send(Arc::new(event));
+                }
+            }
+            Err(e) => tracing::error!("Failed to read the event log: {}", e),
+        }
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    let Ok(id) = notification.payload().parse::<i64>() else {
+                        continue;
+                    };
+                    if caught_up_ids.contains(&id) {
+                        continue;
+                    }
+
+                    match event_by_id(&db, id).await {
+                        Ok(Some(event)) => {
+                            last_id = last_id.max(Some(id));
+                            let _ = bus.send(Arc::new(event));
+                        }
+                        // Pruned already
+                        Ok(None) => {}
+                        Err(e) => tracing::error!("Failed to load event {}: {}", id, e),
+                    }
+                }
+                Ok(None) => {
+                    tracing::warn!("Lost the event listener connection, reconnecting");
+                    break;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to receive events: {}", e);
+                    break;
+                }
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Keeps the latest `size` events of the log and deletes older ones
+pub async fn prune_event_log(db: PgPool, size: u32) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let result = sqlx::query!(
+            "DELETE FROM events
+             WHERE id <= (SELECT id FROM events ORDER BY id DESC OFFSET $1 LIMIT 1)",
+            size as i64
+        )
+        .execute(&db)
+        .await;
+
+        match result {
+            Ok(result) if result.rows_affected() > 0 => {
+                tracing::info!("Pruned {} events from the event log", result.rows_affected())
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to prune the event log: {}", e),
+        }
+    }
+}
+
+/// Whether the event is still in the log, i.e. a stream that saw it can
+/// resume without a gap
+pub async fn is_logged(db: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM events WHERE id = $1) AS "exists!""#,
+        id
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Logged events newer than `id`, oldest first
+pub async fn events_after(db: &PgPool, id: i64) -> Result<Vec<PostEvent>, sqlx::Error> {
+    sqlx::query_as!(
+        PostEvent,
+        "SELECT id, kind, post_id, owner_id, is_public, payload FROM events WHERE id > $1 ORDER BY id",
+        id
+    )
+    .fetch_all(db)
+    .await
+}
+
+async fn connect(db: &PgPool) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(EVENT_CHANNEL).await?;
+    Ok(listener)
+}
+
+async fn latest_event_id(db: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT COALESCE(MAX(id), 0) AS "id!" FROM events"#)
+        .fetch_one(db)
+        .await
+}
+
+async fn event_by_id(db: &PgPool, id: i64) -> Result<Option<PostEvent>, sqlx::Error> {
+    sqlx::query_as!(
+        PostEvent,
+        "SELECT id, kind, post_id, owner_id, is_public, payload FROM events WHERE id = $1",
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
@@ -0,0 +1,201 @@
+// Feed service dựng Atom, RSS và JSON Feed từ các posts đã publish
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Number of most recently published posts a feed lists
+pub const FEED_LENGTH: i64 = 20;
+
+/// Syndication format, chosen by the extension of the feed's file name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+    Json,
+}
+
+impl FeedFormat {
+    /// Format of a feed file name such as `posts.atom`
+    pub fn from_file_name(file: &str) -> Option<Self> {
+        match file {
+            "posts.atom" => Some(FeedFormat::Atom),
+            "posts.rss" => Some(FeedFormat::Rss),
+            "posts.json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    /// URL the feed itself is served at
+    pub feed_url: String,
+    pub home_page_url: String,
+    /// Latest `updated` of the entries
+    pub updated: DateTime<Utc>,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub id: Uuid,
+    pub url: String,
+    pub title: String,
+    pub author: String,
+    pub tags: Vec<String>,
+    pub summary: String,
+    pub content_html: String,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+impl Feed {
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Atom => self.to_atom(),
+            FeedFormat::Rss => self.to_rss(),
+            FeedFormat::Json => self.to_json(),
+        }
+    }
+
+    fn to_atom(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        let _ = writeln!(xml, "  <title>{}</title>", escape_xml(&self.title));
+        let _ = writeln!(xml, "  <id>{}</id>", escape_xml(&self.feed_url));
+        let _ = writeln!(xml, "  <link rel=\"self\" href=\"{}\"/>", escape_xml(&self.feed_url));
+        let _ = writeln!(xml, "  <link rel=\"alternate\" href=\"{}\"/>", escape_xml(&self.home_page_url));
+        let _ = writeln!(xml, "  <updated>{}</updated>", self.updated.to_rfc3339());
+
+        for entry in &self.entries {
+            xml.push_str("  <entry>\n");
+            let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&entry.title));
+            let _ = writeln!(xml, "    <id>urn:uuid:{}</id>", entry.id);
+            let _ = writeln!(xml, "    <link rel=\"alternate\" href=\"{}\"/>", escape_xml(&entry.url));
+            let _ = writeln!(xml, "    <published>{}</published>", entry.published.to_rfc3339());
+            let _ = writeln!(xml, "    <updated>{}</updated>", entry.updated.to_rfc3339());
+            let _ = writeln!(xml, "    <author><name>{}</name></author>", escape_xml(&entry.author));
+            for tag in &entry.tags {
+                let _ = writeln!(xml, "    <category term=\"{}\"/>", escape_xml(tag));
+            }
+            let _ = writeln!(xml, "    <summary>{}</summary>", escape_xml(&entry.summary));
+            let _ = writeln!(xml, "    <content type=\"html\">{}</content>", escape_xml(&entry.content_html));
+            xml.push_str("  </entry>\n");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    fn to_rss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" ");
+        xml.push_str("xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+        xml.push_str("  <channel>\n");
+        let _ = writeln!(xml, "    <title>{}</title>", escape_xml(&self.title));
+        let _ = writeln!(xml, "    <link>{}</link>", escape_xml(&self.home_page_url));
+        let _ = writeln!(xml, "    <description>{}</description>", escape_xml(&self.title));
+        let _ = writeln!(xml, "    <atom:link rel=\"self\" type=\"application/rss+xml\" href=\"{}\"/>", escape_xml(&self.feed_url));
+        let _ = writeln!(xml, "    <lastBuildDate>{}</lastBuildDate>", self.updated.to_rfc2822());
+
+        for entry in &self.entries {
+            xml.push_str("    <item>\n");
+            let _ = writeln!(xml, "      <title>{}</title>", escape_xml(&entry.title));
+            let _ = writeln!(xml, "      <link>{}</link>", escape_xml(&entry.url));
+            let _ = writeln!(xml, "      <guid isPermaLink=\"false\">urn:uuid:{}</guid>", entry.id);
+            let _ = writeln!(xml, "      <pubDate>{}</pubDate>", entry.published.to_rfc2822());
+            let _ = writeln!(xml, "      <dc:creator>{}</dc:creator>", escape_xml(&entry.author));
+            for tag in &entry.tags {
+                let _ = writeln!(xml, "      <category>{}</category>", escape_xml(tag));
+            }
+            let _ = writeln!(xml, "      <description>{}</description>", escape_xml(&entry.content_html));
+            xml.push_str("    </item>\n");
+        }
+
+        xml.push_str("  </channel>\n</rss>\n");
+        xml
+    }
+
+    fn to_json(&self) -> String {
+        let feed = JsonFeed {
+            version: "https://jsonfeed.org/version/1.1",
+            title: &self.title,
+            home_page_url: &self.home_page_url,
+            feed_url: &self.feed_url,
+            items: self
+                .entries
+                .iter()
+                .map(|entry| JsonFeedItem {
+                    id: format!("urn:uuid:{}", entry.id),
+                    url: &entry.url,
+                    title: &entry.title,
+                    content_html: &entry.content_html,
+                    summary: &entry.summary,
+                    date_published: entry.published.to_rfc3339(),
+                    date_modified: entry.updated.to_rfc3339(),
+                    authors: vec![JsonFeedAuthor { name: &entry.author }],
+                    tags: &entry.tags,
+                })
+                .collect(),
+        };
+
+        serde_json::to_string(&feed).unwrap_or_default()
+    }
+}
+
+/// JSON Feed 1.1 document, see https://jsonfeed.org/version/1.1
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: &'a str,
+    feed_url: &'a str,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: String,
+    url: &'a str,
+    title: &'a str,
+    content_html: &'a str,
+    summary: &'a str,
+    date_published: String,
+    date_modified: String,
+    authors: Vec<JsonFeedAuthor<'a>>,
+    tags: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonFeedAuthor<'a> {
+    name: &'a str,
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
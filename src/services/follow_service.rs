@@ -0,0 +1,31 @@
+// Follow service định nghĩa cursor phân trang cho feed cá nhân
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Posts per page of the personal feed
+pub const DEFAULT_FEED_PAGE: u64 = 10;
+pub const MAX_FEED_PAGE: u64 = 100;
+
+/// Position in the personal feed: the publication time and id of the last
+/// post of a page. Serialized as `<unix microseconds>_<post id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub published_at: DateTime<Utc>,
+    pub post_id: Uuid,
+}
+
+impl FeedCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.published_at.timestamp_micros(), self.post_id)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let (micros, post_id) = value.split_once('_')?;
+
+        Some(Self {
+            published_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            post_id: post_id.parse().ok()?,
+        })
+    }
+}
@@ -1,3 +1,18 @@
 pub mod user_service;
+pub mod attachment_service;
+pub mod collection_service;
+pub mod comment_service;
+pub mod event_service;
+pub mod feed_service;
+pub mod follow_service;
+pub mod moderation_service;
+pub mod notification_service;
 pub mod post_service;
-pub mod production_service;
\ No newline at end of file
+pub mod production_service;
+pub mod reaction_service;
+pub mod render_service;
+pub mod revision_service;
+pub mod scheduler_service;
+pub mod slug_service;
+pub mod tag_service;
+pub mod trash_service;
\ No newline at end of file
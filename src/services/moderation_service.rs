@@ -0,0 +1,347 @@
+// Moderation service ghi nhận report, tự động ẩn nội dung bị report nhiều và lưu lịch sử xử lý
+
+use std::fmt;
+
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::services::comment_service;
+
+/// Why a user flags a post or comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportReason {
+    Spam,
+    Inappropriate,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 2] = [ReportReason::Spam, ReportReason::Inappropriate];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportReason::Spam => "spam",
+            ReportReason::Inappropriate => "inappropriate",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| reason.as_str() == value)
+    }
+}
+
+impl fmt::Display for ReportReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a moderator does with a reported post or comment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationAction {
+    /// Closes the reports as unfounded and shows the item again if hidden
+    Dismiss,
+    Hide,
+    Delete,
+    /// Leaves the item as it is and records a warning for its author
+    Warn,
+}
+
+impl ModerationAction {
+    pub const ALL: [ModerationAction; 4] = [
+        ModerationAction::Dismiss,
+        ModerationAction::Hide,
+        ModerationAction::Delete,
+        ModerationAction::Warn,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationAction::Dismiss => "dismiss",
+            ModerationAction::Hide => "hide",
+            ModerationAction::Delete => "delete",
+            ModerationAction::Warn => "warn",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+
+    /// Resolution recorded on the open reports of the item
+    fn resolution(&self) -> &'static str {
+        match self {
+            ModerationAction::Dismiss => "dismissed",
+            _ => "actioned",
+        }
+    }
+}
+
+impl fmt::Display for ModerationAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Audit trail entry of a hide triggered by reports
+const AUTO_HIDE: &str = "auto_hide";
+
+/// Reported post or comment, by internal and public id
+#[derive(Debug, Clone, Copy)]
+pub enum ReportTarget {
+    Post { id: i32, public_id: Uuid },
+    Comment { id: i32, post_id: i32, public_id: Uuid },
+}
+
+impl ReportTarget {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ReportTarget::Post { .. } => "post",
+            ReportTarget::Comment { .. } => "comment",
+        }
+    }
+
+    pub fn public_id(&self) -> Uuid {
+        match self {
+            ReportTarget::Post { public_id, .. } | ReportTarget::Comment { public_id, .. } => *public_id,
+        }
+    }
+
+    fn post_id(&self) -> i32 {
+        match self {
+            ReportTarget::Post { id, .. } => *id,
+            ReportTarget::Comment { post_id, .. } => *post_id,
+        }
+    }
+
+    fn comment_id(&self) -> Option<i32> {
+        match self {
+            ReportTarget::Post { .. } => None,
+            ReportTarget::Comment { id, .. } => Some(*id),
+        }
+    }
+}
+
+/// Report as stored
+#[derive(Debug)]
+pub struct FiledReport {
+    pub public_id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Records a report and hides the item once `auto_hide_threshold` users
+/// have open reports on it (0 disables hiding). Returns `None` when the
+/// reporter already has an open report on the item.
+///
+/// Locks the reported row, so concurrent reports are counted one at a time.
+pub async fn file_report(
+    conn: &mut PgConnection,
+    target: ReportTarget,
+    reporter_id: i32,
+    reason: ReportReason,
+    details: Option<&str>,
+    auto_hide_threshold: u32,
+) -> Result<Option<FiledReport>, sqlx::Error> {
+    let (is_hidden, author_id) = match target {
+        ReportTarget::Post { id, .. } => {
+            let row = sqlx::query!(
+                r#"SELECT hidden_at IS NOT NULL AS "is_hidden!", user_id FROM posts WHERE id = $1 FOR UPDATE"#,
+                id
+            )
+            .fetch_one(&mut *conn)
+            .await?;
+            (row.is_hidden, row.user_id)
+        }
+        ReportTarget::Comment { id, .. } => {
+            let row = sqlx::query!("SELECT is_hidden, user_id FROM comments WHERE id = $1 FOR UPDATE", id)
+                .fetch_one(&mut *conn)
+                .await?;
+            (row.is_hidden, row.user_id)
+        }
+    };
+
+    let report = match target {
+        ReportTarget::Post { id, .. } => {
+            sqlx::query!(
+                "INSERT INTO reports (post_id, reporter_id, reason, details)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (post_id, reporter_id) WHERE comment_id IS NULL AND resolved_at IS NULL
+                 DO NOTHING
+                 RETURNING public_id, created_at",
+                id,
+                reporter_id,
+                reason.as_str(),
+                details
+            )
+            .fetch_optional(&mut *conn)
+            .await?
+            .map(|row| (row.public_id, row.created_at))
+        }
+        ReportTarget::Comment { id, post_id, .. } => {
+            sqlx::query!(
+                "INSERT INTO reports (post_id, comment_id, reporter_id, reason, details)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (comment_id, reporter_id) WHERE comment_id IS NOT NULL AND resolved_at IS NULL
+                 DO NOTHING
+                 RETURNING public_id, created_at",
+                post_id,
+                id,
+                reporter_id,
+                reason.as_str(),
+                details
+            )
+            .fetch_optional(&mut *conn)
+            .await?
+            .map(|row| (row.public_id, row.created_at))
+        }
+    };
+
+    let Some((public_id, created_at)) = report else {
+        return Ok(None);
+    };
+
+    if auto_hide_threshold > 0 && !is_hidden {
+        let open_reports = open_report_count(conn, target).await?;
+
+        if open_reports >= auto_hide_threshold as i64 {
+            set_hidden(conn, target, true, None).await?;
+            record_action(
+                conn,
+                target,
+                Some(author_id),
+                None,
+                AUTO_HIDE,
+                Some(&format!("Hidden after {} reports", open_reports)),
+                0,
+            )
+            .await?;
+            tracing::info!("Hid {} {} after {} reports", target.kind(), target.public_id(), open_reports);
+        }
+    }
+
+    Ok(Some(FiledReport {
+        public_id,
+        created_at,
+    }))
+}
+
+/// Applies a moderator's decision to a reported item, closes its open
+/// reports and records the action. Returns the id of the audit trail entry.
+pub async fn take_action(
+    conn: &mut PgConnection,
+    target: ReportTarget,
+    author_id: Option<i32>,
+    moderator_id: i32,
+    action: ModerationAction,
+    note: Option<&str>,
+) -> Result<i32, sqlx::Error> {
+    // Counted before a comment that is removed outright takes its reports
+    // with it
+    let resolved = sqlx::query!(
+        "UPDATE reports SET resolution = $3, resolved_at = NOW()
+         WHERE post_id = $1 AND comment_id IS NOT DISTINCT FROM $2 AND resolved_at IS NULL",
+        target.post_id(),
+        target.comment_id(),
+        action.resolution()
+    )
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    match action {
+        ModerationAction::Dismiss => {
+            set_hidden(conn, target, false, Some(moderator_id)).await?;
+        }
+        ModerationAction::Hide => {
+            set_hidden(conn, target, true, Some(moderator_id)).await?;
+        }
+        ModerationAction::Delete => match target {
+            ReportTarget::Post { id, .. } => {
+                sqlx::query!(
+                    "UPDATE posts SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+                    id
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            ReportTarget::Comment { id, .. } => {
+                comment_service::delete(conn, id).await?;
+            }
+        },
+        ModerationAction::Warn => {}
+    }
+
+    record_action(
+        conn,
+        target,
+        author_id,
+        Some(moderator_id),
+        action.as_str(),
+        note,
+        resolved as i32,
+    )
+    .await
+}
+
+async fn open_report_count(conn: &mut PgConnection, target: ReportTarget) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM reports
+        WHERE post_id = $1 AND comment_id IS NOT DISTINCT FROM $2 AND resolved_at IS NULL
+        "#,
+        target.post_id(),
+        target.comment_id()
+    )
+    .fetch_one(conn)
+    .await
+}
+
+async fn set_hidden(
+    conn: &mut PgConnection,
+    target: ReportTarget,
+    hide: bool,
+    moderator_id: Option<i32>,
+) -> Result<bool, sqlx::Error> {
+    match target {
+        ReportTarget::Post { id, .. } => {
+            // Hiding changes whether the post counts as published, so it is a
+            // new version of the post
+            let result = sqlx::query!(
+                "UPDATE posts
+                 SET hidden_at = CASE WHEN $2 THEN NOW() END, updated_at = NOW()
+                 WHERE id = $1 AND (hidden_at IS NOT NULL) <> $2",
+                id,
+                hide
+            )
+            .execute(conn)
+            .await?;
+            Ok(result.rows_affected() > 0)
+        }
+        ReportTarget::Comment { id, .. } => comment_service::set_hidden(conn, id, hide, moderator_id).await,
+    }
+}
+
+async fn record_action(
+    conn: &mut PgConnection,
+    target: ReportTarget,
+    author_id: Option<i32>,
+    moderator_id: Option<i32>,
+    action: &str,
+    note: Option<&str>,
+    reports_resolved: i32,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar!(
+        "INSERT INTO moderation_actions
+             (target_type, target_id, author_id, moderator_id, action, note, reports_resolved)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id",
+        target.kind(),
+        target.public_id(),
+        author_id,
+        moderator_id,
+        action,
+        note,
+        reports_resolved
+    )
+    .fetch_one(conn)
+    .await
+}
@@ -0,0 +1,416 @@
+// Notification service tạo thông báo cho comment, mention và review, áp dụng tuỳ chọn của người nhận và gửi email digest
+
+use std::{collections::BTreeMap, fmt, sync::Arc, time::Duration};
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+    auth::ROLE_ADMIN,
+    database::models::Post,
+    mail::{Email, Mailer},
+    services::post_service::PostStatus,
+};
+
+/// Event a user is notified about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// Someone commented on the user's post
+    Comment,
+    /// Someone wrote `@username` in a comment or a published post
+    Mention,
+    /// An admin approved or rejected the user's post
+    Review,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 3] = [
+        NotificationKind::Comment,
+        NotificationKind::Mention,
+        NotificationKind::Review,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Comment => "comment",
+            NotificationKind::Mention => "mention",
+            NotificationKind::Review => "review",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+impl fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Users notified by a single comment or post at most
+pub const MAX_MENTIONS: usize = 20;
+
+/// Lowercased usernames written as `@username`, in order of appearance.
+/// An `@` right after a letter or digit, as in an email address, is not a
+/// mention.
+pub fn extract_mentions(text: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let starts_mention = c == '@' && !previous.is_some_and(|p| p.is_alphanumeric() || p == '_');
+        previous = Some(c);
+        if !starts_mention {
+            continue;
+        }
+
+        let start = index + c.len_utf8();
+        let mut end = start;
+        while let Some(&(next_index, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || matches!(next, '_' | '.' | '-')) {
+                break;
+            }
+            end = next_index + next.len_utf8();
+            previous = Some(next);
+            chars.next();
+        }
+
+        // Punctuation ending a sentence is not part of the name
+        let name = text[start..end].trim_end_matches(['.', '-']).to_lowercase();
+        if !name.is_empty() && !mentions.contains(&name) {
+            mentions.push(name);
+            if mentions.len() == MAX_MENTIONS {
+                break;
+            }
+        }
+    }
+
+    mentions
+}
+
+/// Notifies the post's author and the users mentioned in a new comment.
+/// A mentioned author gets the mention only.
+pub async fn comment_created(
+    conn: &mut PgConnection,
+    post_id: i32,
+    comment_id: i32,
+    commenter_id: i32,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    let mentioned = mentioned_users(conn, content, post_id, commenter_id).await?;
+    deliver(
+        conn,
+        &mentioned,
+        Some(commenter_id),
+        NotificationKind::Mention,
+        post_id,
+        Some(comment_id),
+        None,
+        None,
+    )
+    .await?;
+
+    let author_id = sqlx::query_scalar!("SELECT user_id FROM posts WHERE id = $1", post_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if author_id != commenter_id && !mentioned.contains(&author_id) {
+        deliver(
+            conn,
+            &[author_id],
+            Some(commenter_id),
+            NotificationKind::Comment,
+            post_id,
+            Some(comment_id),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Notifies the author when an admin reviews their post and the users
+/// mentioned in a post once it is published. `actor_id` is `None` for
+/// scheduled publication.
+pub async fn post_transitioned(
+    conn: &mut PgConnection,
+    post: &Post,
+    from: PostStatus,
+    to: PostStatus,
+    actor_id: Option<i32>,
+    comment: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let outcome = match (from, to) {
+        (PostStatus::InReview, PostStatus::Approved) => Some("approved"),
+        (PostStatus::InReview, PostStatus::Draft) => Some("rejected"),
+        _ => None,
+    };
+
+    if let Some(outcome) = outcome {
+        if actor_id != Some(post.user_id) {
+            deliver(
+                conn,
+                &[post.user_id],
+                actor_id,
+                NotificationKind::Review,
+                post.id,
+                None,
+                Some(outcome),
+                comment,
+            )
+            .await?;
+        }
+    }
+
+    if to == PostStatus::Published {
+        post_published(conn, post.id, post.user_id, &format!("{}\n{}", post.title, post.content)).await?;
+    }
+
+    Ok(())
+}
+
+/// Notifies the users mentioned in a post that was just published
+pub async fn post_published(
+    conn: &mut PgConnection,
+    post_id: i32,
+    author_id: i32,
+    text: &str,
+) -> Result<(), sqlx::Error> {
+    let mentioned = mentioned_users(conn, text, post_id, author_id).await?;
+    deliver(
+        conn,
+        &mentioned,
+        Some(author_id),
+        NotificationKind::Mention,
+        post_id,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Existing users mentioned in `text` who can read the post, apart from
+/// the writer
+async fn mentioned_users(
+    conn: &mut PgConnection,
+    text: &str,
+    post_id: i32,
+    writer_id: i32,
+) -> Result<Vec<i32>, sqlx::Error> {
+    let names = extract_mentions(text);
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        SELECT u.id
+        FROM users u
+        JOIN posts p ON p.id = $2
+        WHERE LOWER(u.username) = ANY($1)
+          AND u.id <> $3
+          AND u.is_active AND u.deleted_at IS NULL
+          AND (p.is_published OR u.id = p.user_id OR u.role = $4)
+        ORDER BY u.id
+        "#,
+        &names,
+        post_id,
+        writer_id,
+        ROLE_ADMIN
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// Stores a notification for each recipient, delivered the way their
+/// preferences for `kind` say. Recipients who turned the kind off entirely
+/// get nothing.
+#[allow(clippy::too_many_arguments)]
+async fn deliver(
+    conn: &mut PgConnection,
+    recipients: &[i32],
+    actor_id: Option<i32>,
+    kind: NotificationKind,
+    post_id: i32,
+    comment_id: Option<i32>,
+    detail: Option<&str>,
+    note: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    if recipients.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO notifications
+            (user_id, actor_id, kind, post_id, comment_id, detail, note, in_app, email_digest)
+        SELECT r.user_id, $2, $3, $4, $5, $6, $7,
+               COALESCE(np.in_app, TRUE), COALESCE(np.email_digest, FALSE)
+        FROM UNNEST($1::int4[]) AS r(user_id)
+        LEFT JOIN notification_preferences np ON np.user_id = r.user_id AND np.kind = $3
+        WHERE COALESCE(np.in_app, TRUE) OR COALESCE(np.email_digest, FALSE)
+        "#,
+        recipients,
+        actor_id,
+        kind.as_str(),
+        post_id,
+        comment_id,
+        detail,
+        note
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Notifications waiting for the next email digest
+struct DigestItem {
+    id: i32,
+    user_id: i32,
+    email: String,
+    username: String,
+    kind: String,
+    actor_username: Option<String>,
+    post_title: String,
+    detail: Option<String>,
+}
+
+/// Gathers notifications that users want by email into one digest per
+/// user every `interval_seconds` and sends them through `mailer`.
+pub async fn send_email_digests(db: PgPool, mailer: Arc<dyn Mailer>, interval_seconds: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+    // The first tick completes immediately
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = send_due_digests(&db, mailer.as_ref()).await {
+            tracing::error!("Failed to send email digests: {}", e);
+        }
+    }
+}
+
+/// Sends the pending digests. Rows are claimed with `FOR UPDATE SKIP
+/// LOCKED`, so each one goes into a single digest however many instances
+/// run the loop, and are only marked emailed once the mailer accepted
+/// their digest. A digest that fails is retried on the next run.
+async fn send_due_digests(db: &PgPool, mailer: &dyn Mailer) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let items = sqlx::query_as!(
+        DigestItem,
+        r#"
+        SELECT n.id, n.user_id, u.email, u.username, n.kind,
+               a.username AS "actor_username?", p.title AS post_title, n.detail
+        FROM notifications n
+        JOIN users u ON u.id = n.user_id
+        JOIN posts p ON p.id = n.post_id
+        LEFT JOIN users a ON a.id = n.actor_id
+        WHERE n.email_digest AND n.emailed_at IS NULL
+          AND u.is_active AND u.deleted_at IS NULL
+        ORDER BY n.created_at, n.id
+        LIMIT 1000
+        FOR UPDATE OF n SKIP LOCKED
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut digests: BTreeMap<i32, Vec<DigestItem>> = BTreeMap::new();
+    for item in items {
+        digests.entry(item.user_id).or_default().push(item);
+    }
+
+    for (user_id, items) in digests {
+        if let Err(e) = mailer.send(digest_email(&items)).await {
+            tracing::warn!("Failed to send email digest to user {}: {}", user_id, e);
+            continue;
+        }
+
+        let ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+        sqlx::query!("UPDATE notifications SET emailed_at = NOW() WHERE id = ANY($1)", &ids)
+            .execute(&mut *tx)
+            .await?;
+        tracing::info!("Sent email digest of {} notifications to user {}", items.len(), user_id);
+    }
+
+    tx.commit().await
+}
+
+/// Digest of the notifications of one user
+fn digest_email(items: &[DigestItem]) -> Email {
+    let subject = match items.len() {
+        1 => "1 new notification".to_string(),
+        count => format!("{} new notifications", count),
+    };
+    let lines: Vec<String> = items.iter().map(digest_line).collect();
+
+    Email {
+        to: items[0].email.clone(),
+        subject,
+        body: format!("Hi {},\n\n{}\n", items[0].username, lines.join("\n")),
+    }
+}
+
+fn digest_line(item: &DigestItem) -> String {
+    let actor = item.actor_username.as_deref().unwrap_or("Someone");
+    match NotificationKind::parse(&item.kind) {
+        Some(NotificationKind::Comment) => format!("- {} commented on \"{}\"", actor, item.post_title),
+        Some(NotificationKind::Mention) => format!("- {} mentioned you in \"{}\"", actor, item.post_title),
+        Some(NotificationKind::Review) => format!(
+            "- {} {} \"{}\"",
+            actor,
+            item.detail.as_deref().unwrap_or("reviewed"),
+            item.post_title
+        ),
+        None => format!("- {} on \"{}\"", item.kind, item.post_title),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: &str, actor: Option<&str>, detail: Option<&str>) -> DigestItem {
+        DigestItem {
+            id: 1,
+            user_id: 7,
+            email: "reader@example.com".to_string(),
+            username: "reader".to_string(),
+            kind: kind.to_string(),
+            actor_username: actor.map(str::to_string),
+            post_title: "Post".to_string(),
+            detail: detail.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn digest_lists_every_notification() {
+        let email = digest_email(&[
+            item("comment", Some("ann"), None),
+            item("mention", None, None),
+            item("review", Some("admin"), Some("approved")),
+        ]);
+
+        assert_eq!(email.to, "reader@example.com");
+        assert_eq!(email.subject, "3 new notifications");
+        assert_eq!(
+            email.body,
+            "Hi reader,\n\n\
+             - ann commented on \"Post\"\n\
+             - Someone mentioned you in \"Post\"\n\
+             - admin approved \"Post\"\n"
+        );
+    }
+
+    #[test]
+    fn digest_of_one_notification_is_singular() {
+        assert_eq!(digest_email(&[item("comment", None, None)]).subject, "1 new notification");
+    }
+}
@@ -1,9 +1,464 @@
-// Post service sẽ chứa business logic cho posts
+// Post service chứa business logic cho posts: editorial workflow
+
+use std::fmt;
+
+use sqlx::PgConnection;
+
+use crate::{database::models::Post, services::notification_service};
+
+/// Editorial state of a post
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostStatus {
+    Draft,
+    InReview,
+    Approved,
+    Published,
+    Archived,
+}
+
+impl PostStatus {
+    pub const ALL: [PostStatus; 5] = [
+        PostStatus::Draft,
+        PostStatus::InReview,
+        PostStatus::Approved,
+        PostStatus::Published,
+        PostStatus::Archived,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::InReview => "in_review",
+            PostStatus::Approved => "approved",
+            PostStatus::Published => "published",
+            PostStatus::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == value)
+    }
+}
+
+impl fmt::Display for PostStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Workflow step requested by a caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostAction {
+    Submit,
+    Approve,
+    Reject,
+    Publish,
+    Unpublish,
+    Archive,
+}
+
+impl PostAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostAction::Submit => "submit",
+            PostAction::Approve => "approve",
+            PostAction::Reject => "reject",
+            PostAction::Publish => "publish",
+            PostAction::Unpublish => "unpublish",
+            PostAction::Archive => "archive",
+        }
+    }
+}
+
+/// Posts a bulk operation may touch at once
+pub const MAX_BULK_POSTS: usize = 500;
+
+/// Change applied to many posts by a bulk operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkPostAction {
+    /// Publishes approved posts
+    Publish,
+    /// Takes published posts back to approved
+    Unpublish,
+    /// Adds and removes tags
+    Retag,
+    /// Moves posts to the trash
+    Delete,
+}
+
+impl BulkPostAction {
+    pub const ALL: [BulkPostAction; 4] = [
+        BulkPostAction::Publish,
+        BulkPostAction::Unpublish,
+        BulkPostAction::Retag,
+        BulkPostAction::Delete,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BulkPostAction::Publish => "publish",
+            BulkPostAction::Unpublish => "unpublish",
+            BulkPostAction::Retag => "retag",
+            BulkPostAction::Delete => "delete",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_str() == value)
+    }
+}
+
+impl fmt::Display for BulkPostAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who may perform a transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Actor {
+    Author,
+    Admin,
+    AuthorOrAdmin,
+}
+
+struct Transition {
+    action: PostAction,
+    from: PostStatus,
+    to: PostStatus,
+    actor: Actor,
+}
+
+const TRANSITIONS: &[Transition] = &[
+    Transition {
+        action: PostAction::Submit,
+        from: PostStatus::Draft,
+        to: PostStatus::InReview,
+        actor: Actor::Author,
+    },
+    Transition {
+        action: PostAction::Approve,
+        from: PostStatus::InReview,
+        to: PostStatus::Approved,
+        actor: Actor::Admin,
+    },
+    Transition {
+        action: PostAction::Reject,
+        from: PostStatus::InReview,
+        to: PostStatus::Draft,
+        actor: Actor::Admin,
+    },
+    Transition {
+        action: PostAction::Publish,
+        from: PostStatus::Approved,
+        to: PostStatus::Published,
+        actor: Actor::AuthorOrAdmin,
+    },
+    Transition {
+        action: PostAction::Unpublish,
+        from: PostStatus::Published,
+        to: PostStatus::Approved,
+        actor: Actor::AuthorOrAdmin,
+    },
+    Transition {
+        action: PostAction::Archive,
+        from: PostStatus::Draft,
+        to: PostStatus::Archived,
+        actor: Actor::AuthorOrAdmin,
+    },
+    Transition {
+        action: PostAction::Archive,
+        from: PostStatus::Approved,
+        to: PostStatus::Archived,
+        actor: Actor::AuthorOrAdmin,
+    },
+    Transition {
+        action: PostAction::Archive,
+        from: PostStatus::Published,
+        to: PostStatus::Archived,
+        actor: Actor::AuthorOrAdmin,
+    },
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransitionError {
+    /// The action does not apply to a post in this status
+    InvalidTransition { from: PostStatus, action: PostAction },
+    /// The caller's role may not perform the action
+    Forbidden,
+}
 
 pub struct PostService;
 
 impl PostService {
-    pub fn new() -> Self {
-        Self
+    /// Looks up the status an action leads to from `from`, checking that
+    /// the caller may perform it
+    pub fn transition(
+        action: PostAction,
+        from: PostStatus,
+        is_author: bool,
+        is_admin: bool,
+    ) -> Result<PostStatus, TransitionError> {
+        let transition = TRANSITIONS
+            .iter()
+            .find(|transition| transition.action == action && transition.from == from)
+            .ok_or(TransitionError::InvalidTransition { from, action })?;
+
+        let allowed = match transition.actor {
+            Actor::Author => is_author,
+            Actor::Admin => is_admin,
+            Actor::AuthorOrAdmin => is_author || is_admin,
+        };
+
+        if allowed {
+            Ok(transition.to)
+        } else {
+            Err(TransitionError::Forbidden)
+        }
+    }
+
+    /// Whether the caller may change the title or content of a post in
+    /// `status`. Authors edit their drafts; once a post is submitted only
+    /// admins may, so what gets published is what was reviewed.
+    pub fn may_edit_content(status: PostStatus, is_author: bool, is_admin: bool) -> bool {
+        match status {
+            PostStatus::Draft => is_author || is_admin,
+            _ => is_admin,
+        }
+    }
+
+    /// Loads the posts with `ids` as `PostResponse` shows them, in the order
+    /// of `ids`, with the reaction and bookmark of `viewer`. This is the one
+    /// query that knows how a post is put together: other queries pick the
+    /// ids of the posts they need and load them here. Trashed and
+    /// unpublished posts are loaded too, so visibility is up to the caller.
+    pub async fn find_posts(
+        conn: &mut PgConnection,
+        ids: &[i32],
+        viewer: Option<i32>,
+    ) -> Result<Vec<Post>, sqlx::Error> {
+        sqlx::query_as!(
+            Post,
+            r#"
+            SELECT p.id, p.public_id, p.slug, p.title, p.content, p.content_format, p.content_html,
+                   ARRAY(SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                         WHERE pt.post_id = p.id ORDER BY t.name) AS "tags!",
+                   p.comment_count,
+                   p.reaction_counts, p.bookmark_count,
+                   (SELECT r.kind FROM post_reactions r
+                    WHERE r.post_id = p.id AND r.user_id = $2) AS my_reaction,
+                   EXISTS (SELECT 1 FROM post_bookmarks b
+                           WHERE b.post_id = p.id AND b.user_id = $2) AS "is_bookmarked!",
+                   COALESCE((SELECT jsonb_agg(a ORDER BY a.id) FROM attachments a
+                             WHERE a.post_id = p.id), '[]') AS "attachments!",
+                   p.user_id, u.public_id AS user_public_id,
+                   p.status, p.is_published, p.published_at, p.publish_at, p.created_at, p.updated_at
+            FROM UNNEST($1::int4[]) WITH ORDINALITY AS listed (id, position)
+            JOIN posts p ON p.id = listed.id
+            JOIN users u ON u.id = p.user_id
+            ORDER BY listed.position
+            "#,
+            ids,
+            viewer
+        )
+        .fetch_all(&mut *conn)
+        .await
+    }
+
+    /// Loads one post like `find_posts`, `None` when it does not exist
+    pub async fn find_post(
+        conn: &mut PgConnection,
+        id: i32,
+        viewer: Option<i32>,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        Ok(Self::find_posts(conn, &[id], viewer).await?.pop())
+    }
+
+    /// Moves a post from `from` to `to`, records the change in its history
+    /// and notifies the users concerned. Returns `None` when the post is no
+    /// longer in `from`.
+    pub async fn apply_transition(
+        conn: &mut PgConnection,
+        post_id: i32,
+        from: PostStatus,
+        to: PostStatus,
+        actor_id: i32,
+        comment: Option<&str>,
+    ) -> Result<Option<Post>, sqlx::Error> {
+        let post_id = sqlx::query_scalar!(
+            r#"
+            WITH updated AS (
+                UPDATE posts
+                SET status = $3::varchar,
+                    published_at = CASE WHEN $3::varchar = 'published' THEN NOW() ELSE published_at END,
+                    publish_at = CASE WHEN $3::varchar IN ('published', 'archived') THEN NULL ELSE publish_at END,
+                    updated_at = NOW()
+                WHERE id = $1 AND status = $2::varchar AND deleted_at IS NULL
+                RETURNING id
+            ), history AS (
+                INSERT INTO post_status_history (post_id, from_status, to_status, actor_id, comment)
+                SELECT id, $2::varchar, $3::varchar, $4, $5 FROM updated
+            )
+            SELECT id AS "id!" FROM updated
+            "#,
+            post_id,
+            from.as_str(),
+            to.as_str(),
+            actor_id,
+            comment
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let post = match post_id {
+            Some(post_id) => Self::find_post(conn, post_id, Some(actor_id)).await?,
+            None => None,
+        };
+
+        if let Some(post) = &post {
+            notification_service::post_transitioned(conn, post, from, to, Some(actor_id), comment).await?;
+        }
+
+        Ok(post)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIONS: [PostAction; 6] = [
+        PostAction::Submit,
+        PostAction::Approve,
+        PostAction::Reject,
+        PostAction::Publish,
+        PostAction::Unpublish,
+        PostAction::Archive,
+    ];
+
+    /// (is_author, is_admin) of every kind of caller
+    const CALLERS: [(bool, bool); 4] = [(false, false), (true, false), (false, true), (true, true)];
+
+    /// The workflow as documented: the status an action leads to and who
+    /// may perform it
+    fn expected(action: PostAction, from: PostStatus) -> Option<(PostStatus, Actor)> {
+        use PostStatus::*;
+
+        match (action, from) {
+            (PostAction::Submit, Draft) => Some((InReview, Actor::Author)),
+            (PostAction::Approve, InReview) => Some((Approved, Actor::Admin)),
+            (PostAction::Reject, InReview) => Some((Draft, Actor::Admin)),
+            (PostAction::Publish, Approved) => Some((Published, Actor::AuthorOrAdmin)),
+            (PostAction::Unpublish, Published) => Some((Approved, Actor::AuthorOrAdmin)),
+            (PostAction::Archive, Draft | Approved | Published) => {
+                Some((Archived, Actor::AuthorOrAdmin))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn every_transition_follows_the_workflow() {
+        for action in ACTIONS {
+            for from in PostStatus::ALL {
+                for (is_author, is_admin) in CALLERS {
+                    let result = PostService::transition(action, from, is_author, is_admin);
+                    let expected = match expected(action, from) {
+                        None => Err(TransitionError::InvalidTransition { from, action }),
+                        Some((to, actor)) => {
+                            let allowed = match actor {
+                                Actor::Author => is_author,
+                                Actor::Admin => is_admin,
+                                Actor::AuthorOrAdmin => is_author || is_admin,
+                            };
+                            if allowed {
+                                Ok(to)
+                            } else {
+                                Err(TransitionError::Forbidden)
+                            }
+                        }
+                    };
+
+                    assert_eq!(
+                        result, expected,
+                        "{} from {} by author={} admin={}",
+                        action.as_str(), from, is_author, is_admin
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_author_submits() {
+        assert_eq!(
+            PostService::transition(PostAction::Submit, PostStatus::Draft, true, false),
+            Ok(PostStatus::InReview)
+        );
+        assert_eq!(
+            PostService::transition(PostAction::Submit, PostStatus::Draft, false, true),
+            Err(TransitionError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn authors_cannot_review_their_own_posts() {
+        for action in [PostAction::Approve, PostAction::Reject] {
+            assert_eq!(
+                PostService::transition(action, PostStatus::InReview, true, false),
+                Err(TransitionError::Forbidden)
+            );
+        }
+    }
+
+    #[test]
+    fn unreviewed_posts_cannot_be_published() {
+        for from in [PostStatus::Draft, PostStatus::InReview] {
+            assert_eq!(
+                PostService::transition(PostAction::Publish, from, true, true),
+                Err(TransitionError::InvalidTransition { from, action: PostAction::Publish })
+            );
+        }
+    }
+
+    #[test]
+    fn unpublished_posts_can_be_published_again() {
+        let to = PostService::transition(PostAction::Unpublish, PostStatus::Published, true, false);
+        assert_eq!(to, Ok(PostStatus::Approved));
+        assert_eq!(
+            PostService::transition(PostAction::Publish, to.unwrap(), true, false),
+            Ok(PostStatus::Published)
+        );
+    }
+
+    #[test]
+    fn archived_posts_are_final() {
+        for action in ACTIONS {
+            assert!(PostService::transition(action, PostStatus::Archived, true, true).is_err());
+        }
+    }
+
+    #[test]
+    fn invalid_transitions_win_over_forbidden_ones() {
+        assert_eq!(
+            PostService::transition(PostAction::Approve, PostStatus::Published, false, false),
+            Err(TransitionError::InvalidTransition {
+                from: PostStatus::Published,
+                action: PostAction::Approve
+            })
+        );
+    }
+
+    #[test]
+    fn only_drafts_are_editable_by_their_author() {
+        for status in PostStatus::ALL {
+            assert_eq!(
+                PostService::may_edit_content(status, true, false),
+                status == PostStatus::Draft,
+                "{status}"
+            );
+            assert!(PostService::may_edit_content(status, false, true), "{status}");
+            assert!(!PostService::may_edit_content(status, false, false), "{status}");
+        }
     }
-}
\ No newline at end of file
+}
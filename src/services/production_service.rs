@@ -1,7 +1,9 @@
 // Production service sẽ chứa business logic cho production
 
+#[allow(dead_code)]
 pub struct ProductionService;
 
+#[allow(dead_code)]
 impl ProductionService {
     pub fn new() -> Self {
         Self
@@ -0,0 +1,121 @@
+// Reaction service ghi nhận reaction của user và cập nhật bộ đếm trên post
+
+use std::fmt;
+
+use sqlx::PgConnection;
+
+/// Reaction a user can leave on a post
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionKind {
+    Like,
+    Love,
+    Insightful,
+    Funny,
+    Celebrate,
+}
+
+impl ReactionKind {
+    pub const ALL: [ReactionKind; 5] = [
+        ReactionKind::Like,
+        ReactionKind::Love,
+        ReactionKind::Insightful,
+        ReactionKind::Funny,
+        ReactionKind::Celebrate,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionKind::Like => "like",
+            ReactionKind::Love => "love",
+            ReactionKind::Insightful => "insightful",
+            ReactionKind::Funny => "funny",
+            ReactionKind::Celebrate => "celebrate",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+impl fmt::Display for ReactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Sets (`Some`) or removes (`None`) a user's reaction to a post and moves
+/// the post's counters accordingly. Returns the counters after the change.
+///
+/// Locks the post row, so concurrent changes to the same post are applied
+/// one after the other. Run it inside a transaction.
+pub async fn set_reaction(
+    conn: &mut PgConnection,
+    post_id: i32,
+    user_id: i32,
+    kind: Option<ReactionKind>,
+) -> Result<serde_json::Value, sqlx::Error> {
+    let counts = sqlx::query_scalar!(
+        "SELECT reaction_counts FROM posts WHERE id = $1 FOR NO KEY UPDATE",
+        post_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let previous = sqlx::query_scalar!(
+        "SELECT kind FROM post_reactions WHERE post_id = $1 AND user_id = $2",
+        post_id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let kind = kind.map(|kind| kind.as_str());
+    if previous.as_deref() == kind {
+        return Ok(counts);
+    }
+
+    match kind {
+        Some(kind) => {
+            sqlx::query!(
+                "INSERT INTO post_reactions (post_id, user_id, kind)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (post_id, user_id) DO UPDATE SET kind = EXCLUDED.kind, created_at = NOW()",
+                post_id,
+                user_id,
+                kind
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        None => {
+            sqlx::query!(
+                "DELETE FROM post_reactions WHERE post_id = $1 AND user_id = $2",
+                post_id,
+                user_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        UPDATE posts
+        SET reaction_counts = reaction_counts
+            || CASE WHEN $3::text IS NULL THEN '{}'::jsonb
+                    ELSE jsonb_build_object($3::text, COALESCE((reaction_counts ->> $3::text)::int, 0) - 1)
+               END
+            || CASE WHEN $2::text IS NULL THEN '{}'::jsonb
+                    ELSE jsonb_build_object($2::text, COALESCE((reaction_counts ->> $2::text)::int, 0) + 1)
+               END
+        WHERE id = $1
+        RETURNING reaction_counts
+        "#,
+        post_id,
+        kind,
+        previous
+    )
+    .fetch_one(conn)
+    .await
+}
@@ -0,0 +1,436 @@
+// Render service chuyển content của post sang HTML an toàn
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+use pulldown_cmark::{html, Event, Options, Parser, TagEnd};
+use sqlx::{PgConnection, PgPool};
+
+use crate::database::models::Post;
+
+const EXCERPT_CHARS: usize = 200;
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Markup language of a post's content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentFormat {
+    Plain,
+    Markdown,
+}
+
+impl ContentFormat {
+    pub const ALL: [ContentFormat; 2] = [ContentFormat::Plain, ContentFormat::Markdown];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Plain => "plain",
+            ContentFormat::Markdown => "markdown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str() == value)
+    }
+}
+
+impl fmt::Display for ContentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH
+}
+
+/// Whitelist of the HTML markdown may produce: tables, code blocks and task
+/// list checkboxes survive, scripts, styles and event handlers do not
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tags(["input"])
+            .add_tag_attributes("input", ["checked", "disabled"])
+            .add_tag_attributes("code", ["class"])
+            .tag_attribute_values(HashMap::from([(
+                "input",
+                HashMap::from([("type", HashSet::from(["checkbox"]))]),
+            )]));
+        builder
+    })
+}
+
+/// Renders content to HTML that is safe to embed in a page
+pub fn render_html(content: &str, format: ContentFormat) -> String {
+    match format {
+        ContentFormat::Plain => content
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(|paragraph| {
+                format!(
+                    "<p>{}</p>\n",
+                    escape_html(paragraph).replace('\n', "<br>\n")
+                )
+            })
+            .collect(),
+        ContentFormat::Markdown => {
+            let mut unsafe_html = String::with_capacity(content.len() * 3 / 2);
+            html::push_html(
+                &mut unsafe_html,
+                Parser::new_ext(content, markdown_options()),
+            );
+            sanitizer().clean(&unsafe_html).to_string()
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Readable text of the content with markup removed
+pub fn plain_text(content: &str, format: ContentFormat) -> String {
+    match format {
+        ContentFormat::Plain => content.to_string(),
+        ContentFormat::Markdown => {
+            let mut text = String::with_capacity(content.len());
+            for event in Parser::new_ext(content, markdown_options()) {
+                match event {
+                    Event::Text(value) | Event::Code(value) => text.push_str(&value),
+                    Event::SoftBreak | Event::HardBreak | Event::Rule => text.push(' '),
+                    Event::End(
+                        TagEnd::Paragraph
+                        | TagEnd::Heading(_)
+                        | TagEnd::Item
+                        | TagEnd::TableCell
+                        | TagEnd::CodeBlock,
+                    ) => text.push(' '),
+                    _ => {}
+                }
+            }
+            text
+        }
+    }
+}
+
+/// First words of the text, cut at a word boundary
+pub fn excerpt(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut excerpt = String::new();
+
+    for word in &words {
+        let needed = if excerpt.is_empty() { 0 } else { 1 } + word.chars().count();
+        if excerpt.chars().count() + needed > EXCERPT_CHARS {
+            excerpt.push('…');
+            return excerpt;
+        }
+        if !excerpt.is_empty() {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+
+    excerpt
+}
+
+pub fn reading_time_minutes(text: &str) -> u32 {
+    text.split_whitespace()
+        .count()
+        .div_ceil(WORDS_PER_MINUTE)
+        .max(1) as u32
+}
+
+/// Everything derived from a post's content for display
+pub struct RenderedContent {
+    pub html: String,
+    pub excerpt: String,
+    pub reading_time_minutes: u32,
+}
+
+/// Derives the display fields of a post, reusing the cached HTML when there
+/// is one
+pub fn rendered(content: &str, format: &str, cached_html: Option<String>) -> RenderedContent {
+    let format = ContentFormat::parse(format).unwrap_or(ContentFormat::Plain);
+    let text = plain_text(content, format);
+
+    RenderedContent {
+        html: cached_html.unwrap_or_else(|| render_html(content, format)),
+        excerpt: excerpt(&text),
+        reading_time_minutes: reading_time_minutes(&text),
+    }
+}
+
+/// Delimiters `ts_headline` is asked to put around matches, see
+/// `headlines`. They are removed from the text beforehand, so any left in
+/// a headline are `ts_headline`'s own.
+const HEADLINE_START: char = '\u{2}';
+const HEADLINE_STOP: char = '\u{3}';
+
+/// Text search headlines are cut from: the plain text of the content,
+/// without the headline delimiters
+pub fn headline_text(content: &str, format: &str) -> String {
+    let format = ContentFormat::parse(format).unwrap_or(ContentFormat::Plain);
+    plain_text(content, format).replace([HEADLINE_START, HEADLINE_STOP], "")
+}
+
+/// HTML of a headline: the text escaped, with matches wrapped in `<mark>`
+fn headline_html(headline: &str) -> String {
+    escape_html(headline)
+        .replace(HEADLINE_START, "<mark>")
+        .replace(HEADLINE_STOP, "</mark>")
+}
+
+/// Cuts the parts of each text matching `ts_query` as HTML, in the order
+/// of `texts`, which come from `headline_text`
+pub async fn headlines(
+    conn: &mut PgConnection,
+    ts_query: &str,
+    texts: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    let headlines = sqlx::query_scalar!(
+        r#"
+        SELECT ts_headline('simple', source.text, to_tsquery('simple', $1),
+                           E'StartSel=\x02, StopSel=\x03, MaxWords=35, MinWords=15, MaxFragments=2')
+                   AS "headline!"
+        FROM UNNEST($2::text[]) WITH ORDINALITY AS source (text, position)
+        ORDER BY source.position
+        "#,
+        ts_query,
+        texts
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(headlines.iter().map(|headline| headline_html(headline)).collect())
+}
+
+/// Renders and stores the HTML of a post whose cached rendering was
+/// invalidated by the write that returned it
+pub async fn cache_html(conn: &mut PgConnection, post: &mut Post) -> Result<(), sqlx::Error> {
+    if post.content_html.is_some() {
+        return Ok(());
+    }
+
+    let format = ContentFormat::parse(&post.content_format).unwrap_or(ContentFormat::Plain);
+    let content_html = render_html(&post.content, format);
+
+    // A newer write may already have replaced the content
+    sqlx::query!(
+        "UPDATE posts SET content_html = $3
+         WHERE id = $1 AND updated_at = $2 AND content_html IS NULL",
+        post.id,
+        post.updated_at,
+        content_html
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    post.content_html = Some(content_html);
+    Ok(())
+}
+
+/// Renders posts that have no cached HTML yet, e.g. ones written before
+/// rendering existed. Runs once at startup.
+pub async fn render_missing_html(db: PgPool) {
+    let mut rendered = 0;
+    let mut last_id = 0;
+
+    loop {
+        let batch = match sqlx::query!(
+            "SELECT id, content, content_format, updated_at FROM posts
+             WHERE content_html IS NULL AND id > $1
+             ORDER BY id
+             LIMIT 100",
+            last_id
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(batch) => batch,
+            Err(e) => {
+                tracing::error!("Failed to load posts to render: {}", e);
+                return;
+            }
+        };
+
+        if batch.is_empty() {
+            break;
+        }
+
+        for post in batch {
+            last_id = post.id;
+            let format = ContentFormat::parse(&post.content_format).unwrap_or(ContentFormat::Plain);
+            let result = sqlx::query!(
+                "UPDATE posts SET content_html = $3
+                 WHERE id = $1 AND updated_at = $2 AND content_html IS NULL",
+                post.id,
+                post.updated_at,
+                render_html(&post.content, format)
+            )
+            .execute(&db)
+            .await;
+
+            match result {
+                Ok(result) if result.rows_affected() > 0 => rendered += 1,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::error!("Failed to store rendered post: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    if rendered > 0 {
+        tracing::info!("Rendered HTML for {} posts", rendered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(content: &str) -> String {
+        render_html(content, ContentFormat::Markdown)
+    }
+
+    #[test]
+    fn markdown_drops_scripts() {
+        let html = markdown("Hello\n\n<script>alert('x')</script>\n\nworld");
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("alert"), "{html}");
+        assert!(html.contains("<p>Hello</p>"), "{html}");
+    }
+
+    #[test]
+    fn markdown_drops_event_handlers() {
+        let html = markdown(
+            r#"<img src="plan.png" onerror="alert(1)"> <a href="/x" onclick="alert(2)">x</a>"#,
+        );
+        assert!(!html.contains("onerror"), "{html}");
+        assert!(!html.contains("onclick"), "{html}");
+        assert!(html.contains(r#"src="plan.png""#), "{html}");
+    }
+
+    #[test]
+    fn markdown_drops_javascript_links() {
+        let html = markdown("[click](javascript:alert(1))");
+        assert!(!html.contains("javascript:"), "{html}");
+        assert!(html.contains("click"), "{html}");
+    }
+
+    #[test]
+    fn markdown_keeps_task_list_checkboxes() {
+        let html = markdown("- [x] done\n- [ ] todo");
+        assert_eq!(html.matches(r#"type="checkbox""#).count(), 2, "{html}");
+        assert_eq!(html.matches("checked").count(), 1, "{html}");
+    }
+
+    #[test]
+    fn markdown_keeps_only_checkbox_inputs() {
+        let html =
+            markdown(r#"<input type="text" value="secret"> <input type="checkbox" checked>"#);
+        assert!(!html.contains(r#"type="text""#), "{html}");
+        assert!(!html.contains("secret"), "{html}");
+        assert!(html.contains(r#"type="checkbox""#), "{html}");
+    }
+
+    #[test]
+    fn plain_text_is_escaped_into_paragraphs() {
+        let html = render_html("a <b>&\nnext line\n\n\n\nsecond", ContentFormat::Plain);
+        assert_eq!(
+            html,
+            "<p>a &lt;b&gt;&amp;<br>\nnext line</p>\n<p>second</p>\n"
+        );
+    }
+
+    #[test]
+    fn plain_text_of_markdown_has_no_markup() {
+        let text = plain_text(
+            "# Title\n\nSome **bold** and `code`\n\n- one\n- two",
+            ContentFormat::Markdown,
+        );
+        assert_eq!(
+            text.split_whitespace().collect::<Vec<_>>(),
+            ["Title", "Some", "bold", "and", "code", "one", "two"]
+        );
+    }
+
+    #[test]
+    fn plain_text_of_plain_content_is_unchanged() {
+        assert_eq!(
+            plain_text("**not bold**", ContentFormat::Plain),
+            "**not bold**"
+        );
+    }
+
+    #[test]
+    fn headline_markup_comes_back_escaped() {
+        let text = headline_text(r#"<img src=x onerror="alert(1)"> a match"#, "plain");
+        let headline = headline_html(&text.replace("match", "\u{2}match\u{3}"));
+        assert_eq!(
+            headline,
+            "&lt;img src=x onerror=&quot;alert(1)&quot;&gt; a <mark>match</mark>"
+        );
+    }
+
+    #[test]
+    fn headline_of_markdown_is_plain_text() {
+        let text = headline_text("**bold** <script>alert(1)</script>\n\n<b>x</b>", "markdown");
+        assert!(!text.contains('<'), "{text}");
+        assert!(!text.contains('*'), "{text}");
+        assert!(text.contains("bold"), "{text}");
+    }
+
+    #[test]
+    fn content_cannot_forge_headline_marks() {
+        let text = headline_text("a \u{2}b\u{3} c", "plain");
+        assert_eq!(headline_html(&text), "a b c");
+    }
+
+    #[test]
+    fn short_text_is_its_own_excerpt() {
+        assert_eq!(excerpt("  a short\n text "), "a short text");
+    }
+
+    #[test]
+    fn long_text_is_cut_at_a_word_boundary() {
+        let text = "word ".repeat(100);
+        let excerpt = excerpt(&text);
+
+        assert!(excerpt.ends_with("word…"), "{excerpt}");
+        assert!(excerpt.chars().count() <= EXCERPT_CHARS + 1);
+        assert_eq!(excerpt.trim_end_matches('…').split(' ').count(), 40);
+    }
+
+    #[test]
+    fn excerpt_counts_characters_not_bytes() {
+        // 40 words of 4 characters fit in 200 characters but not in 200 bytes
+        let text = "Việt ".repeat(40);
+        assert!(!excerpt(&text).ends_with('…'));
+    }
+
+    #[test]
+    fn reading_time_is_at_least_a_minute() {
+        assert_eq!(reading_time_minutes(""), 1);
+        assert_eq!(reading_time_minutes(&"word ".repeat(200)), 1);
+        assert_eq!(reading_time_minutes(&"word ".repeat(201)), 2);
+    }
+}
@@ -0,0 +1,171 @@
+// Revision service so sánh nội dung giữa các revisions của post
+
+/// Largest number of changed line pairs compared by `diff_lines`
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+impl DiffOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffOp::Equal => "equal",
+            DiffOp::Insert => "insert",
+            DiffOp::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffLine<'a> {
+    pub op: DiffOp,
+    pub text: &'a str,
+}
+
+/// Line-based diff turning `old` into `new`, built from the longest common
+/// subsequence of lines. Returns `None` when the changed region is too
+/// large to compare.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Option<Vec<DiffLine<'a>>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Only the region between the common prefix and suffix needs the table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        return None;
+    }
+
+    // lcs[i * width + j] is the LCS length of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let line = |op, text| DiffLine { op, text };
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    lines.extend(old[..prefix].iter().map(|text| line(DiffOp::Equal, *text)));
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            lines.push(line(DiffOp::Equal, a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            lines.push(line(DiffOp::Delete, a[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffOp::Insert, b[j]));
+            j += 1;
+        }
+    }
+    lines.extend(a[i..].iter().map(|text| line(DiffOp::Delete, *text)));
+    lines.extend(b[j..].iter().map(|text| line(DiffOp::Insert, *text)));
+    lines.extend(old[old.len() - suffix..].iter().map(|text| line(DiffOp::Equal, *text)));
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops<'a>(lines: &[DiffLine<'a>]) -> Vec<(DiffOp, &'a str)> {
+        lines.iter().map(|line| (line.op, line.text)).collect()
+    }
+
+    /// Distinct lines, so that no two texts share any
+    fn numbered(prefix: &str, count: usize) -> String {
+        (0..count).map(|n| format!("{prefix}{n}\n")).collect()
+    }
+
+    #[test]
+    fn empty_texts_have_an_empty_diff() {
+        assert_eq!(diff_lines("", ""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn identical_texts_are_all_equal() {
+        let lines = diff_lines("a\nb\nc", "a\nb\nc").unwrap();
+        assert_eq!(
+            ops(&lines),
+            [(DiffOp::Equal, "a"), (DiffOp::Equal, "b"), (DiffOp::Equal, "c")]
+        );
+    }
+
+    #[test]
+    fn changed_lines_are_deleted_then_inserted() {
+        let lines = diff_lines("a\nb\nc\nd", "a\nx\nc\nd\ne").unwrap();
+        assert_eq!(
+            ops(&lines),
+            [
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Insert, "x"),
+                (DiffOp::Equal, "c"),
+                (DiffOp::Equal, "d"),
+                (DiffOp::Insert, "e"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_from_and_to_empty_text() {
+        assert_eq!(
+            ops(&diff_lines("", "a\nb").unwrap()),
+            [(DiffOp::Insert, "a"), (DiffOp::Insert, "b")]
+        );
+        assert_eq!(
+            ops(&diff_lines("a\nb", "").unwrap()),
+            [(DiffOp::Delete, "a"), (DiffOp::Delete, "b")]
+        );
+    }
+
+    #[test]
+    fn changed_region_up_to_the_cell_cap_is_compared() {
+        let old = numbered("old ", 2000);
+        let new = numbered("new ", 2000);
+        assert_eq!(2000 * 2000, MAX_DIFF_CELLS);
+
+        let lines = diff_lines(&old, &new).unwrap();
+        assert_eq!(lines.len(), 4000);
+    }
+
+    #[test]
+    fn changed_region_past_the_cell_cap_is_not_compared() {
+        let old = numbered("old ", 2001);
+        let new = numbered("new ", 2000);
+        assert_eq!(diff_lines(&old, &new), None);
+    }
+
+    #[test]
+    fn common_prefix_and_suffix_do_not_count_towards_the_cap() {
+        let shared = numbered("shared ", 5000);
+        let old = format!("{shared}old\n{shared}");
+        let new = format!("{shared}new\n{shared}");
+
+        let lines = diff_lines(&old, &new).unwrap();
+        let changed: Vec<_> = lines.iter().filter(|line| line.op != DiffOp::Equal).collect();
+        assert_eq!(changed.len(), 2);
+    }
+}
@@ -0,0 +1,72 @@
+// Scheduler service publish các posts đã được duyệt khi tới giờ publish_at
+
+use std::time::Duration;
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::services::notification_service;
+
+/// Publishes approved posts whose `publish_at` has passed.
+///
+/// Every instance of the server runs this loop. Due rows are claimed with
+/// `FOR UPDATE SKIP LOCKED` and only moved on while still `approved`, so
+/// each post is published exactly once however many instances race for it.
+pub async fn publish_scheduled_posts(db: PgPool, interval_seconds: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        let result = match db.begin().await {
+            Ok(mut tx) => match publish_due_posts(&mut tx).await {
+                Ok(count) => tx.commit().await.map(|_| count),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(count) if count > 0 => tracing::info!("Published {} scheduled posts", count),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to publish scheduled posts: {}", e),
+        }
+    }
+}
+
+/// Publishes a batch of due posts and notifies the users they mention.
+/// Returns how many posts were published.
+async fn publish_due_posts(conn: &mut PgConnection) -> Result<usize, sqlx::Error> {
+    let published = sqlx::query!(
+        r#"
+        WITH due AS (
+            SELECT id FROM posts
+            WHERE status = 'approved' AND publish_at <= NOW() AND deleted_at IS NULL
+            ORDER BY publish_at
+            LIMIT 100
+            FOR UPDATE SKIP LOCKED
+        ), published AS (
+            UPDATE posts
+            SET status = 'published',
+                published_at = NOW(),
+                publish_at = NULL,
+                updated_at = NOW()
+            FROM due
+            WHERE posts.id = due.id AND posts.status = 'approved'
+            RETURNING posts.id, posts.user_id, posts.title, posts.content
+        ), history AS (
+            INSERT INTO post_status_history (post_id, from_status, to_status, actor_id, comment)
+            SELECT id, 'approved', 'published', NULL, 'Scheduled publication' FROM published
+        )
+        SELECT id, user_id, title, content FROM published
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for post in &published {
+        let text = format!("{}\n{}", post.title, post.content);
+        notification_service::post_published(conn, post.id, post.user_id, &text).await?;
+    }
+
+    Ok(published.len())
+}
@@ -0,0 +1,260 @@
+// Slug service tạo slug từ tiêu đề (bỏ dấu tiếng Việt) và giữ lịch sử slug của posts
+
+use sqlx::PgConnection;
+
+use crate::database::models::Post;
+
+pub const MAX_SLUG_LENGTH: usize = 100;
+
+/// Slug used when a title has no letters or digits to build one from
+const FALLBACK_SLUG: &str = "post";
+
+/// Vietnamese letters with diacritics and the ASCII letter they fold to
+const VIETNAMESE_LETTERS: &[(&str, char)] = &[
+    ("àáảãạăằắẳẵặâầấẩẫậ", 'a'),
+    ("èéẻẽẹêềếểễệ", 'e'),
+    ("ìíỉĩị", 'i'),
+    ("òóỏõọôồốổỗộơờớởỡợ", 'o'),
+    ("ùúủũụưừứửữự", 'u'),
+    ("ỳýỷỹỵ", 'y'),
+    ("đ", 'd'),
+];
+
+fn fold_letter(c: char) -> Option<char> {
+    if c.is_ascii_alphanumeric() {
+        return Some(c);
+    }
+    VIETNAMESE_LETTERS
+        .iter()
+        .find(|(letters, _)| letters.contains(c))
+        .map(|(_, ascii)| *ascii)
+}
+
+/// Lowercase ASCII words of `title` joined by hyphens, e.g.
+/// "Xin chào Việt Nam!" becomes "xin-chao-viet-nam"
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    let mut pending_hyphen = false;
+
+    for c in title.chars().flat_map(char::to_lowercase) {
+        // Combining marks of decomposed text belong to the previous letter
+        if ('\u{0300}'..='\u{036f}').contains(&c) {
+            continue;
+        }
+        match fold_letter(c) {
+            Some(c) => {
+                if pending_hyphen && !slug.is_empty() {
+                    slug.push('-');
+                }
+                pending_hyphen = false;
+                slug.push(c);
+            }
+            None => pending_hyphen = true,
+        }
+    }
+
+    let slug = truncate(&slug, MAX_SLUG_LENGTH);
+    if slug.is_empty() {
+        FALLBACK_SLUG.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Whether `slug` is lowercase ASCII words joined by single hyphens
+pub fn is_valid_slug(slug: &str) -> bool {
+    slug.len() <= MAX_SLUG_LENGTH
+        && slug.split('-').all(|word| {
+            !word.is_empty()
+                && word.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        })
+}
+
+/// Cuts an ASCII slug to `max` bytes without leaving a trailing hyphen
+fn truncate(slug: &str, max: usize) -> &str {
+    slug[..slug.len().min(max)].trim_end_matches('-')
+}
+
+/// Makes `slug` the current slug of the post. Returns `false` when it
+/// belongs, or used to belong, to another post.
+pub async fn claim_slug(
+    conn: &mut PgConnection,
+    post_id: i32,
+    slug: &str,
+) -> Result<bool, sqlx::Error> {
+    // A slug the post had before is taken back, other posts' slugs are not
+    let claimed = sqlx::query_scalar!(
+        "WITH claimed AS (
+            INSERT INTO post_slugs (slug, post_id)
+            VALUES ($1, $2)
+            ON CONFLICT (slug) DO UPDATE SET created_at = NOW()
+            WHERE post_slugs.post_id = EXCLUDED.post_id
+            RETURNING slug
+        )
+        UPDATE posts SET slug = claimed.slug
+        FROM claimed
+        WHERE posts.id = $2
+        RETURNING posts.id",
+        slug,
+        post_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(claimed.is_some())
+}
+
+/// Gives the post a slug built from `title`, numbered `-2`, `-3`, … when
+/// the plain one is taken. Returns the slug.
+pub async fn assign_slug(
+    conn: &mut PgConnection,
+    post_id: i32,
+    title: &str,
+) -> Result<String, sqlx::Error> {
+    let base = slugify(title);
+    if claim_slug(conn, post_id, &base).await? {
+        return Ok(base);
+    }
+
+    // Continue after the highest number in use rather than probing from 2
+    let mut number = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(MAX(substring(slug FROM '-([0-9]{1,9})$')::int), 1) + 1 AS "number!"
+        FROM post_slugs
+        WHERE slug ~ ('^' || $1 || '-[0-9]{1,9}$')
+        "#,
+        base
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    loop {
+        let slug = numbered_slug(&base, number);
+        if claim_slug(conn, post_id, &slug).await? {
+            return Ok(slug);
+        }
+        number += 1;
+    }
+}
+
+/// `base` numbered with `-{number}`, shortened to keep within the length limit
+fn numbered_slug(base: &str, number: i32) -> String {
+    let suffix = format!("-{}", number);
+    format!("{}{}", truncate(base, MAX_SLUG_LENGTH - suffix.len()), suffix)
+}
+
+/// Whether `slug` is `base` or `base` numbered by `assign_slug`
+fn is_built_from(slug: &str, base: &str) -> bool {
+    slug == base
+        || slug
+            .strip_prefix(base)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .is_some_and(|number| number.parse::<u32>().is_ok())
+}
+
+/// Moves the slug of a post whose title changed from `previous_title` to
+/// one built from the new title. The old slug keeps pointing at the post.
+pub async fn follow_title(
+    conn: &mut PgConnection,
+    post: &mut Post,
+    previous_title: &str,
+) -> Result<(), sqlx::Error> {
+    if post.title == previous_title {
+        return Ok(());
+    }
+
+    // A slug already built from the new title, numbered or not, is kept
+    if !is_built_from(&post.slug, &slugify(&post.title)) {
+        post.slug = assign_slug(conn, post.id, &post.title).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vietnamese_diacritics_are_folded() {
+        assert_eq!(slugify("Xin chào Việt Nam!"), "xin-chao-viet-nam");
+        assert_eq!(slugify("Đường đi khó không vì ngăn sông"), "duong-di-kho-khong-vi-ngan-song");
+        assert_eq!(slugify("Bảo dưỡng máy ép thủy lực"), "bao-duong-may-ep-thuy-luc");
+        assert_eq!(slugify("ỨNG DỤNG ĐỘNG CƠ"), "ung-dung-dong-co");
+    }
+
+    #[test]
+    fn every_vietnamese_vowel_folds_to_ascii() {
+        for (letters, ascii) in VIETNAMESE_LETTERS {
+            for letter in letters.chars() {
+                assert_eq!(slugify(&letter.to_string()), ascii.to_string(), "{letter}");
+                let upper: String = letter.to_uppercase().collect();
+                assert_eq!(slugify(&upper), ascii.to_string(), "{upper}");
+            }
+        }
+    }
+
+    #[test]
+    fn decomposed_diacritics_are_folded() {
+        // "Việt" with the diacritics as combining marks
+        assert_eq!(slugify("Vie\u{0323}\u{0302}t"), "viet");
+    }
+
+    #[test]
+    fn punctuation_and_spaces_become_single_hyphens() {
+        assert_eq!(slugify("  Hello,   world -- 2024!  "), "hello-world-2024");
+    }
+
+    #[test]
+    fn titles_without_letters_fall_back() {
+        assert_eq!(slugify("!!! ???"), FALLBACK_SLUG);
+        assert_eq!(slugify("日本語"), FALLBACK_SLUG);
+    }
+
+    #[test]
+    fn long_titles_are_cut_without_a_trailing_hyphen() {
+        let slug = slugify(&format!("{} tail", "a".repeat(MAX_SLUG_LENGTH - 1)));
+        assert_eq!(slug, "a".repeat(MAX_SLUG_LENGTH - 1));
+        assert!(is_valid_slug(&slug));
+    }
+
+    #[test]
+    fn slugs_are_valid() {
+        assert!(is_valid_slug("xin-chao-2"));
+        assert!(!is_valid_slug("Xin-chao"));
+        assert!(!is_valid_slug("xin--chao"));
+        assert!(!is_valid_slug("-xin"));
+        assert!(!is_valid_slug(""));
+        assert!(!is_valid_slug(&"a".repeat(MAX_SLUG_LENGTH + 1)));
+    }
+
+    #[test]
+    fn colliding_slugs_are_numbered() {
+        assert_eq!(numbered_slug("xin-chao", 2), "xin-chao-2");
+        assert_eq!(numbered_slug("xin-chao", 10), "xin-chao-10");
+    }
+
+    #[test]
+    fn numbered_slugs_stay_within_the_length_limit() {
+        let base = format!("{}-b", "a".repeat(MAX_SLUG_LENGTH - 2));
+        let slug = numbered_slug(&base, 2);
+
+        assert_eq!(slug, format!("{}-2", "a".repeat(MAX_SLUG_LENGTH - 2)));
+        assert!(is_valid_slug(&slug));
+
+        // Cutting must not leave two hyphens before the number
+        let base = format!("{}-bb", "a".repeat(MAX_SLUG_LENGTH - 4));
+        let slug = numbered_slug(&base, 12);
+        assert!(is_valid_slug(&slug), "{slug}");
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+    }
+
+    #[test]
+    fn numbered_slugs_are_built_from_their_title() {
+        assert!(is_built_from("xin-chao", "xin-chao"));
+        assert!(is_built_from("xin-chao-3", "xin-chao"));
+        assert!(!is_built_from("xin-chao-ban", "xin-chao"));
+        assert!(!is_built_from("xin-chao-", "xin-chao"));
+        assert!(!is_built_from("xin", "xin-chao"));
+    }
+}
@@ -0,0 +1,64 @@
+// Tag service chuẩn hoá tên tag và gắn tags vào posts
+
+use sqlx::PgConnection;
+
+pub const MAX_TAG_LENGTH: usize = 50;
+pub const MAX_TAGS_PER_POST: usize = 10;
+
+/// Canonical form of a tag name: trimmed, lowercase, single spaces
+pub fn normalize_tag(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Normalized, deduplicated tag names in the order given
+pub fn normalize_tags(names: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::with_capacity(names.len());
+    for tag in names.iter().map(|name| normalize_tag(name)) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Replaces the tags of a post, creating tags that do not exist yet.
+/// Returns the tag names of the post, sorted.
+pub async fn set_post_tags(
+    conn: &mut PgConnection,
+    post_id: i32,
+    names: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    let names = normalize_tags(names);
+
+    sqlx::query_scalar!(
+        r#"
+        WITH input AS (
+            SELECT DISTINCT unnest($2::varchar[]) AS name
+        ), inserted AS (
+            INSERT INTO tags (name)
+            SELECT name FROM input
+            ON CONFLICT (name) DO NOTHING
+            RETURNING id, name
+        ), wanted AS (
+            SELECT id, name FROM inserted
+            UNION
+            SELECT tags.id, tags.name FROM tags JOIN input ON input.name = tags.name
+        ), removed AS (
+            DELETE FROM post_tags
+            WHERE post_id = $1 AND tag_id NOT IN (SELECT id FROM wanted)
+        ), added AS (
+            INSERT INTO post_tags (post_id, tag_id)
+            SELECT $1, id FROM wanted
+            ON CONFLICT DO NOTHING
+        )
+        SELECT name AS "name!" FROM wanted ORDER BY name
+        "#,
+        post_id,
+        &names
+    )
+    .fetch_all(conn)
+    .await
+}
@@ -0,0 +1,92 @@
+// Trash service dọn dẹp users và posts đã bị soft delete quá hạn
+
+use std::time::Duration;
+
+use sqlx::{PgConnection, PgPool};
+
+/// Permanently deletes trashed users and posts once their retention period
+/// has passed. Runs hourly for the lifetime of the server.
+pub async fn purge_expired_trash(db: PgPool, retention_days: u32) {
+    let mut interval = tokio::time::interval(Duration::from_secs(3600));
+
+    loop {
+        interval.tick().await;
+
+        match purge(&db, retention_days).await {
+            Ok((posts, users)) if posts > 0 || users > 0 => {
+                tracing::info!("Purged {} posts and {} users from the trash", posts, users);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to purge trash: {}", e),
+        }
+    }
+}
+
+/// Deletes expired posts and users, returning how many of each
+async fn purge(db: &PgPool, retention_days: u32) -> Result<(u64, u64), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let posts = sqlx::query!(
+        "DELETE FROM posts WHERE deleted_at < NOW() - make_interval(days => $1)",
+        retention_days as i32
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let user_ids = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE deleted_at < NOW() - make_interval(days => $1) FOR UPDATE",
+        retention_days as i32
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let users = delete_users(&mut tx, &user_ids).await?;
+
+    tx.commit().await?;
+
+    Ok((posts, users))
+}
+
+/// Deletes users for good. Their comments, reactions and bookmarks go with
+/// them, and with the comments the replies of other users, so the posts
+/// they were on are counted again.
+pub async fn delete_users(conn: &mut PgConnection, user_ids: &[i32]) -> Result<u64, sqlx::Error> {
+    let post_ids = sqlx::query_scalar!(
+        r#"
+        SELECT post_id AS "post_id!" FROM comments WHERE user_id = ANY($1)
+        UNION SELECT post_id FROM post_reactions WHERE user_id = ANY($1)
+        UNION SELECT post_id FROM post_bookmarks WHERE user_id = ANY($1)
+        "#,
+        user_ids
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let deleted = sqlx::query!("DELETE FROM users WHERE id = ANY($1)", user_ids)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    // Same rule as the comment endpoints: live comments that are not hidden
+    sqlx::query!(
+        r#"
+        UPDATE posts p
+        SET comment_count = (
+                SELECT COUNT(*) FROM comments c
+                WHERE c.post_id = p.id AND c.deleted_at IS NULL AND NOT c.is_hidden
+            )::int,
+            reaction_counts = COALESCE((
+                SELECT jsonb_object_agg(kind, count)
+                FROM (SELECT kind, COUNT(*) AS count FROM post_reactions r
+                      WHERE r.post_id = p.id GROUP BY kind) counts
+            ), '{}'),
+            bookmark_count = (SELECT COUNT(*) FROM post_bookmarks b WHERE b.post_id = p.id)::int
+        WHERE p.id = ANY($1)
+        "#,
+        &post_ids
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(deleted)
+}
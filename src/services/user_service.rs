@@ -1,12 +1,48 @@
 // User service sẽ chứa business logic cho users
 // Tạm thời để trống, sẽ implement sau
 
+#[allow(dead_code)]
 pub struct UserService;
 
+#[allow(dead_code)]
 impl UserService {
     pub fn new() -> Self {
         Self
     }
     
     // Business logic methods sẽ được thêm vào đây
-}
\ No newline at end of file
+}
+
+/// Makes the account with this email an admin, creating it with the given
+/// password when it does not exist. An existing account keeps its password
+/// unless it has none that can be signed in with, such as the seeded admin.
+pub async fn bootstrap_admin(
+    db: &sqlx::PgPool,
+    email: &str,
+    password: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let password_hash = bcrypt::hash(password, 10)?;
+    let username = email.split('@').next().unwrap_or(email);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO users (email, username, password_hash, role)
+        VALUES ($1, $2, $3, 'admin')
+        ON CONFLICT (email) DO UPDATE
+        SET role = 'admin',
+            password_hash = CASE
+                WHEN users.password_hash LIKE '$2%' THEN users.password_hash
+                ELSE EXCLUDED.password_hash
+            END
+        "#,
+        email,
+        username,
+        password_hash
+    )
+    .execute(db)
+    .await?;
+
+    tracing::info!("{} is an admin", email);
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::{Storage, StorageError};
+
+/// Stores objects as files below a root directory
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: &str) -> Self {
+        Self { root: PathBuf::from(root) }
+    }
+
+    /// Keys are generated by the server, but never let one leave the root
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        if key.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return Err(StorageError::Backend(format!("Invalid key: {}", key)));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+fn backend_error(e: std::io::Error) -> StorageError {
+    match e.kind() {
+        ErrorKind::NotFound => StorageError::NotFound,
+        _ => StorageError::Backend(e.to_string()),
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(backend_error)?;
+        }
+
+        // Written under a temporary name first so readers never see half a file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, &data).await.map_err(backend_error)?;
+        tokio::fs::rename(&partial, &path).await.map_err(backend_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError> {
+        let data = tokio::fs::read(self.path(key)?).await.map_err(backend_error)?;
+        Ok(Bytes::from(data))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(backend_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Storage in a directory of its own, removed when dropped
+    struct TempStorage {
+        storage: LocalStorage,
+    }
+
+    impl TempStorage {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir()
+                .join(format!("local-storage-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            Self {
+                storage: LocalStorage { root },
+            }
+        }
+    }
+
+    impl Drop for TempStorage {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.storage.root);
+        }
+    }
+
+    #[tokio::test]
+    async fn objects_round_trip() {
+        let temp = TempStorage::new("round-trip");
+        let storage = &temp.storage;
+        let key = "attachments/1/file.png";
+
+        storage.put(key, Bytes::from_static(b"first"), "image/png").await.unwrap();
+        storage.put(key, Bytes::from_static(b"second"), "image/png").await.unwrap();
+        assert_eq!(storage.get(key).await.unwrap(), Bytes::from_static(b"second"));
+        assert!(!storage.root.join("attachments/1/file.partial").exists());
+
+        storage.delete(key).await.unwrap();
+        assert!(matches!(storage.get(key).await, Err(StorageError::NotFound)));
+        // Deleting again is not an error
+        storage.delete(key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn keys_stay_below_the_root() {
+        let temp = TempStorage::new("keys");
+        let storage = &temp.storage;
+
+        for key in ["../escape", "a/../../escape", "./a", "a//b", "/etc/passwd", ""] {
+            let result = storage.put(key, Bytes::from_static(b"x"), "text/plain").await;
+            assert!(matches!(result, Err(StorageError::Backend(_))), "{key}");
+            assert!(matches!(storage.get(key).await, Err(StorageError::Backend(_))), "{key}");
+            assert!(matches!(storage.delete(key).await, Err(StorageError::Backend(_))), "{key}");
+        }
+    }
+}
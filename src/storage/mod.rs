@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::config::AppConfig;
+
+pub mod local;
+pub mod s3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error("object not found")]
+    NotFound,
+    #[error("{0}")]
+    Backend(String),
+}
+
+/// Blob store for uploaded files, addressed by slash-separated keys
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError>;
+
+    /// Deleting a missing object succeeds
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Backend selected by `STORAGE_BACKEND`
+pub async fn from_config(config: &AppConfig) -> Result<Arc<dyn Storage>, Box<dyn std::error::Error>> {
+    match config.storage_backend.as_str() {
+        "local" => Ok(Arc::new(local::LocalStorage::new(&config.storage_local_dir))),
+        "s3" => {
+            let bucket = config
+                .s3_bucket
+                .clone()
+                .ok_or("S3_BUCKET is required when STORAGE_BACKEND=s3")?;
+            Ok(Arc::new(
+                s3::S3Storage::new(bucket, config.s3_endpoint.clone(), config.s3_region.clone()).await,
+            ))
+        }
+        other => Err(format!("Unknown STORAGE_BACKEND: {}", other).into()),
+    }
+}
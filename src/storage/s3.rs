@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{error::SdkError, primitives::ByteStream, Client};
+use bytes::Bytes;
+
+use super::{Storage, StorageError};
+
+/// Stores objects in an S3 bucket. With an endpoint it talks to any
+/// S3-compatible service, such as MinIO, using path-style addressing.
+/// Credentials come from the usual AWS environment variables and profiles.
+pub struct S3Storage {
+    client: Client,
+    bucket: String,
+}
+
+impl S3Storage {
+    pub async fn new(bucket: String, endpoint: Option<String>, region: String) -> Self {
+        let shared = aws_config::from_env()
+            .region(aws_config::Region::new(region))
+            .load()
+            .await;
+
+        let mut config = aws_sdk_s3::config::Builder::from(&shared);
+        if let Some(endpoint) = endpoint {
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+
+        Self {
+            client: Client::from_conf(config.build()),
+            bucket,
+        }
+    }
+}
+
+fn backend_error<E: std::fmt::Debug>(e: E) -> StorageError {
+    StorageError::Backend(format!("{:?}", e))
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .map_err(backend_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Bytes, StorageError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| match &e {
+                SdkError::ServiceError(service) if service.err().is_no_such_key() => {
+                    StorageError::NotFound
+                }
+                _ => backend_error(e),
+            })?;
+
+        let data = object.body.collect().await.map_err(backend_error)?;
+        Ok(data.into_bytes())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        // S3 answers deletes of missing keys with success as well
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(backend_error)?;
+        Ok(())
+    }
+}